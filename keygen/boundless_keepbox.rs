@@ -9,65 +9,37 @@
 // - Zeroization of sensitive data
 // - File permissions (0600)
 // - Password strength validation
-
-use aes_gcm::{
-    aead::{Aead, KeyInit},
-    Aes256Gcm, Nonce,
+//
+// The encryption and file formats live in the `boundless_wallet::keepbox`
+// and `boundless_wallet::vault` library modules; this binary handles
+// prompting and presentation. `agent start` keeps unlocked keys in a
+// background key agent (`boundless_wallet::agent`) so `boundless-wallet sign`
+// and `tx sign` need no password while it runs.
+
+#[cfg(unix)]
+use boundless_wallet::agent::{self, Agent, AgentClient, StopReason};
+use boundless_wallet::files;
+use boundless_wallet::keepbox::{FormatVersion, KdfCost, KdfPreset};
+#[cfg(unix)]
+use boundless_wallet::keys::HeldKey;
+#[cfg(unix)]
+use boundless_wallet::prompt::unlock_keepboxes;
+use boundless_wallet::output::{self, OutputFormat};
+use boundless_wallet::prompt::{
+    passphrase_for, prompt_bip39_passphrase, prompt_password, set_password_source, PasswordArgs,
+};
+use boundless_wallet::vault::EntryMetadata;
+use boundless_wallet::{
+    say, Derivation, Error, KeepBox, KeepBoxFile, KeyType, Language, Mnemonic, Result, Vault, WalletData,
 };
-use argon2::{Argon2, ParamsBuilder, Version};
-use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
-use bip39::{Language, Mnemonic};
 use clap::{Parser, Subcommand};
-use ed25519_dalek::SigningKey;
-use rpassword::read_password;
-use serde::{Deserialize, Serialize};
-use sha3::{Digest, Sha3_256};
+use serde_json::{json, Value};
 use std::fs;
-use std::io::{self, Write};
-use std::path::PathBuf;
-use zeroize::{Zeroize, ZeroizeOnDrop};
-
-// ===== Data Structures =====
-
-#[derive(Serialize, Deserialize)]
-struct KeepBox {
-    version: String,
-    crypto: CryptoParams,
-    encrypted_data: String, // Base64 encoded
-    metadata: Metadata,
-}
-
-#[derive(Serialize, Deserialize)]
-struct CryptoParams {
-    cipher: String,
-    kdf: String,
-    kdf_params: KdfParams,
-    nonce: String, // Base64 encoded (12 bytes for GCM)
-}
-
-#[derive(Serialize, Deserialize)]
-struct KdfParams {
-    memory_cost: u32,
-    time_cost: u32,
-    parallelism: u32,
-    salt: String, // Base64 encoded (32 bytes)
-}
-
-#[derive(Serialize, Deserialize)]
-struct Metadata {
-    created: String,
-    modified: String,
-    label: Option<String>,
-    address: String,
-}
-
-#[derive(Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
-struct WalletData {
-    mnemonic: String,
-    public_key: String,
-    address: String,
-    key_type: String,
-}
+use std::io;
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::time::Duration;
+use zeroize::Zeroizing;
 
 // ===== CLI Structure =====
 
@@ -77,6 +49,13 @@ struct WalletData {
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    #[command(flatten)]
+    password: PasswordArgs,
+
+    /// Output format: text, or json for one machine-readable document on stdout
+    #[arg(long, global = true, value_name = "FORMAT", default_value = "text")]
+    output_format: OutputFormat,
 }
 
 #[derive(Subcommand)]
@@ -91,16 +70,33 @@ enum Commands {
         #[arg(short, long)]
         output: PathBuf,
 
+        /// Replace an existing output file, moving it to a timestamped backup first
+        #[arg(long)]
+        force: bool,
+
         /// Optional label for the wallet
         #[arg(short, long)]
         label: Option<String>,
+
+        /// Prompt for the BIP39 passphrase the wallet was generated with
+        #[arg(long)]
+        bip39_passphrase: bool,
+
+        #[command(flatten)]
+        kdf: KdfArgs,
     },
 
-    /// Open and display wallet information (without secrets)
+    /// Open and display wallet information (without secrets); lists every
+    /// address in a vault
     Open {
         /// KeepBox file to open
         #[arg(short, long)]
         keepbox: PathBuf,
+
+        /// Decrypt (prompts for password) to confirm the displayed metadata
+        /// belongs to the encrypted wallet
+        #[arg(long)]
+        decrypt: bool,
     },
 
     /// Export wallet to JSON (requires password)
@@ -113,6 +109,14 @@ enum Commands {
         #[arg(short, long)]
         output: PathBuf,
 
+        /// Replace an existing output file, moving it to a timestamped backup first
+        #[arg(long)]
+        force: bool,
+
+        /// Vault entry to export (label or address)
+        #[arg(short, long)]
+        entry: Option<String>,
+
         /// Show private key in export (DANGEROUS)
         #[arg(long, default_value_t = false)]
         show_private: bool,
@@ -120,21 +124,86 @@ enum Commands {
 
     /// Import wallet from mnemonic or JSON into KeepBox
     Import {
-        /// Mnemonic phrase (if not provided, will prompt)
-        #[arg(short, long)]
-        mnemonic: Option<String>,
-
-        /// Or import from JSON file
-        #[arg(short, long)]
-        json: Option<PathBuf>,
+        #[command(flatten)]
+        source: WalletSourceArgs,
 
         /// Output KeepBox file
         #[arg(short, long)]
         output: PathBuf,
 
+        /// Replace an existing output file, moving it to a timestamped backup first
+        #[arg(long)]
+        force: bool,
+
         /// Optional label for the wallet
         #[arg(short, long)]
         label: Option<String>,
+
+        #[command(flatten)]
+        kdf: KdfArgs,
+    },
+
+    /// Add a wallet to a multi-wallet vault, creating the vault if needed
+    Add {
+        /// Vault file
+        #[arg(short, long)]
+        keepbox: PathBuf,
+
+        /// Label for the new entry (unique within the vault)
+        #[arg(short, long)]
+        label: String,
+
+        /// Take the wallet from an existing single-wallet KeepBox
+        #[arg(long, conflicts_with_all = ["mnemonic", "json"])]
+        from_keepbox: Option<PathBuf>,
+
+        #[command(flatten)]
+        source: WalletSourceArgs,
+
+        /// Label for the vault itself when creating it
+        #[arg(long)]
+        vault_label: Option<String>,
+
+        /// Argon2id cost when creating the vault
+        #[command(flatten)]
+        kdf: KdfArgs,
+    },
+
+    /// Remove a wallet from a vault
+    Remove {
+        /// Vault file
+        #[arg(short, long)]
+        keepbox: PathBuf,
+
+        /// Entry to remove (label or address)
+        #[arg(short, long)]
+        entry: String,
+
+        /// Do not ask for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+
+    /// List the wallets in a vault (no password needed)
+    List {
+        /// Vault file
+        #[arg(short, long)]
+        keepbox: PathBuf,
+    },
+
+    /// Change the label of a vault entry
+    Rename {
+        /// Vault file
+        #[arg(short, long)]
+        keepbox: PathBuf,
+
+        /// Entry to rename (label or address)
+        #[arg(short, long)]
+        entry: String,
+
+        /// New label
+        #[arg(short, long)]
+        new_label: String,
     },
 
     /// Change KeepBox password
@@ -142,6 +211,14 @@ enum Commands {
         /// KeepBox file
         #[arg(short, long)]
         keepbox: PathBuf,
+
+        /// Argon2id cost for the re-encrypted file (default: keep current)
+        #[command(flatten)]
+        kdf: KdfArgs,
+
+        /// Do not keep a timestamped copy of the file under the old password
+        #[arg(long)]
+        no_backup: bool,
     },
 
     /// Verify KeepBox integrity and password
@@ -150,628 +227,1181 @@ enum Commands {
         #[arg(short, long)]
         keepbox: PathBuf,
     },
-}
 
-// ===== Encryption Functions =====
-
-fn derive_key_from_password(password: &str, salt: &[u8]) -> Result<[u8; 32], String> {
-    // Argon2id parameters matching ENCRYPTED_KEYSTORE_DESIGN.md
-    let params = ParamsBuilder::new()
-        .m_cost(65536) // 64 MB
-        .t_cost(3)     // 3 iterations
-        .p_cost(4)     // 4 parallelism
-        .build()
-        .map_err(|e| format!("Failed to build Argon2 params: {}", e))?;
-
-    let argon2 = Argon2::new(
-        argon2::Algorithm::Argon2id,
-        Version::V0x13,
-        params,
-    );
-
-    // Derive 32-byte key
-    let mut key = [0u8; 32];
-    argon2
-        .hash_password_into(password.as_bytes(), salt, &mut key)
-        .map_err(|e| format!("Failed to derive key: {}", e))?;
-
-    Ok(key)
-}
-
-fn encrypt_wallet_data(
-    wallet_data: &WalletData,
-    password: &str,
-) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>), String> {
-    // Generate random salt (32 bytes)
-    let mut salt = [0u8; 32];
-    getrandom::getrandom(&mut salt).map_err(|e| format!("Failed to generate salt: {}", e))?;
-
-    // Derive encryption key
-    let key = derive_key_from_password(password, &salt)?;
-
-    // Generate random nonce (12 bytes for GCM)
-    let mut nonce_bytes = [0u8; 12];
-    getrandom::getrandom(&mut nonce_bytes)
-        .map_err(|e| format!("Failed to generate nonce: {}", e))?;
-
-    // Create cipher
-    let cipher = Aes256Gcm::new_from_slice(&key)
-        .map_err(|e| format!("Failed to create cipher: {}", e))?;
-
-    // Serialize wallet data
-    let plaintext = serde_json::to_string(wallet_data)
-        .map_err(|e| format!("Failed to serialize wallet data: {}", e))?;
-
-    // Encrypt
-    let nonce = Nonce::from_slice(&nonce_bytes);
-    let ciphertext = cipher
-        .encrypt(nonce, plaintext.as_bytes())
-        .map_err(|e| format!("Encryption failed: {}", e))?;
-
-    Ok((ciphertext, salt.to_vec(), nonce_bytes.to_vec()))
-}
-
-fn decrypt_wallet_data(
-    ciphertext: &[u8],
-    password: &str,
-    salt: &[u8],
-    nonce: &[u8],
-) -> Result<WalletData, String> {
-    // Derive decryption key
-    let key = derive_key_from_password(password, salt)?;
-
-    // Create cipher
-    let cipher = Aes256Gcm::new_from_slice(&key)
-        .map_err(|e| format!("Failed to create cipher: {}", e))?;
-
-    // Decrypt
-    let nonce = Nonce::from_slice(nonce);
-    let plaintext_bytes = cipher
-        .decrypt(nonce, ciphertext)
-        .map_err(|_| "Decryption failed - incorrect password or corrupted data".to_string())?;
-
-    // Deserialize
-    let wallet_data: WalletData = serde_json::from_slice(&plaintext_bytes)
-        .map_err(|e| format!("Failed to deserialize wallet data: {}", e))?;
+    /// Upgrade a KeepBox to the current file format in place
+    Migrate {
+        /// KeepBox file to upgrade
+        #[arg(short, long)]
+        keepbox: PathBuf,
+
+        /// Do not keep a copy of the original file
+        #[arg(long)]
+        no_backup: bool,
+    },
 
-    Ok(wallet_data)
+    /// Key agent holding unlocked wallets for `boundless-wallet sign` and
+    /// `tx sign`
+    #[cfg(unix)]
+    Agent {
+        #[command(subcommand)]
+        command: AgentCommands,
+    },
 }
 
-// ===== Password Functions =====
+#[cfg(unix)]
+#[derive(Subcommand)]
+enum AgentCommands {
+    /// Unlock KeepBoxes once and serve their keys in the background
+    Start {
+        /// KeepBox files to unlock (every wallet of a vault is loaded)
+        #[arg(short, long, required = true)]
+        keepbox: Vec<PathBuf>,
+
+        /// Socket path (default: $XDG_RUNTIME_DIR/boundless-agent.sock)
+        #[arg(long)]
+        socket: Option<PathBuf>,
+
+        /// Wipe the keys and exit after this many seconds without a request
+        #[arg(long, default_value_t = agent::DEFAULT_IDLE_TIMEOUT.as_secs())]
+        idle_timeout: u64,
+
+        /// Stay in the foreground instead of forking
+        #[arg(long)]
+        foreground: bool,
+    },
 
-fn validate_password_strength(password: &str) -> Result<(), String> {
-    if password.len() < 12 {
-        return Err("Password must be at least 12 characters long".to_string());
-    }
+    /// List the keys a running agent holds
+    List {
+        /// Socket path (default: $BOUNDLESS_AGENT_SOCK)
+        #[arg(long)]
+        socket: Option<PathBuf>,
+    },
 
-    let has_lowercase = password.chars().any(|c| c.is_lowercase());
-    let has_uppercase = password.chars().any(|c| c.is_uppercase());
-    let has_digit = password.chars().any(|c| c.is_numeric());
-    let has_special = password.chars().any(|c| !c.is_alphanumeric());
+    /// Wipe one key, or all of them, from a running agent; it exits when
+    /// none are left
+    Lock {
+        /// Address of the key to wipe (default: every key)
+        #[arg(short, long)]
+        address: Option<String>,
 
-    let strength_score = has_lowercase as u8
-        + has_uppercase as u8
-        + has_digit as u8
-        + has_special as u8;
+        /// Socket path (default: $BOUNDLESS_AGENT_SOCK)
+        #[arg(long)]
+        socket: Option<PathBuf>,
+    },
+}
 
-    if strength_score < 3 {
-        return Err(
-            "Password must contain at least 3 of: lowercase, uppercase, digits, special characters"
-                .to_string(),
-        );
-    }
+/// Wallet input shared by `import` and `add`
+#[derive(clap::Args)]
+struct WalletSourceArgs {
+    /// Mnemonic phrase (if not provided, will prompt)
+    #[arg(short, long)]
+    mnemonic: Option<String>,
 
-    // Check for common weak passwords
-    let weak_passwords = vec![
-        "password123",
-        "qwerty123456",
-        "admin123456",
-        "123456789012",
-    ];
-    if weak_passwords.contains(&password.to_lowercase().as_str()) {
-        return Err("Password is too common, please choose a stronger password".to_string());
-    }
+    /// Or import from JSON file
+    #[arg(short, long)]
+    json: Option<PathBuf>,
 
-    Ok(())
-}
+    /// Derivation path for mnemonic imports: "legacy" (default) or a
+    /// hardened SLIP-0010 path such as "m/44'/777'/0'/0'"
+    #[arg(long, conflicts_with_all = ["account", "json"])]
+    path: Option<Derivation>,
 
-fn prompt_password(prompt: &str, confirm: bool) -> Result<String, String> {
-    loop {
-        print!("{}", prompt);
-        io::stdout()
-            .flush()
-            .map_err(|e| format!("Failed to flush stdout: {}", e))?;
+    /// Boundless account index for mnemonic imports
+    #[arg(long, conflicts_with = "json")]
+    account: Option<u32>,
 
-        let password = read_password()
-            .map_err(|e| format!("Failed to read password: {}", e))?;
+    /// Key type for mnemonic imports: ed25519 (default), ml-dsa-44 or hybrid
+    #[arg(short = 't', long, default_value = "ed25519", conflicts_with = "json")]
+    key_type: KeyType,
 
-        if password.is_empty() {
-            eprintln!("❌ Password cannot be empty");
-            continue;
-        }
+    /// Mnemonic wordlist, e.g. japanese (detected from the words if not given)
+    #[arg(long, conflicts_with = "json")]
+    language: Option<Language>,
+
+    /// Prompt for the BIP39 passphrase the wallet was generated with
+    #[arg(long)]
+    bip39_passphrase: bool,
+}
 
-        if let Err(e) = validate_password_strength(&password) {
-            eprintln!("❌ {}", e);
-            continue;
+impl WalletSourceArgs {
+    /// Load the wallet from JSON, the given mnemonic, or a prompted mnemonic
+    fn load(self) -> Result<WalletData> {
+        let derivation = Derivation::from_options(self.path, self.account)?;
+
+        let passphrase = if self.bip39_passphrase {
+            // Confirm when deriving fresh keys; JSON imports are checked
+            // against the stored address instead
+            prompt_bip39_passphrase(self.json.is_none())?
+        } else {
+            Zeroizing::new(String::new())
+        };
+
+        if let Some(json) = self.json {
+            // Import from JSON
+            load_wallet_json(&json, &passphrase)
+        } else if let Some(mnemonic_phrase) = self.mnemonic {
+            // Import from mnemonic
+            restore_from_mnemonic(&mnemonic_phrase, self.language, &passphrase, self.key_type, &derivation)
+        } else {
+            // Prompt for mnemonic
+            output::prompt("Enter your mnemonic phrase (12-24 words):\n> ")?;
+
+            let mut mnemonic_input = String::new();
+            io::stdin()
+                .read_line(&mut mnemonic_input)
+                .map_err(|e| Error::io("Failed to read input", e))?;
+
+            restore_from_mnemonic(mnemonic_input.trim(), self.language, &passphrase, self.key_type, &derivation)
         }
+    }
+}
 
-        if confirm {
-            print!("Confirm password: ");
-            io::stdout()
-                .flush()
-                .map_err(|e| format!("Failed to flush stdout: {}", e))?;
+/// Argon2id cost options for commands that encrypt
+#[derive(clap::Args)]
+struct KdfArgs {
+    /// KDF cost preset: interactive, standard (default) or paranoid
+    #[arg(long)]
+    kdf_preset: Option<KdfPreset>,
 
-            let password2 = read_password()
-                .map_err(|e| format!("Failed to read password: {}", e))?;
+    /// Argon2id memory in KiB (overrides the preset)
+    #[arg(long)]
+    kdf_memory: Option<u32>,
 
-            if password != password2 {
-                eprintln!("❌ Passwords do not match");
-                continue;
-            }
+    /// Argon2id iterations (overrides the preset)
+    #[arg(long)]
+    kdf_time: Option<u32>,
+
+    /// Argon2id lanes (overrides the preset)
+    #[arg(long)]
+    kdf_parallelism: Option<u32>,
+}
+
+impl KdfArgs {
+    /// Requested cost, or `None` if no KDF option was given
+    fn resolve(&self) -> Result<Option<KdfCost>> {
+        if self.kdf_preset.is_none()
+            && self.kdf_memory.is_none()
+            && self.kdf_time.is_none()
+            && self.kdf_parallelism.is_none()
+        {
+            return Ok(None);
         }
 
-        return Ok(password);
+        let mut cost = self.kdf_preset.unwrap_or_default().cost();
+        cost.memory_cost = self.kdf_memory.unwrap_or(cost.memory_cost);
+        cost.time_cost = self.kdf_time.unwrap_or(cost.time_cost);
+        cost.parallelism = self.kdf_parallelism.unwrap_or(cost.parallelism);
+        cost.validate()?;
+        Ok(Some(cost))
     }
 }
 
 // ===== Wallet Functions =====
 
-fn generate_mnemonic() -> Mnemonic {
-    let mut entropy = [0u8; 32];
-    getrandom::getrandom(&mut entropy).expect("Failed to generate entropy");
-    Mnemonic::from_entropy(&entropy).expect("Failed to create mnemonic")
+fn restore_from_mnemonic(
+    mnemonic_phrase: &str,
+    language: Option<Language>,
+    passphrase: &str,
+    key_type: KeyType,
+    derivation: &Derivation,
+) -> Result<WalletData> {
+    let mnemonic = Mnemonic::parse_with(language, mnemonic_phrase)?;
+    WalletData::from_mnemonic(&mnemonic, passphrase, key_type, derivation)
 }
 
-fn derive_address(public_key: &[u8]) -> String {
-    let mut hasher = Sha3_256::new();
-    hasher.update(public_key);
-    let hash = hasher.finalize();
-    hex::encode(&hash)
+/// Load a plaintext wallet file and check its keys derive from its
+/// mnemonic under `passphrase`
+fn load_wallet_json(path: &PathBuf, passphrase: &str) -> Result<WalletData> {
+    let mut wallet_data = read_wallet_json(path)?;
+    wallet_data.has_passphrase = !passphrase.is_empty();
+    // Files from other tools may not record a non-English wordlist
+    if Mnemonic::parse_in(wallet_data.language, &wallet_data.mnemonic).is_err() {
+        wallet_data.language = Mnemonic::parse(&wallet_data.mnemonic)?.language();
+    }
+
+    if let Err(e) = wallet_data.verify(passphrase) {
+        if passphrase.is_empty() {
+            output::warn("If this wallet was generated with a BIP39 passphrase, use --bip39-passphrase");
+        }
+        return Err(e);
+    }
+
+    Ok(wallet_data)
 }
 
-fn restore_from_mnemonic(mnemonic_phrase: &str) -> Result<WalletData, String> {
-    // Parse and validate mnemonic
-    let mnemonic = Mnemonic::parse_in(Language::English, mnemonic_phrase)
-        .map_err(|e| format!("Invalid mnemonic: {}", e))?;
+fn read_wallet_json(path: &PathBuf) -> Result<WalletData> {
+    let wallet_json =
+        fs::read_to_string(path).map_err(|e| Error::io("Failed to read wallet file", e))?;
+    serde_json::from_str(&wallet_json)
+        .map_err(|e| Error::Encoding(format!("Failed to parse wallet JSON: {}", e)))
+}
 
-    // Derive seed
-    let seed = mnemonic.to_seed("");
+fn print_wallet_summary(wallet_data: &WalletData) {
+    say!("✓ Loaded wallet");
+    say!("  Address: {}", wallet_data.address);
+    say!("  Key type: {}", wallet_data.key_type);
+    say!("  Derivation: {}", wallet_data.derivation);
+    say!("  Mnemonic: {} words, {}", wallet_data.mnemonic.split_whitespace().count(), wallet_data.language);
+    if wallet_data.has_passphrase {
+        say!("  BIP39 passphrase: in use (not stored)");
+    }
+    say!();
+}
 
-    // Generate Ed25519 keypair from first 32 bytes
-    let mut key_bytes = [0u8; 32];
-    key_bytes.copy_from_slice(&seed[..32]);
-    let signing_key = SigningKey::from_bytes(&key_bytes);
-    let verifying_key = signing_key.verifying_key();
+fn print_entries(entries: &[EntryMetadata]) {
+    if entries.is_empty() {
+        say!("  (no wallets)");
+        return;
+    }
 
-    // Derive address
-    let address = derive_address(verifying_key.as_bytes());
+    let width = entries.iter().map(|e| e.label.chars().count()).max().unwrap_or(0).max(5);
+    say!("  {:<width$}  {:<17}  ADDRESS", "LABEL", "KEY TYPE");
+    for entry in entries {
+        say!("  {:<width$}  {:<17}  {}", entry.label, entry.key_type, entry.address);
+    }
+}
 
-    Ok(WalletData {
-        mnemonic: mnemonic.to_string(),
-        public_key: hex::encode(verifying_key.as_bytes()),
-        address,
-        key_type: "Ed25519".to_string(),
-    })
+/// Save a newly sealed KeepBox without clobbering an existing file; with
+/// `force` the existing file is first moved to a timestamped backup
+fn save_new(keepbox: &KeepBox, path: &Path, force: bool) -> Result<Option<PathBuf>> {
+    let backup = if force { files::backup_existing(path)? } else { None };
+    keepbox.save(path)?;
+    if let Some(backup) = &backup {
+        say!("✓ Moved the previous file to: {}", backup.display());
+    }
+    Ok(backup)
+}
+
+/// Keep a copy of a KeepBox about to be re-encrypted in place; the copy
+/// still opens with the old password
+fn backup_before_replace(path: &Path, no_backup: bool) -> Result<Option<PathBuf>> {
+    if no_backup {
+        return Ok(None);
+    }
+    let backup = files::backup_copy(path)?;
+    say!("✓ Backup of original: {}", backup.display());
+    output::warn(format!(
+        "{} still opens with the OLD password; delete it once the new password is confirmed",
+        backup.display()
+    ));
+    Ok(Some(backup))
+}
+
+/// Read a line from stdin after printing `prompt`
+fn read_line(prompt: &str) -> Result<String> {
+    output::prompt(prompt)?;
+
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .map_err(|e| Error::io("Failed to read input", e))?;
+    Ok(input.trim().to_string())
 }
 
 // ===== Command Implementations =====
 
-fn cmd_init(wallet_path: PathBuf, output_path: PathBuf, label: Option<String>) -> Result<(), String> {
-    println!("🔐 Creating encrypted KeepBox from wallet...");
-    println!();
+fn cmd_init(
+    wallet_path: PathBuf,
+    output_path: PathBuf,
+    force: bool,
+    label: Option<String>,
+    bip39_passphrase: bool,
+    kdf: KdfArgs,
+) -> Result<Value> {
+    say!("🔐 Creating encrypted KeepBox from wallet...");
+    say!();
 
-    // Read wallet JSON
-    let wallet_json = fs::read_to_string(&wallet_path)
-        .map_err(|e| format!("Failed to read wallet file: {}", e))?;
+    files::ensure_absent(&output_path, force)?;
+    let kdf = kdf.resolve()?.unwrap_or_default();
 
-    let wallet_data: WalletData = serde_json::from_str(&wallet_json)
-        .map_err(|e| format!("Failed to parse wallet JSON: {}", e))?;
+    let passphrase = if bip39_passphrase {
+        prompt_bip39_passphrase(false)?
+    } else {
+        Zeroizing::new(String::new())
+    };
+
+    // Read wallet JSON
+    let wallet_data = load_wallet_json(&wallet_path, &passphrase)?;
 
-    println!("✓ Loaded wallet");
-    println!("  Address: {}", wallet_data.address);
-    println!();
+    say!("✓ Loaded wallet");
+    say!("  Address: {}", wallet_data.address);
+    say!();
 
     // Prompt for password
-    println!("⚠️  Choose a strong password to encrypt your wallet.");
-    println!("    Minimum 12 characters with mixed case, numbers, and symbols.");
-    println!();
+    say!("⚠️  Choose a strong password to encrypt your wallet.");
+    say!("    Minimum 12 characters with mixed case, numbers, and symbols.");
+    say!();
 
     let password = prompt_password("Enter password: ", true)?;
-    println!();
-
-    println!("🔒 Encrypting wallet data...");
-
-    // Encrypt wallet data
-    let (ciphertext, salt, nonce) = encrypt_wallet_data(&wallet_data, &password)?;
-
-    // Create KeepBox structure
-    let keepbox = KeepBox {
-        version: "1.0.0".to_string(),
-        crypto: CryptoParams {
-            cipher: "aes-256-gcm".to_string(),
-            kdf: "argon2id".to_string(),
-            kdf_params: KdfParams {
-                memory_cost: 65536,
-                time_cost: 3,
-                parallelism: 4,
-                salt: BASE64.encode(&salt),
-            },
-            nonce: BASE64.encode(&nonce),
-        },
-        encrypted_data: BASE64.encode(&ciphertext),
-        metadata: Metadata {
-            created: chrono::Utc::now().to_rfc3339(),
-            modified: chrono::Utc::now().to_rfc3339(),
-            label,
-            address: wallet_data.address.clone(),
-        },
+    say!();
+
+    say!("🔒 Encrypting wallet data (Argon2id {})...", kdf);
+
+    let keepbox = KeepBox::seal_with(&wallet_data, &password, label, &kdf)?;
+    let backup = save_new(&keepbox, &output_path, force)?;
+
+    say!("✓ Encrypted wallet data");
+    say!("✓ Created KeepBox");
+    say!();
+    say!("✅ Successfully created encrypted KeepBox: {}", output_path.display());
+    say!();
+    say!("📝 Important:");
+    say!("   - Remember your password - it CANNOT be recovered");
+    say!("   - Store a backup of this file in a secure location");
+    say!("   - The original wallet.json can now be securely deleted");
+
+    Ok(json!({
+        "keepbox": output_path,
+        "address": keepbox.metadata.address,
+        "label": keepbox.metadata.label,
+        "version": keepbox.version,
+        "kdf_params": keepbox.crypto.kdf_params,
+        "backup": backup,
+    }))
+}
+
+fn cmd_open(keepbox_path: PathBuf, decrypt: bool) -> Result<Value> {
+    let keepbox = match KeepBoxFile::load(&keepbox_path)? {
+        KeepBoxFile::Wallet(keepbox) => keepbox,
+        KeepBoxFile::Vault(vault) => return open_vault(&vault, decrypt),
     };
 
-    // Serialize and write
-    let keepbox_json = serde_json::to_string_pretty(&keepbox)
-        .map_err(|e| format!("Failed to serialize KeepBox: {}", e))?;
+    // Display public information
+    say!("📦 KeepBox Information");
+    say!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    say!();
+    say!("Version:     {}", keepbox.version);
+    say!("Encryption:  {} with {}", keepbox.crypto.cipher, keepbox.crypto.kdf);
+    say!("KDF params:  {}", keepbox.crypto.kdf_params.cost());
+    say!();
+    say!("Address:     {}", keepbox.metadata.address);
+    if let Some(label) = &keepbox.metadata.label {
+        say!("Label:       {}", label);
+    }
+    say!("Created:     {}", keepbox.metadata.created);
+    say!("Modified:    {}", keepbox.metadata.modified);
+    say!();
+    say!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    say!();
+
+    if !keepbox.is_authenticated() {
+        output::warn(format!(
+            "Format {} does not authenticate this metadata; it may have been edited. \
+             Confirm with --decrypt, and upgrade with 'migrate'.",
+            keepbox.version
+        ));
+        say!();
+    }
 
-    fs::write(&output_path, keepbox_json)
-        .map_err(|e| format!("Failed to write KeepBox file: {}", e))?;
+    let mut info = json!({
+        "kind": "wallet",
+        "version": keepbox.version,
+        "cipher": keepbox.crypto.cipher,
+        "kdf": keepbox.crypto.kdf,
+        "kdf_params": keepbox.crypto.kdf_params,
+        "address": keepbox.metadata.address,
+        "label": keepbox.metadata.label,
+        "created": keepbox.metadata.created,
+        "modified": keepbox.metadata.modified,
+        "authenticated": keepbox.is_authenticated(),
+        "verified": false,
+    });
+
+    if !decrypt {
+        say!("💡 Use 'export' command to access wallet data (requires password)");
+        return Ok(info);
+    }
 
-    // Set file permissions on Unix-like systems
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(&output_path)
-            .map_err(|e| format!("Failed to get file metadata: {}", e))?
-            .permissions();
-        perms.set_mode(0o600);
-        fs::set_permissions(&output_path, perms)
-            .map_err(|e| format!("Failed to set file permissions: {}", e))?;
+    let password = prompt_password("Enter password: ", false)?;
+    say!();
+
+    // For authenticated files a tampered header already fails decryption
+    let wallet_data = keepbox.unseal(&password)?;
+    if let Err(e) = keepbox.check_metadata(&wallet_data) {
+        say!("🚨 WARNING: The address shown above is NOT this wallet's address!");
+        say!("   Wallet address: {}", wallet_data.address);
+        say!();
+        return Err(e);
     }
 
-    println!("✓ Encrypted wallet data");
-    println!("✓ Created KeepBox");
-    println!();
-    println!("✅ Successfully created encrypted KeepBox: {}", output_path.display());
-    println!();
-    println!("📝 Important:");
-    println!("   - Remember your password - it CANNOT be recovered");
-    println!("   - Store a backup of this file in a secure location");
-    println!("   - The original wallet.json can now be securely deleted");
+    say!("✓ Metadata matches the encrypted wallet");
+    info["verified"] = json!(true);
 
-    Ok(())
+    Ok(info)
 }
 
-fn cmd_open(keepbox_path: PathBuf) -> Result<(), String> {
-    // Read KeepBox file
-    let keepbox_json = fs::read_to_string(&keepbox_path)
-        .map_err(|e| format!("Failed to read KeepBox file: {}", e))?;
+fn open_vault(vault: &Vault, decrypt: bool) -> Result<Value> {
+    say!("📦 KeepBox Vault Information");
+    say!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    say!();
+    say!("Version:     {}", vault.version);
+    say!("Encryption:  {} with {}", vault.crypto.cipher, vault.crypto.kdf);
+    say!("KDF params:  {}", vault.crypto.kdf_params.cost());
+    say!();
+    if let Some(label) = &vault.metadata.label {
+        say!("Label:       {}", label);
+    }
+    say!("Created:     {}", vault.metadata.created);
+    say!("Modified:    {}", vault.metadata.modified);
+    say!("Wallets:     {}", vault.metadata.entries.len());
+    say!();
+    print_entries(&vault.metadata.entries);
+    say!();
+    say!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    say!();
+
+    let mut info = json!({
+        "kind": "vault",
+        "version": vault.version,
+        "cipher": vault.crypto.cipher,
+        "kdf": vault.crypto.kdf,
+        "kdf_params": vault.crypto.kdf_params,
+        "label": vault.metadata.label,
+        "created": vault.metadata.created,
+        "modified": vault.metadata.modified,
+        "entries": vault.metadata.entries,
+        "authenticated": true,
+        "verified": false,
+    });
+
+    if !decrypt {
+        say!("💡 Use 'export --entry <label>' to access a wallet (requires password)");
+        return Ok(info);
+    }
 
-    let keepbox: KeepBox = serde_json::from_str(&keepbox_json)
-        .map_err(|e| format!("Failed to parse KeepBox: {}", e))?;
+    let password = prompt_password("Enter password: ", false)?;
+    say!();
 
-    // Display public information
-    println!("📦 KeepBox Information");
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!();
-    println!("Version:     {}", keepbox.version);
-    println!("Encryption:  {} with {}", keepbox.crypto.cipher, keepbox.crypto.kdf);
-    println!();
-    println!("Address:     {}", keepbox.metadata.address);
-    if let Some(label) = &keepbox.metadata.label {
-        println!("Label:       {}", label);
-    }
-    println!("Created:     {}", keepbox.metadata.created);
-    println!("Modified:    {}", keepbox.metadata.modified);
-    println!();
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!();
-    println!("💡 Use 'export' command to access wallet data (requires password)");
+    vault.unseal(&password)?;
+    say!("✓ Entry list matches the encrypted wallets");
+    info["verified"] = json!(true);
 
-    Ok(())
+    Ok(info)
 }
 
 fn cmd_export(
     keepbox_path: PathBuf,
     output_path: PathBuf,
+    force: bool,
+    entry: Option<String>,
     show_private: bool,
-) -> Result<(), String> {
-    println!("🔓 Exporting wallet from KeepBox...");
-    println!();
+) -> Result<Value> {
+    say!("🔓 Exporting wallet from KeepBox...");
+    say!();
 
-    // Read KeepBox file
-    let keepbox_json = fs::read_to_string(&keepbox_path)
-        .map_err(|e| format!("Failed to read KeepBox file: {}", e))?;
+    files::ensure_absent(&output_path, force)?;
 
-    let keepbox: KeepBox = serde_json::from_str(&keepbox_json)
-        .map_err(|e| format!("Failed to parse KeepBox: {}", e))?;
+    let keepbox = KeepBoxFile::load(&keepbox_path)?;
+    // Fail on a bad --entry before asking for the password
+    keepbox.address(entry.as_deref())?;
 
     // Prompt for password
     let password = prompt_password("Enter password: ", false)?;
-    println!();
+    say!();
 
-    println!("🔓 Decrypting wallet data...");
+    say!("🔓 Decrypting wallet data...");
 
-    // Decode encrypted data
-    let ciphertext = BASE64.decode(&keepbox.encrypted_data)
-        .map_err(|e| format!("Failed to decode ciphertext: {}", e))?;
-    let salt = BASE64.decode(&keepbox.crypto.kdf_params.salt)
-        .map_err(|e| format!("Failed to decode salt: {}", e))?;
-    let nonce = BASE64.decode(&keepbox.crypto.nonce)
-        .map_err(|e| format!("Failed to decode nonce: {}", e))?;
+    let wallet_data = keepbox.unseal_wallet(&password, entry.as_deref())?;
 
-    // Decrypt
-    let wallet_data = decrypt_wallet_data(&ciphertext, &password, &salt, &nonce)?;
+    say!("✓ Decrypted wallet data");
+    say!();
 
-    println!("✓ Decrypted wallet data");
-    println!();
+    let passphrase = passphrase_for(&wallet_data)?;
+    let wallet = wallet_data.to_output(&passphrase, show_private)?;
 
     // Display wallet info
-    println!("📬 Address:    {}", wallet_data.address);
-    println!("🔐 Public Key: {}", wallet_data.public_key);
-    println!();
+    say!("📬 Address:    {}", wallet.address);
+    say!("🔐 Public Key: {}", wallet.public_key);
+    say!();
 
     if show_private {
-        println!("⚠️  WARNING: Exporting with mnemonic included!");
-        println!();
+        output::warn("Exporting with private key included!");
+        say!();
     }
 
     // Write to file
-    let export_json = serde_json::to_string_pretty(&wallet_data)
-        .map_err(|e| format!("Failed to serialize wallet data: {}", e))?;
+    let export_json = serde_json::to_string_pretty(&wallet)
+        .map_err(|e| Error::Encoding(format!("Failed to serialize wallet data: {}", e)))?;
 
-    fs::write(&output_path, export_json)
-        .map_err(|e| format!("Failed to write export file: {}", e))?;
+    let backup = if force { files::backup_existing(&output_path)? } else { None };
+    files::create_private_file(&output_path, export_json.as_bytes())?;
+    if let Some(backup) = &backup {
+        say!("✓ Moved the previous file to: {}", backup.display());
+    }
 
-    println!("✅ Successfully exported wallet to: {}", output_path.display());
-    println!();
-    println!("⚠️  Security Warning:");
-    println!("   - The exported file contains your mnemonic in PLAINTEXT");
-    println!("   - Store it securely or delete it after use");
-    println!("   - Consider re-encrypting it immediately");
+    say!("✅ Successfully exported wallet to: {}", output_path.display());
+    say!();
+    say!("⚠️  Security Warning:");
+    say!("   - The exported file contains your mnemonic in PLAINTEXT");
+    say!("   - Store it securely or delete it after use");
+    say!("   - Consider re-encrypting it immediately");
+    if output::is_json() {
+        output::warn(format!("{} contains the mnemonic in plaintext", output_path.display()));
+    }
 
-    Ok(())
+    Ok(json!({
+        "output": output_path,
+        "address": wallet.address,
+        "public_key": wallet.public_key,
+        "private_key_included": show_private,
+        "backup": backup,
+    }))
 }
 
 fn cmd_import(
-    mnemonic: Option<String>,
-    json_path: Option<PathBuf>,
+    source: WalletSourceArgs,
     output_path: PathBuf,
+    force: bool,
     label: Option<String>,
-) -> Result<(), String> {
-    println!("📥 Importing wallet into KeepBox...");
-    println!();
-
-    let wallet_data = if let Some(json) = json_path {
-        // Import from JSON
-        let wallet_json = fs::read_to_string(&json)
-            .map_err(|e| format!("Failed to read wallet file: {}", e))?;
-        serde_json::from_str(&wallet_json)
-            .map_err(|e| format!("Failed to parse wallet JSON: {}", e))?
-    } else if let Some(mnemonic_phrase) = mnemonic {
-        // Import from mnemonic
-        restore_from_mnemonic(&mnemonic_phrase)?
-    } else {
-        // Prompt for mnemonic
-        println!("Enter your 24-word mnemonic phrase:");
-        print!("> ");
-        io::stdout().flush().unwrap();
+    kdf: KdfArgs,
+) -> Result<Value> {
+    say!("📥 Importing wallet into KeepBox...");
+    say!();
 
-        let mut mnemonic_input = String::new();
-        io::stdin()
-            .read_line(&mut mnemonic_input)
-            .map_err(|e| format!("Failed to read input: {}", e))?;
+    files::ensure_absent(&output_path, force)?;
 
-        restore_from_mnemonic(mnemonic_input.trim())?
-    };
+    let kdf = kdf.resolve()?.unwrap_or_default();
 
-    println!("✓ Loaded wallet");
-    println!("  Address: {}", wallet_data.address);
-    println!();
+    let wallet_data = source.load()?;
+    print_wallet_summary(&wallet_data);
 
-    // Now create encrypted KeepBox (reuse init logic)
-    println!("⚠️  Choose a strong password to encrypt your wallet.");
-    println!("    Minimum 12 characters with mixed case, numbers, and symbols.");
-    println!();
+    say!("⚠️  Choose a strong password to encrypt your wallet.");
+    say!("    Minimum 12 characters with mixed case, numbers, and symbols.");
+    say!();
 
     let password = prompt_password("Enter password: ", true)?;
-    println!();
-
-    println!("🔒 Encrypting wallet data...");
-
-    let (ciphertext, salt, nonce) = encrypt_wallet_data(&wallet_data, &password)?;
-
-    let keepbox = KeepBox {
-        version: "1.0.0".to_string(),
-        crypto: CryptoParams {
-            cipher: "aes-256-gcm".to_string(),
-            kdf: "argon2id".to_string(),
-            kdf_params: KdfParams {
-                memory_cost: 65536,
-                time_cost: 3,
-                parallelism: 4,
-                salt: BASE64.encode(&salt),
-            },
-            nonce: BASE64.encode(&nonce),
-        },
-        encrypted_data: BASE64.encode(&ciphertext),
-        metadata: Metadata {
-            created: chrono::Utc::now().to_rfc3339(),
-            modified: chrono::Utc::now().to_rfc3339(),
-            label,
-            address: wallet_data.address.clone(),
-        },
+    say!();
+
+    say!("🔒 Encrypting wallet data (Argon2id {})...", kdf);
+
+    let keepbox = KeepBox::seal_with(&wallet_data, &password, label, &kdf)?;
+    let backup = save_new(&keepbox, &output_path, force)?;
+
+    say!("✓ Encrypted wallet data");
+    say!("✓ Created KeepBox");
+    say!();
+    say!("✅ Successfully imported wallet into KeepBox: {}", output_path.display());
+
+    Ok(json!({
+        "keepbox": output_path,
+        "address": wallet_data.address,
+        "public_key": wallet_data.public_key,
+        "key_type": wallet_data.key_type,
+        "derivation": wallet_data.derivation,
+        "language": wallet_data.language,
+        "label": keepbox.metadata.label,
+        "version": keepbox.version,
+        "backup": backup,
+    }))
+}
+
+fn cmd_change_password(keepbox_path: PathBuf, kdf: KdfArgs, no_backup: bool) -> Result<Value> {
+    say!("🔄 Changing KeepBox password...");
+    say!();
+
+    let kdf = kdf.resolve()?;
+
+    let mut keepbox = match KeepBoxFile::load(&keepbox_path)? {
+        KeepBoxFile::Wallet(keepbox) => keepbox,
+        KeepBoxFile::Vault(vault) => return change_vault_password(&keepbox_path, vault, kdf, no_backup),
     };
+    let old_version = keepbox.version;
 
-    let keepbox_json = serde_json::to_string_pretty(&keepbox)
-        .map_err(|e| format!("Failed to serialize KeepBox: {}", e))?;
+    // Prompt for old password
+    let old_password = prompt_password("Enter current password: ", false)?;
+    say!();
 
-    fs::write(&output_path, keepbox_json)
-        .map_err(|e| format!("Failed to write KeepBox file: {}", e))?;
+    say!("🔓 Decrypting wallet data...");
 
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(&output_path)
-            .map_err(|e| format!("Failed to get file metadata: {}", e))?
-            .permissions();
-        perms.set_mode(0o600);
-        fs::set_permissions(&output_path, perms)
-            .map_err(|e| format!("Failed to set file permissions: {}", e))?;
+    let wallet_data = keepbox.unseal(&old_password)?;
+
+    say!("✓ Decrypted with old password");
+    say!();
+
+    // Prompt for new password
+    say!("⚠️  Choose a new strong password.");
+    say!();
+
+    let new_password = prompt_password("Enter new password: ", true)?;
+    say!();
+
+    say!("🔒 Re-encrypting with new password...");
+
+    match kdf {
+        Some(kdf) => {
+            say!("   New KDF params: {}", kdf);
+            keepbox.reseal_with(&wallet_data, &new_password, &kdf)?
+        }
+        None => keepbox.reseal(&wallet_data, &new_password)?,
     }
+    let backup = backup_before_replace(&keepbox_path, no_backup)?;
+    keepbox.save_atomic(&keepbox_path, &new_password)?;
+
+    say!("✓ Re-encrypted wallet data");
+    say!("✓ Verified the new file decrypts before replacing the old one");
+    if keepbox.version != old_version {
+        say!("✓ Upgraded format {} → {}", old_version, keepbox.version);
+    }
+    say!();
+    say!("✅ Successfully changed KeepBox password");
+    say!();
+    say!("⚠️  Remember your new password - it CANNOT be recovered");
+
+    Ok(json!({
+        "keepbox": keepbox_path,
+        "address": keepbox.metadata.address,
+        "previous_version": old_version,
+        "version": keepbox.version,
+        "kdf_params": keepbox.crypto.kdf_params,
+        "backup": backup,
+    }))
+}
+
+fn change_vault_password(vault_path: &PathBuf, mut vault: Vault, kdf: Option<KdfCost>, no_backup: bool) -> Result<Value> {
+    let old_password = prompt_password("Enter current password: ", false)?;
+    say!();
+
+    say!("🔓 Decrypting vault...");
+
+    let data = vault.unseal(&old_password)?;
+
+    say!("✓ Decrypted {} wallet(s) with old password", data.entries.len());
+    say!();
 
-    println!("✓ Encrypted wallet data");
-    println!("✓ Created KeepBox");
-    println!();
-    println!("✅ Successfully imported wallet into KeepBox: {}", output_path.display());
+    say!("⚠️  Choose a new strong password.");
+    say!();
 
-    Ok(())
+    let new_password = prompt_password("Enter new password: ", true)?;
+    say!();
+
+    say!("🔒 Re-encrypting with new password...");
+
+    let old_version = vault.version;
+    match kdf {
+        Some(kdf) => {
+            say!("   New KDF params: {}", kdf);
+            vault.reseal_with(&data, &new_password, &kdf)?
+        }
+        None => vault.reseal(&data, &new_password)?,
+    }
+    let backup = backup_before_replace(vault_path, no_backup)?;
+    vault.save_atomic(vault_path, &new_password)?;
+
+    say!("✓ Re-encrypted vault");
+    say!("✓ Verified the new file decrypts before replacing the old one");
+    if vault.version != old_version {
+        say!("✓ Upgraded format {} → {}", old_version, vault.version);
+    }
+    say!();
+    say!("✅ Successfully changed vault password");
+    say!();
+    say!("⚠️  Remember your new password - it CANNOT be recovered");
+
+    Ok(json!({
+        "keepbox": vault_path,
+        "entries": vault.metadata.entries,
+        "previous_version": old_version,
+        "version": vault.version,
+        "kdf_params": vault.crypto.kdf_params,
+        "backup": backup,
+    }))
 }
 
-fn cmd_change_password(keepbox_path: PathBuf) -> Result<(), String> {
-    println!("🔄 Changing KeepBox password...");
-    println!();
+fn cmd_verify(keepbox_path: PathBuf) -> Result<Value> {
+    say!("🔍 Verifying KeepBox integrity...");
+    say!();
 
-    // Read KeepBox file
-    let keepbox_json = fs::read_to_string(&keepbox_path)
-        .map_err(|e| format!("Failed to read KeepBox file: {}", e))?;
+    let keepbox = match KeepBoxFile::load(&keepbox_path)? {
+        KeepBoxFile::Wallet(keepbox) => keepbox,
+        KeepBoxFile::Vault(vault) => return verify_vault(&keepbox_path, &vault),
+    };
 
-    let mut keepbox: KeepBox = serde_json::from_str(&keepbox_json)
-        .map_err(|e| format!("Failed to parse KeepBox: {}", e))?;
+    say!("✓ KeepBox file structure valid");
 
-    // Prompt for old password
-    let old_password = prompt_password("Enter current password: ", false)?;
-    println!();
+    keepbox.check_encoding()?;
 
-    println!("🔓 Decrypting wallet data...");
+    say!("✓ Encrypted data encoding valid");
 
-    // Decode and decrypt with old password
-    let ciphertext = BASE64.decode(&keepbox.encrypted_data)
-        .map_err(|e| format!("Failed to decode ciphertext: {}", e))?;
-    let salt = BASE64.decode(&keepbox.crypto.kdf_params.salt)
-        .map_err(|e| format!("Failed to decode salt: {}", e))?;
-    let nonce = BASE64.decode(&keepbox.crypto.nonce)
-        .map_err(|e| format!("Failed to decode nonce: {}", e))?;
+    // Prompt for password
+    let password = prompt_password("Enter password to verify: ", false)?;
+    say!();
 
-    let wallet_data = decrypt_wallet_data(&ciphertext, &old_password, &salt, &nonce)?;
+    say!("🔓 Attempting decryption...");
 
-    println!("✓ Decrypted with old password");
-    println!();
+    let wallet_data = keepbox.unseal(&password)?;
 
-    // Prompt for new password
-    println!("⚠️  Choose a new strong password.");
-    println!();
+    say!("✓ Password correct");
+    say!("✓ Decryption successful");
 
-    let new_password = prompt_password("Enter new password: ", true)?;
-    println!();
+    keepbox.check_metadata(&wallet_data)?;
+    if keepbox.is_authenticated() {
+        say!("✓ Metadata authenticated");
+    } else {
+        say!("✓ Metadata matches (format {} - run 'migrate' to authenticate it)", keepbox.version);
+    }
 
-    println!("🔒 Re-encrypting with new password...");
+    // Verify address derivation
+    let passphrase = passphrase_for(&wallet_data)?;
+    wallet_data.verify(&passphrase)?;
+
+    say!("✓ Address verification passed");
+    say!();
+    say!("✅ KeepBox verification SUCCESSFUL");
+    say!();
+    say!("Wallet Address: {}", wallet_data.address);
+
+    Ok(json!({
+        "keepbox": keepbox_path,
+        "kind": "wallet",
+        "version": keepbox.version,
+        "address": wallet_data.address,
+        "authenticated": keepbox.is_authenticated(),
+    }))
+}
+
+fn verify_vault(vault_path: &PathBuf, vault: &Vault) -> Result<Value> {
+    say!("✓ Vault file structure valid");
+
+    vault.check_encoding()?;
 
-    // Re-encrypt with new password
-    let (new_ciphertext, new_salt, new_nonce) = encrypt_wallet_data(&wallet_data, &new_password)?;
+    say!("✓ Encrypted data encoding valid");
 
-    // Update KeepBox
-    keepbox.crypto.kdf_params.salt = BASE64.encode(&new_salt);
-    keepbox.crypto.nonce = BASE64.encode(&new_nonce);
-    keepbox.encrypted_data = BASE64.encode(&new_ciphertext);
-    keepbox.metadata.modified = chrono::Utc::now().to_rfc3339();
+    let password = prompt_password("Enter password to verify: ", false)?;
+    say!();
+
+    say!("🔓 Attempting decryption...");
 
-    // Write updated KeepBox
-    let keepbox_json = serde_json::to_string_pretty(&keepbox)
-        .map_err(|e| format!("Failed to serialize KeepBox: {}", e))?;
+    let data = vault.unseal(&password)?;
 
-    fs::write(&keepbox_path, keepbox_json)
-        .map_err(|e| format!("Failed to write KeepBox file: {}", e))?;
+    say!("✓ Password correct");
+    say!("✓ Decryption successful");
+    say!("✓ Entry list authenticated");
+
+    for entry in &data.entries {
+        let passphrase = passphrase_for(&entry.wallet)?;
+        entry.wallet.verify(&passphrase)?;
+        say!("✓ {}: address verification passed", entry.metadata.label);
+    }
 
-    println!("✓ Re-encrypted wallet data");
-    println!();
-    println!("✅ Successfully changed KeepBox password");
-    println!();
-    println!("⚠️  Remember your new password - it CANNOT be recovered");
+    say!();
+    say!("✅ Vault verification SUCCESSFUL ({} wallet(s))", data.entries.len());
 
-    Ok(())
+    Ok(json!({
+        "keepbox": vault_path,
+        "kind": "vault",
+        "version": vault.version,
+        "entries": vault.metadata.entries,
+        "authenticated": true,
+    }))
 }
 
-fn cmd_verify(keepbox_path: PathBuf) -> Result<(), String> {
-    println!("🔍 Verifying KeepBox integrity...");
-    println!();
+fn cmd_add(
+    vault_path: PathBuf,
+    label: String,
+    from_keepbox: Option<PathBuf>,
+    source: WalletSourceArgs,
+    vault_label: Option<String>,
+    kdf: KdfArgs,
+) -> Result<Value> {
+    say!("➕ Adding wallet '{}' to vault...", label);
+    say!();
+
+    boundless_wallet::vault::validate_label(&label)?;
+    let kdf = kdf.resolve()?;
+
+    // Unlock (or create) the vault before asking for the wallet
+    let created = !vault_path.exists();
+    let (mut vault, password) = if !created {
+        if kdf.is_some() || vault_label.is_some() {
+            output::warn(
+                "--kdf-* and --vault-label only apply when creating a vault; ignoring them \
+                 (use 'change-password' to change the KDF cost)",
+            );
+            say!();
+        }
 
-    // Read KeepBox file
-    let keepbox_json = fs::read_to_string(&keepbox_path)
-        .map_err(|e| format!("Failed to read KeepBox file: {}", e))?;
+        let vault = Vault::load(&vault_path)?;
+        let password = prompt_password("Enter vault password: ", false)?;
+        say!();
+        (vault, password)
+    } else {
+        let kdf = kdf.unwrap_or_default();
+        say!("📦 {} does not exist; creating a new vault", vault_path.display());
+        say!();
+        say!("⚠️  Choose a strong password to encrypt the vault.");
+        say!("    Minimum 12 characters with mixed case, numbers, and symbols.");
+        say!();
+
+        let password = prompt_password("Enter password: ", true)?;
+        say!();
+        say!("🔒 Creating vault (Argon2id {})...", kdf);
+        (Vault::create(&password, vault_label, &kdf)?, password)
+    };
 
-    let keepbox: KeepBox = serde_json::from_str(&keepbox_json)
-        .map_err(|e| format!("Failed to parse KeepBox: {}", e))?;
+    let mut data = vault.unseal(&password)?;
+    say!("✓ Unlocked vault ({} wallet(s))", data.entries.len());
+    say!();
+
+    let wallet_data = match from_keepbox {
+        Some(path) => {
+            let keepbox = KeepBox::load(&path)?;
+            let keepbox_password = prompt_password(&format!("Enter password for {}: ", path.display()), false)?;
+            say!();
+            let wallet_data = keepbox.unseal(&keepbox_password)?;
+            keepbox.check_metadata(&wallet_data)?;
+            wallet_data
+        }
+        None => source.load()?,
+    };
+    print_wallet_summary(&wallet_data);
 
-    println!("✓ KeepBox file structure valid");
+    let address = data.add(&label, wallet_data)?.address.clone();
 
-    // Verify base64 encoding
-    let _ = BASE64.decode(&keepbox.encrypted_data)
-        .map_err(|_| "Invalid base64 encoding in encrypted_data".to_string())?;
-    let _ = BASE64.decode(&keepbox.crypto.kdf_params.salt)
-        .map_err(|_| "Invalid base64 encoding in salt".to_string())?;
-    let _ = BASE64.decode(&keepbox.crypto.nonce)
-        .map_err(|_| "Invalid base64 encoding in nonce".to_string())?;
+    say!("🔒 Re-encrypting vault...");
+    vault.reseal(&data, &password)?;
+    vault.save_atomic(&vault_path, &password)?;
 
-    println!("✓ Encrypted data encoding valid");
+    say!("✓ Added '{}' ({})", label, address);
+    say!();
+    say!("✅ Vault {} now holds {} wallet(s)", vault_path.display(), data.entries.len());
 
-    // Prompt for password
-    let password = prompt_password("Enter password to verify: ", false)?;
-    println!();
+    Ok(json!({
+        "keepbox": vault_path,
+        "vault_created": created,
+        "label": label,
+        "address": address,
+        "entries": vault.metadata.entries,
+    }))
+}
 
-    println!("🔓 Attempting decryption...");
+fn cmd_remove(vault_path: PathBuf, entry: String, yes: bool) -> Result<Value> {
+    let mut vault = Vault::load(&vault_path)?;
+    let address = vault.address(Some(&entry))?.to_string();
+
+    say!("🗑️  Removing wallet from vault:");
+    say!("   Entry:   {}", entry);
+    say!("   Address: {}", address);
+    say!();
+
+    if !yes {
+        say!("⚠️  The wallet's mnemonic is deleted from this vault. Make sure it is");
+        say!("   backed up elsewhere (e.g. 'export --entry') if you still need it.");
+        let confirmation = read_line("Type the entry label or address to confirm: ")?;
+        if confirmation != entry {
+            say!("❌ Cancelled");
+            return Ok(json!({
+                "keepbox": vault_path,
+                "removed": false,
+                "label": entry,
+                "address": address,
+                "entries": vault.metadata.entries,
+            }));
+        }
+        say!();
+    }
 
-    // Try to decrypt
-    let ciphertext = BASE64.decode(&keepbox.encrypted_data).unwrap();
-    let salt = BASE64.decode(&keepbox.crypto.kdf_params.salt).unwrap();
-    let nonce = BASE64.decode(&keepbox.crypto.nonce).unwrap();
+    let password = prompt_password("Enter vault password: ", false)?;
+    say!();
 
-    let wallet_data = decrypt_wallet_data(&ciphertext, &password, &salt, &nonce)?;
+    let mut data = vault.unseal(&password)?;
+    let removed = data.remove(&entry)?;
 
-    println!("✓ Password correct");
-    println!("✓ Decryption successful");
+    vault.reseal(&data, &password)?;
+    vault.save_atomic(&vault_path, &password)?;
 
-    // Verify address derivation
-    let restored_wallet = restore_from_mnemonic(&wallet_data.mnemonic)?;
-    if restored_wallet.address != wallet_data.address {
-        return Err("Address mismatch - wallet data may be corrupted".to_string());
+    say!("✓ Removed '{}' ({})", removed.metadata.label, removed.metadata.address);
+    say!();
+    say!("✅ Vault {} now holds {} wallet(s)", vault_path.display(), data.entries.len());
+
+    Ok(json!({
+        "keepbox": vault_path,
+        "removed": true,
+        "label": removed.metadata.label,
+        "address": removed.metadata.address,
+        "entries": vault.metadata.entries,
+    }))
+}
+
+fn cmd_list(vault_path: PathBuf) -> Result<Value> {
+    let vault = Vault::load(&vault_path)?;
+
+    match &vault.metadata.label {
+        Some(label) => say!("📦 {} ({}) - {} wallet(s)", vault_path.display(), label, vault.metadata.entries.len()),
+        None => say!("📦 {} - {} wallet(s)", vault_path.display(), vault.metadata.entries.len()),
     }
+    say!();
+    print_entries(&vault.metadata.entries);
+
+    Ok(json!({
+        "keepbox": vault_path,
+        "label": vault.metadata.label,
+        "entries": vault.metadata.entries,
+    }))
+}
+
+fn cmd_rename(vault_path: PathBuf, entry: String, new_label: String) -> Result<Value> {
+    let mut vault = Vault::load(&vault_path)?;
+    let address = vault.address(Some(&entry))?.to_string();
+    boundless_wallet::vault::validate_label(&new_label)?;
 
-    println!("✓ Address verification passed");
-    println!();
-    println!("✅ KeepBox verification SUCCESSFUL");
-    println!();
-    println!("Wallet Address: {}", wallet_data.address);
+    say!("✏️  Renaming {} → {}", entry, new_label);
+    say!("   Address: {}", address);
+    say!();
 
-    Ok(())
+    // Labels are authenticated with the vault, so renaming re-encrypts it
+    let password = prompt_password("Enter vault password: ", false)?;
+    say!();
+
+    let mut data = vault.unseal(&password)?;
+    data.rename(&entry, &new_label)?;
+
+    vault.reseal(&data, &password)?;
+    vault.save_atomic(&vault_path, &password)?;
+
+    say!("✅ Renamed to '{}'", new_label);
+
+    Ok(json!({
+        "keepbox": vault_path,
+        "previous_label": entry,
+        "label": new_label,
+        "address": address,
+        "entries": vault.metadata.entries,
+    }))
+}
+
+fn cmd_migrate(keepbox_path: PathBuf, no_backup: bool) -> Result<Value> {
+    say!("⬆️  Migrating KeepBox...");
+    say!();
+
+    let mut keepbox = match KeepBoxFile::load(&keepbox_path)? {
+        KeepBoxFile::Wallet(keepbox) => keepbox,
+        KeepBoxFile::Vault(vault) => {
+            say!("✅ Vault files are already at the current format ({}); nothing to do", vault.version);
+            return Ok(json!({
+                "keepbox": keepbox_path,
+                "migrated": false,
+                "previous_version": vault.version,
+                "version": vault.version,
+                "backup": null,
+            }));
+        }
+    };
+    let old_version = keepbox.version;
+
+    if old_version == FormatVersion::CURRENT {
+        say!("✅ Already at the current format ({}); nothing to do", old_version);
+        return Ok(json!({
+            "keepbox": keepbox_path,
+            "migrated": false,
+            "previous_version": old_version,
+            "version": old_version,
+            "backup": null,
+        }));
+    }
+
+    say!("Format:      {} → {}", old_version, FormatVersion::CURRENT);
+    say!();
+
+    let password = prompt_password("Enter password: ", false)?;
+    say!();
+
+    keepbox.migrate(&password)?;
+    say!("✓ Re-encrypted with authenticated header (same password and KDF cost)");
+
+    let mut backup = None;
+    if !no_backup {
+        let backup_path = files::backup_copy_tagged(&keepbox_path, Some(&format!("v{}", old_version)))?;
+        say!("✓ Backup of original: {}", backup_path.display());
+        backup = Some(backup_path);
+    }
+
+    // Only replaces the original once the written file decrypts
+    keepbox.save_atomic(&keepbox_path, &password)?;
+    say!("✓ Upgraded KeepBox decrypts");
+
+    say!();
+    say!("✅ Migrated {} to format {}", keepbox_path.display(), keepbox.version);
+
+    Ok(json!({
+        "keepbox": keepbox_path,
+        "migrated": true,
+        "previous_version": old_version,
+        "version": keepbox.version,
+        "backup": backup,
+    }))
+}
+
+// ===== Key Agent =====
+
+#[cfg(unix)]
+fn print_agent_keys(keys: &[HeldKey]) {
+    for key in keys {
+        say!("   {}  {:<18} {}", key.address, key.key_type.as_str(), key.label.as_deref().unwrap_or("-"));
+    }
+}
+
+#[cfg(unix)]
+fn agent_client(socket: Option<PathBuf>) -> Result<AgentClient> {
+    socket.map(AgentClient::new).or_else(AgentClient::from_env).ok_or_else(|| {
+        Error::Agent(format!("no agent found; start one with 'agent start' or set {}", agent::SOCKET_ENV))
+    })
+}
+
+#[cfg(unix)]
+fn cmd_agent_start(keepboxes: Vec<PathBuf>, socket: Option<PathBuf>, idle_timeout: u64, foreground: bool) -> Result<Value> {
+    if idle_timeout == 0 {
+        return Err(Error::InvalidArgument("--idle-timeout must be at least 1 second".to_string()));
+    }
+    let socket = socket.unwrap_or_else(agent::default_socket_path);
+    let mut key_agent = Agent::new(Duration::from_secs(idle_timeout));
+
+    say!("🔑 Starting key agent");
+    say!();
+    unlock_keepboxes(&keepboxes, key_agent.keys_mut())?;
+
+    let listener = agent::bind(&socket)?;
+    if let Err(e) = key_agent.keys_mut().lock_memory() {
+        output::warn(format!("Could not lock key memory ({}); keys may be swapped to disk", e));
+    }
+
+    let keys = key_agent.keys().list();
+    let mut data = json!({
+        "socket": socket,
+        "keys": keys,
+        "idle_timeout": idle_timeout,
+        "pid": null,
+    });
+
+    if !foreground {
+        match agent::daemonize()? {
+            Some(pid) => {
+                data["pid"] = json!(pid);
+                say!();
+                say!("✅ Agent running (pid {}) with {} key(s); idle timeout {}s", pid, keys.len(), idle_timeout);
+                say!();
+                say!("💡 Point boundless-wallet at it with:");
+                say!("   export {}={}", agent::SOCKET_ENV, socket.display());
+                return Ok(data);
+            }
+            // mlock does not survive fork
+            None => {
+                let _ = key_agent.keys_mut().lock_memory();
+            }
+        }
+    } else {
+        data["pid"] = json!(std::process::id());
+        say!();
+        say!("✅ Agent listening on {} with {} key(s); idle timeout {}s", socket.display(), keys.len(), idle_timeout);
+        say!("   export {}={}", agent::SOCKET_ENV, socket.display());
+    }
+
+    let stopped = key_agent.serve(&listener);
+    drop(key_agent);
+    let _ = fs::remove_file(&socket);
+
+    match stopped? {
+        StopReason::Locked => say!("🔒 All keys locked; agent stopped"),
+        StopReason::IdleTimeout => say!("🔒 Idle for {}s; keys wiped and agent stopped", idle_timeout),
+    }
+    Ok(data)
+}
+
+#[cfg(unix)]
+fn cmd_agent_list(socket: Option<PathBuf>) -> Result<Value> {
+    let client = agent_client(socket)?;
+    let keys = client.list()?;
+
+    say!("🔑 Key agent at {} holds {} key(s)", client.socket().display(), keys.len());
+    print_agent_keys(&keys);
+
+    Ok(json!({
+        "socket": client.socket(),
+        "keys": keys,
+    }))
+}
+
+#[cfg(unix)]
+fn cmd_agent_lock(address: Option<String>, socket: Option<PathBuf>) -> Result<Value> {
+    let client = agent_client(socket)?;
+    let (locked, remaining) = client.lock(address.as_deref())?;
+
+    say!("🔒 Wiped {} key(s) from the agent", locked);
+    if remaining == 0 {
+        say!("   No keys left; the agent has stopped");
+    } else {
+        say!("   {} key(s) still unlocked", remaining);
+    }
+
+    Ok(json!({
+        "socket": client.socket(),
+        "locked": locked,
+        "remaining": remaining,
+    }))
 }
 
 // ===== Main =====
 
 fn main() {
-    let cli = Cli::parse();
+    let cli: Cli = output::parse_or_exit("boundless-keepbox");
+    output::set_output_format(cli.output_format);
+    set_password_source(cli.password.source());
 
-    let result = match cli.command {
+    let (command, result) = match cli.command {
         Commands::Init {
             wallet,
             output,
+            force,
             label,
-        } => cmd_init(wallet, output, label),
-        Commands::Open { keepbox } => cmd_open(keepbox),
+            bip39_passphrase,
+            kdf,
+        } => ("init", cmd_init(wallet, output, force, label, bip39_passphrase, kdf)),
+        Commands::Open { keepbox, decrypt } => ("open", cmd_open(keepbox, decrypt)),
         Commands::Export {
             keepbox,
             output,
+            force,
+            entry,
             show_private,
-        } => cmd_export(keepbox, output, show_private),
+        } => ("export", cmd_export(keepbox, output, force, entry, show_private)),
         Commands::Import {
-            mnemonic,
-            json,
+            source,
             output,
+            force,
+            label,
+            kdf,
+        } => ("import", cmd_import(source, output, force, label, kdf)),
+        Commands::Add {
+            keepbox,
             label,
-        } => cmd_import(mnemonic, json, output, label),
-        Commands::ChangePassword { keepbox } => cmd_change_password(keepbox),
-        Commands::Verify { keepbox } => cmd_verify(keepbox),
+            from_keepbox,
+            source,
+            vault_label,
+            kdf,
+        } => ("add", cmd_add(keepbox, label, from_keepbox, source, vault_label, kdf)),
+        Commands::Remove { keepbox, entry, yes } => ("remove", cmd_remove(keepbox, entry, yes)),
+        Commands::List { keepbox } => ("list", cmd_list(keepbox)),
+        Commands::Rename {
+            keepbox,
+            entry,
+            new_label,
+        } => ("rename", cmd_rename(keepbox, entry, new_label)),
+        Commands::ChangePassword { keepbox, kdf, no_backup } => {
+            ("change-password", cmd_change_password(keepbox, kdf, no_backup))
+        }
+        Commands::Verify { keepbox } => ("verify", cmd_verify(keepbox)),
+        Commands::Migrate { keepbox, no_backup } => ("migrate", cmd_migrate(keepbox, no_backup)),
+        #[cfg(unix)]
+        Commands::Agent { command } => match command {
+            AgentCommands::Start {
+                keepbox,
+                socket,
+                idle_timeout,
+                foreground,
+            } => ("agent start", cmd_agent_start(keepbox, socket, idle_timeout, foreground)),
+            AgentCommands::List { socket } => ("agent list", cmd_agent_list(socket)),
+            AgentCommands::Lock { address, socket } => ("agent lock", cmd_agent_lock(address, socket)),
+        },
     };
 
-    if let Err(e) = result {
-        eprintln!("❌ Error: {}", e);
-        std::process::exit(1);
-    }
+    output::finish(command, result)
 }
@@ -0,0 +1,97 @@
+// Boundless Mock Node - in-memory JSON-RPC node for testing
+//
+// Serves the JSON-RPC methods `send_transaction.py` and `boundless-wallet`
+// use (balances, nonces, submit, transaction lookups, block numbers) from
+// memory, checking signatures, nonces and balances of submitted transfers,
+// so wallet tooling can be tested without node containers. Faults can be
+// injected to exercise timeouts and retries.
+//
+// The node itself lives in the `boundless_wallet::mock_node` library module,
+// which Rust tests use directly; this binary is for scripts and manual use.
+// Both are built only with the `mock-node` feature.
+//
+// Usage:
+//   boundless-mock-node --account <public_key>=1000 --balance <address>=5
+//   boundless-mock-node --listen 127.0.0.1:0 --fail 'submit_transaction=lose-response'
+
+use boundless_wallet::mock_node::{FaultRule, MockNode};
+use boundless_wallet::output;
+use boundless_wallet::tx::{format_amount, parse_amount};
+use boundless_wallet::{say, Error, Result};
+use clap::Parser;
+
+// ===== CLI Structure =====
+
+#[derive(Parser)]
+#[command(name = "boundless-mock-node")]
+#[command(about = "Boundless BLS Blockchain - In-memory JSON-RPC node for testing", long_about = None)]
+struct Cli {
+    /// Address to listen on; port 0 picks a free port, printed on startup
+    #[arg(short, long, value_name = "ADDR", default_value = "127.0.0.1:9933")]
+    listen: String,
+
+    /// Account that can send, by public key (hex), with its balance in BLS
+    #[arg(long, value_name = "PUBKEY=BLS", value_parser = parse_funding)]
+    account: Vec<(String, u128)>,
+
+    /// Balance in BLS of an address whose public key is unknown (it can
+    /// receive but not send)
+    #[arg(long, value_name = "ADDRESS=BLS", value_parser = parse_funding)]
+    balance: Vec<(String, u128)>,
+
+    /// Starting block number
+    #[arg(long, default_value_t = 0)]
+    block_number: u64,
+
+    /// Leave transactions pending instead of confirming each in a new block
+    #[arg(long)]
+    no_auto_mine: bool,
+
+    /// Make calls fail: METHOD=FAULT[*TIMES], with METHOD `*` for any and
+    /// FAULT one of drop, lose-response, garbage, http:<status>,
+    /// rpc:<code>, delay:<ms>
+    #[arg(long, value_name = "RULE")]
+    fail: Vec<FaultRule>,
+}
+
+fn parse_funding(value: &str) -> std::result::Result<(String, u128), String> {
+    let (key, amount) = value.split_once('=').ok_or("expected <hex>=<BLS>")?;
+    let amount = parse_amount(amount).map_err(|e| e.to_string())?;
+    Ok((key.to_string(), amount))
+}
+
+// ===== Command =====
+
+fn cmd_serve(cli: Cli) -> Result<()> {
+    let node = MockNode::bind(&cli.listen)?;
+    node.set_block_number(cli.block_number);
+    node.set_auto_mine(!cli.no_auto_mine);
+
+    for (public_key, balance) in &cli.account {
+        let public_key = hex::decode(public_key)
+            .map_err(|e| Error::Encoding(format!("Invalid public key hex: {}", e)))?;
+        let address = node.add_account(&public_key, *balance)?;
+        say!("✓ Account {}: {} BLS", address, format_amount(*balance));
+    }
+    for (address, balance) in &cli.balance {
+        node.set_balance(address, *balance)?;
+        say!("✓ Balance {}: {} BLS (receive only)", address, format_amount(*balance));
+    }
+    for rule in cli.fail {
+        say!("⚠️  Injecting fault {}", rule);
+        node.inject(rule);
+    }
+
+    say!("✅ Mock node listening on {}", node.url());
+    node.wait();
+    Ok(())
+}
+
+// ===== Main =====
+
+fn main() {
+    let cli: Cli = output::parse_or_exit("boundless-mock-node");
+
+    let result = cmd_serve(cli).map(|()| serde_json::Value::Null);
+    output::finish("boundless-mock-node", result)
+}
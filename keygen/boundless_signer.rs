@@ -0,0 +1,127 @@
+// Boundless Signer - remote signing service
+//
+// Serves KeepBox keys to miners and payout bots over a local HTTP/JSON API
+// so they can get signatures without holding a mnemonic. Every key has a
+// policy (recipients, amount per transaction and per day, fee, nonce gaps),
+// a protection database refuses to sign two different transactions with
+// the same nonce, and every decision is logged as one JSON line.
+//
+// The policy, protection and HTTP code live in the `boundless_wallet::policy`,
+// `boundless_wallet::signer` and `boundless_wallet::http` library modules;
+// this binary unlocks the KeepBoxes and runs the server.
+//
+// Usage:
+//   boundless-signer serve --config signer.json
+
+use boundless_wallet::output::{self, OutputFormat};
+use boundless_wallet::prompt::{set_password_source, unlock_keepboxes, PasswordArgs};
+use boundless_wallet::signer::{Config, RemoteSigner};
+use boundless_wallet::{say, Error, Result};
+use clap::{Parser, Subcommand};
+use serde_json::{json, Value};
+use std::net::TcpListener;
+use std::path::PathBuf;
+
+// ===== CLI Structure =====
+
+#[derive(Parser)]
+#[command(name = "boundless-signer")]
+#[command(about = "Boundless BLS Blockchain - Policy-enforcing remote signer", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    #[command(flatten)]
+    password: PasswordArgs,
+
+    /// Output format: text, or json for one machine-readable document on stdout
+    #[arg(long, global = true, value_name = "FORMAT", default_value = "text")]
+    output_format: OutputFormat,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Unlock the configured KeepBoxes and serve signing requests until killed
+    Serve {
+        /// Signer configuration (JSON): keepboxes, policies, protection_db,
+        /// log_file, token_file, listen
+        #[arg(short, long)]
+        config: PathBuf,
+
+        /// Address to listen on, overriding the configuration
+        #[arg(short, long, value_name = "ADDR")]
+        listen: Option<String>,
+    },
+}
+
+// ===== Commands =====
+
+fn cmd_serve(config_path: PathBuf, listen: Option<String>) -> Result<Value> {
+    let mut config = Config::load(&config_path)?;
+    if let Some(listen) = listen {
+        config.listen = Some(listen);
+    }
+    if config.keepboxes.is_empty() {
+        return Err(Error::InvalidArgument(format!("{} lists no keepboxes", config_path.display())));
+    }
+    let mut signer = RemoteSigner::new(&config)?;
+
+    say!("🔑 Starting remote signer");
+    say!();
+    unlock_keepboxes(&config.keepboxes, signer.keys_mut())?;
+
+    let keys = signer.keys().list();
+    for key in &keys {
+        if signer.policy(&key.address).is_none() {
+            output::warn(format!("{} has no policy and will refuse every request", key.address));
+        }
+    }
+    for address in config.policies.keys() {
+        if !keys.iter().any(|key| key.address == *address) {
+            output::warn(format!("policy for {} matches no unlocked key", address));
+        }
+    }
+
+    let listener = TcpListener::bind(config.listen())
+        .map_err(|e| Error::io(format!("Failed to listen on {}", config.listen()), e))?;
+    let local = listener.local_addr().map_err(|e| Error::io("Failed to read listen address", e))?;
+    if !local.ip().is_loopback() && config.token_file.is_none() {
+        output::warn(format!(
+            "listening on {} without a token_file; anyone who can reach it can request signatures",
+            local
+        ));
+    }
+    #[cfg(unix)]
+    if let Err(e) = signer.keys_mut().lock_memory() {
+        output::warn(format!("Could not lock key memory ({}); keys may be swapped to disk", e));
+    }
+
+    say!();
+    say!("✅ Signing for {} key(s) on http://{}", keys.len(), local);
+    say!("   Protection database: {}", config.protection_db.display());
+    match &config.log_file {
+        Some(log) => say!("   Decision log:        {}", log.display()),
+        None => say!("   Decisions are logged to stderr"),
+    }
+
+    signer.serve(&listener)?;
+
+    Ok(json!({
+        "listen": local.to_string(),
+        "keys": keys,
+    }))
+}
+
+// ===== Main =====
+
+fn main() {
+    let cli: Cli = output::parse_or_exit("boundless-signer");
+    output::set_output_format(cli.output_format);
+    set_password_source(cli.password.source());
+
+    let (command, result) = match cli.command {
+        Commands::Serve { config, listen } => ("serve", cmd_serve(config, listen)),
+    };
+
+    output::finish(command, result)
+}
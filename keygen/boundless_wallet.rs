@@ -0,0 +1,1447 @@
+//! Boundless Wallet - transaction tool
+//!
+//! Builds and signs transfers offline without Python: the signed output is
+//! byte-identical to `send_transaction.py`, and keys are only ever read from
+//! an encrypted KeepBox. `sign`, `tx sign` and `send` use a running key agent
+//! (`boundless-keepbox agent start`) that holds the wallet's key instead of
+//! prompting for the password.
+//!
+//! `balance`, `nonce`, `send`, `tx-status` and `block-number` talk to a node
+//! over JSON-RPC (`--rpc-url`, default http://localhost:9933).
+//!
+//! Usage:
+//!   boundless-wallet tx build --keepbox wallet.keepbox --to <address> --amount 1.5 --nonce 0
+//!   boundless-wallet tx sign --keepbox wallet.keepbox --tx tx_unsigned.json
+//!   boundless-wallet tx create --keepbox wallet.keepbox --to <address> --amount 1.5
+//!   boundless-wallet tx broadcast --tx tx_signed.json
+//!   boundless-wallet sign --keepbox wallet.keepbox --message-file tx_hash.txt --output signature.sig
+//!   boundless-wallet verify-signature --pubkey <hex> --address <hex> --signature-file signature.sig --message-file tx_hash.txt
+//!   boundless-wallet split --keepbox wallet.keepbox --threshold 3 --shares 5 --output-dir shares/
+//!   boundless-wallet combine --output wallet.keepbox shares/share-1-1.txt shares/share-1-3.txt shares/share-1-4.txt
+//!   boundless-wallet balance --keepbox wallet.keepbox
+//!   boundless-wallet send --keepbox wallet.keepbox --to <address> --amount 1.5
+//!   boundless-wallet tx-status <hash>
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[cfg(unix)]
+use boundless_wallet::agent::AgentClient;
+use boundless_wallet::envelope::{self, Encoding, SignedEnvelope, UnsignedEnvelope};
+use boundless_wallet::files;
+use boundless_wallet::message::{sign_message, verify_message};
+use boundless_wallet::output::{self, OutputFormat};
+use boundless_wallet::prompt::{
+    passphrase_for, prompt_bip39_passphrase, prompt_password, set_password_source, PasswordArgs,
+};
+use boundless_wallet::rpc::{self, RpcClient, TransactionInfo};
+use boundless_wallet::slip39::{self, GroupSpec, Share};
+use boundless_wallet::tx::{format_amount, parse_amount};
+use boundless_wallet::{
+    say, Address, Derivation, Error, KeepBox, KeepBoxFile, KeyType, Keypair, Language, Mnemonic, Result, Signer, Transaction,
+    WalletData,
+};
+use clap::{ArgGroup, Parser, Subcommand};
+use serde_json::{json, Value};
+use zeroize::Zeroizing;
+
+// ===== CLI Structure =====
+
+#[derive(Parser)]
+#[command(name = "boundless-wallet")]
+#[command(about = "Boundless BLS Blockchain - Offline transaction builder and signer", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    #[command(flatten)]
+    password: PasswordArgs,
+
+    /// Always unlock the KeepBox, even if a key agent holds the wallet
+    #[arg(long, global = true)]
+    no_agent: bool,
+
+    #[command(flatten)]
+    rpc: RpcArgs,
+
+    /// Output format: text, or json for one machine-readable document on stdout
+    #[arg(long, global = true, value_name = "FORMAT", default_value = "text")]
+    output_format: OutputFormat,
+}
+
+/// Node connection for `balance`, `nonce`, `send`, `tx-status` and
+/// `block-number`
+#[derive(clap::Args)]
+struct RpcArgs {
+    /// Node JSON-RPC endpoint
+    #[arg(long, global = true, value_name = "URL", default_value = rpc::DEFAULT_URL)]
+    rpc_url: String,
+
+    /// Seconds to wait for the node to connect and to answer each call
+    #[arg(long, global = true, value_name = "SECONDS", default_value_t = rpc::DEFAULT_TIMEOUT.as_secs())]
+    rpc_timeout: u64,
+
+    /// Extra attempts for calls that fail before the node answers
+    /// (a sent transaction is never resubmitted)
+    #[arg(long, global = true, value_name = "N", default_value_t = rpc::DEFAULT_RETRIES)]
+    rpc_retries: u32,
+}
+
+impl RpcArgs {
+    fn client(&self) -> Result<RpcClient> {
+        if self.rpc_timeout == 0 {
+            return Err(Error::InvalidArgument("--rpc-timeout must be at least 1 second".to_string()));
+        }
+        Ok(RpcClient::new(&self.rpc_url)?
+            .with_timeout(Duration::from_secs(self.rpc_timeout))
+            .with_retries(self.rpc_retries))
+    }
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Build and sign transactions
+    Tx {
+        #[command(subcommand)]
+        command: TxCommands,
+    },
+
+    /// Sign a message with a KeepBox wallet (reads stdin if no message is given)
+    Sign {
+        /// KeepBox holding the signing wallet
+        #[arg(short, long, visible_alias = "keystore")]
+        keepbox: PathBuf,
+
+        /// Vault entry to sign with (label or address)
+        #[arg(short, long)]
+        entry: Option<String>,
+
+        #[command(flatten)]
+        message: MessageArgs,
+
+        /// Write the signature (hex) to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Verify a message signature and that the public key owns the address
+    #[command(group(ArgGroup::new("sig").required(true).args(["signature", "signature_file"])))]
+    VerifySignature {
+        /// Public key (hex-encoded)
+        #[arg(short, long)]
+        pubkey: String,
+
+        /// Expected address (hex-encoded)
+        #[arg(short, long)]
+        address: String,
+
+        /// Signature (hex-encoded)
+        #[arg(short, long)]
+        signature: Option<String>,
+
+        /// File containing the hex signature written by `sign --output`
+        #[arg(long)]
+        signature_file: Option<PathBuf>,
+
+        #[command(flatten)]
+        message: MessageArgs,
+    },
+
+    /// Split a wallet's mnemonic into SLIP-0039 Shamir shares
+    #[command(group(ArgGroup::new("secret").required(true).args(["keepbox", "mnemonic"])))]
+    #[command(group(ArgGroup::new("scheme").required(true).args(["threshold", "group"])))]
+    Split {
+        /// KeepBox holding the wallet to back up
+        #[arg(short, long)]
+        keepbox: Option<PathBuf>,
+
+        /// Vault entry to back up (label or address)
+        #[arg(short, long, requires = "keepbox")]
+        entry: Option<String>,
+
+        /// Split a mnemonic entered at a prompt instead of a KeepBox's
+        #[arg(short, long)]
+        mnemonic: bool,
+
+        /// Shares needed to recover the wallet
+        #[arg(long, requires = "shares", conflicts_with = "group")]
+        threshold: Option<u8>,
+
+        /// Number of shares to create (at most 16)
+        #[arg(long, requires = "threshold")]
+        shares: Option<u8>,
+
+        /// A group of shares as THRESHOLD-of-COUNT, e.g. 2-of-3; repeat for
+        /// several groups (for example one per officer team)
+        #[arg(long, value_name = "T-of-N")]
+        group: Vec<GroupSpec>,
+
+        /// Groups needed to recover the wallet when several are given
+        #[arg(long, requires = "group")]
+        group_threshold: Option<u8>,
+
+        /// Write each share to its own file (share-<group>-<member>.txt) in
+        /// this directory instead of printing them; required with
+        /// --output-format json
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+
+        /// PBKDF2 cost of the share encryption: 10000 << N iterations
+        #[arg(long, default_value_t = slip39::DEFAULT_ITERATION_EXPONENT)]
+        iteration_exponent: u8,
+    },
+
+    /// Rebuild a wallet from SLIP-0039 shares into a new KeepBox
+    Combine {
+        /// Files holding shares, one per line (if none are given, shares are
+        /// prompted for)
+        share_files: Vec<PathBuf>,
+
+        /// Output KeepBox file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Replace an existing output file, moving it to a timestamped backup first
+        #[arg(long)]
+        force: bool,
+
+        /// Optional label for the wallet
+        #[arg(short, long)]
+        label: Option<String>,
+
+        /// Fail unless the rebuilt wallet has this address
+        #[arg(long)]
+        address: Option<String>,
+
+        /// Derivation path the wallet used: "legacy" (default) or a hardened
+        /// SLIP-0010 path such as "m/44'/777'/0'/0'"
+        #[arg(long, conflicts_with = "account")]
+        path: Option<Derivation>,
+
+        /// Boundless account index the wallet used
+        #[arg(long)]
+        account: Option<u32>,
+
+        /// Key type: ed25519 (default), ml-dsa-44 or hybrid
+        #[arg(short = 't', long, default_value = "ed25519")]
+        key_type: KeyType,
+
+        /// Prompt for the BIP39 passphrase the wallet was generated with
+        #[arg(long)]
+        bip39_passphrase: bool,
+
+        /// Wordlist the original mnemonic was written in
+        #[arg(long, default_value = "english")]
+        language: Language,
+    },
+
+    /// Show an account's balance on the node
+    Balance {
+        #[command(flatten)]
+        account: AccountArgs,
+    },
+
+    /// Show the nonce the account's next transaction must use
+    Nonce {
+        #[command(flatten)]
+        account: AccountArgs,
+    },
+
+    /// Build, sign and broadcast a transfer in one step
+    Send {
+        /// KeepBox holding the sender's wallet
+        #[arg(short, long)]
+        keepbox: PathBuf,
+
+        /// Vault entry to send from (label or address)
+        #[arg(short, long)]
+        entry: Option<String>,
+
+        /// Recipient address (hex)
+        #[arg(long)]
+        to: String,
+
+        /// Amount in BLS (up to 18 decimals)
+        #[arg(long)]
+        amount: String,
+
+        /// Fee in BLS
+        #[arg(long, default_value = "0")]
+        fee: String,
+
+        /// Sender account nonce (default: asked from the node)
+        #[arg(long)]
+        nonce: Option<u64>,
+
+        /// Do not ask for confirmation before broadcasting
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Look up a transaction on the node
+    TxStatus {
+        /// Transaction hash (hex)
+        hash: String,
+    },
+
+    /// Show the node's current block height
+    BlockNumber,
+}
+
+/// Account queried by `balance` and `nonce`
+#[derive(clap::Args)]
+#[group(id = "account", required = true, multiple = false, args = ["address", "keepbox"])]
+struct AccountArgs {
+    /// Account address (hex)
+    #[arg(short, long)]
+    address: Option<String>,
+
+    /// Take the address from a KeepBox (no password needed)
+    #[arg(short, long)]
+    keepbox: Option<PathBuf>,
+
+    /// Vault entry (label or address)
+    #[arg(short, long, requires = "keepbox")]
+    entry: Option<String>,
+}
+
+/// Message input shared by `sign` and `verify-signature`
+#[derive(clap::Args)]
+struct MessageArgs {
+    /// Message (hex-encoded)
+    #[arg(short, long, conflicts_with = "message_file")]
+    message: Option<String>,
+
+    /// Read the raw message bytes from a file
+    #[arg(long)]
+    message_file: Option<PathBuf>,
+
+    /// Sign / verify with the "Boundless Signed Message" domain prefix
+    #[arg(long)]
+    prefixed: bool,
+}
+
+#[derive(Subcommand)]
+enum TxCommands {
+    /// Build an unsigned transfer
+    #[command(group(ArgGroup::new("sender").required(true).args(["from", "keepbox"])))]
+    Build {
+        /// Sender address (hex)
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Take the sender address from a KeepBox (no password needed)
+        #[arg(short, long)]
+        keepbox: Option<PathBuf>,
+
+        /// Vault entry to send from (label or address)
+        #[arg(short, long, requires = "keepbox")]
+        entry: Option<String>,
+
+        /// Recipient address (hex)
+        #[arg(long)]
+        to: String,
+
+        /// Amount in BLS (up to 18 decimals)
+        #[arg(long)]
+        amount: String,
+
+        /// Fee in BLS
+        #[arg(long, default_value = "0")]
+        fee: String,
+
+        /// Sender account nonce
+        #[arg(long)]
+        nonce: u64,
+
+        /// Output file
+        #[arg(short, long, default_value = "tx_unsigned.json")]
+        output: PathBuf,
+    },
+
+    /// Sign an unsigned transfer with a KeepBox wallet
+    Sign {
+        /// KeepBox holding the sender's wallet
+        #[arg(short, long)]
+        keepbox: PathBuf,
+
+        /// Vault entry to sign with (default: the transaction's sender)
+        #[arg(short, long)]
+        entry: Option<String>,
+
+        /// Unsigned transaction file from `tx build`, or envelope from
+        /// `tx create` (signed into an envelope for `tx broadcast`, in the
+        /// same encoding)
+        #[arg(long)]
+        tx: PathBuf,
+
+        /// Output file for the signed transaction
+        #[arg(short, long, default_value = "tx_signed.json")]
+        output: PathBuf,
+    },
+
+    /// Create an unsigned transaction envelope to sign offline (no password
+    /// needed)
+    #[command(group(ArgGroup::new("sender").required(true).args(["from", "keepbox"])))]
+    Create {
+        /// Sender address (hex)
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Take the sender address from a KeepBox (no password needed)
+        #[arg(short, long)]
+        keepbox: Option<PathBuf>,
+
+        /// Vault entry to send from (label or address)
+        #[arg(short, long, requires = "keepbox")]
+        entry: Option<String>,
+
+        /// Recipient address (hex)
+        #[arg(long)]
+        to: String,
+
+        /// Amount in BLS (up to 18 decimals)
+        #[arg(long)]
+        amount: String,
+
+        /// Fee in BLS
+        #[arg(long, default_value = "0")]
+        fee: String,
+
+        /// Sender account nonce (default: asked from the node)
+        #[arg(long)]
+        nonce: Option<u64>,
+
+        /// Network the transaction is meant for
+        #[arg(long, default_value = envelope::DEFAULT_CHAIN_ID)]
+        chain_id: String,
+
+        /// Envelope encoding: json or cbor
+        #[arg(long, default_value = "json")]
+        encoding: Encoding,
+
+        /// Output file
+        #[arg(short, long, default_value = "tx_unsigned.json")]
+        output: PathBuf,
+    },
+
+    /// Check a transaction envelope and show what it does (no keys or
+    /// network needed)
+    Inspect {
+        /// Unsigned or signed envelope
+        #[arg(long)]
+        tx: PathBuf,
+    },
+
+    /// Check a signed envelope and submit its transaction to the node
+    Broadcast {
+        /// Signed envelope from `tx sign`
+        #[arg(long)]
+        tx: PathBuf,
+
+        /// Refuse envelopes created for another network
+        #[arg(long, default_value = envelope::DEFAULT_CHAIN_ID)]
+        chain_id: String,
+
+        /// Do not ask for confirmation before broadcasting
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+// ===== Helpers =====
+
+/// Decrypt a KeepBox (or one vault entry) and re-derive its signing keypair
+fn unlock_keypair(keepbox: &KeepBoxFile, entry: Option<&str>) -> Result<Keypair> {
+    let password = prompt_password("Enter password: ", false)?;
+    say!();
+
+    let wallet_data = keepbox.unseal_wallet(&password, entry)?;
+    say!("✓ Decrypted wallet data");
+
+    let passphrase = passphrase_for(&wallet_data)?;
+    wallet_data.keypair(&passphrase)
+}
+
+/// The key agent's signer for `address`, if one is running and holds it
+#[cfg(unix)]
+fn agent_signer(address: &str) -> Option<Box<dyn Signer>> {
+    let client = AgentClient::from_env()?;
+    match client.signer(address) {
+        Ok(signer) => {
+            say!("✓ Using key agent at {}", client.socket().display());
+            signer.map(|s| Box::new(s) as Box<dyn Signer>)
+        }
+        Err(e) => {
+            output::warn(format!("{}; unlocking the KeepBox instead", e));
+            None
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn agent_signer(_address: &str) -> Option<Box<dyn Signer>> {
+    None
+}
+
+/// Signer for a KeepBox wallet: the key agent if it holds the key, otherwise
+/// the decrypted KeepBox. The flag says whether the agent signs.
+fn signer_for(keepbox: &KeepBoxFile, entry: Option<&str>, use_agent: bool) -> Result<(Box<dyn Signer>, bool)> {
+    if use_agent {
+        if let Some(signer) = agent_signer(keepbox.address(entry)?) {
+            return Ok((signer, true));
+        }
+    }
+    Ok((Box::new(unlock_keypair(keepbox, entry)?), false))
+}
+
+/// Address from `--address`, or from a KeepBox's public metadata
+fn account_address(account: &AccountArgs) -> Result<String> {
+    let address = match (&account.address, &account.keepbox) {
+        (Some(address), _) => address.clone(),
+        (None, Some(path)) => KeepBoxFile::load(path)?.address(account.entry.as_deref())?.to_string(),
+        (None, None) => unreachable!("clap requires --address or --keepbox"),
+    };
+    Ok(address.parse::<Address>()?.to_hex())
+}
+
+/// Message bytes from `--message`, `--message-file` or stdin
+fn read_message(args: &MessageArgs) -> Result<Vec<u8>> {
+    if let Some(message) = &args.message {
+        return hex::decode(message.trim())
+            .map_err(|e| Error::Encoding(format!("Invalid message hex: {}", e)));
+    }
+    if let Some(path) = &args.message_file {
+        return fs::read(path).map_err(|e| Error::io("Failed to read message file", e));
+    }
+
+    let mut message = Vec::new();
+    io::stdin()
+        .read_to_end(&mut message)
+        .map_err(|e| Error::io("Failed to read message from stdin", e))?;
+    Ok(message)
+}
+
+fn decode_hex_arg(name: &str, value: &str) -> Result<Vec<u8>> {
+    hex::decode(value.trim()).map_err(|e| Error::Encoding(format!("Invalid {} hex: {}", name, e)))
+}
+
+fn print_transaction(tx: &Transaction) {
+    say!("   From:   {}", tx.from);
+    say!("   To:     {}", tx.to);
+    say!("   Amount: {} BLS", format_amount(tx.amount));
+    say!("   Fee:    {} BLS", format_amount(tx.fee));
+    say!("   Nonce:  {}", tx.nonce);
+}
+
+fn read_transaction_file(path: &PathBuf) -> Result<Vec<u8>> {
+    fs::read(path).map_err(|e| Error::io("Failed to read transaction file", e))
+}
+
+fn parse_transaction(json: &[u8]) -> Result<Transaction> {
+    let tx: Transaction = serde_json::from_slice(json)
+        .map_err(|e| Error::Encoding(format!("Failed to parse transaction JSON: {}", e)))?;
+    // Re-validate addresses of hand-edited files
+    Transaction::new(&tx.from, &tx.to, tx.amount, tx.nonce, tx.fee)
+}
+
+/// Sender from `--from`, or from a KeepBox's public metadata
+fn sender_address(from: Option<String>, keepbox_path: Option<PathBuf>, entry: Option<&str>) -> Result<String> {
+    match (from, keepbox_path) {
+        (Some(from), _) => Ok(from),
+        (None, Some(path)) => Ok(KeepBoxFile::load(&path)?.address(entry)?.to_string()),
+        (None, None) => unreachable!("clap requires --from or --keepbox"),
+    }
+}
+
+fn print_envelope(summary: &str, chain_id: &str, checksum: &str) {
+    say!("   {}", summary);
+    say!("   Chain:    {}", chain_id);
+    say!("   Checksum: {}", checksum);
+}
+
+/// Shares from `--group` options, or a single group from `--threshold`
+/// and `--shares`
+fn share_groups(
+    threshold: Option<u8>,
+    shares: Option<u8>,
+    groups: Vec<GroupSpec>,
+    group_threshold: Option<u8>,
+) -> Result<(u8, Vec<GroupSpec>)> {
+    if let (Some(threshold), Some(count)) = (threshold, shares) {
+        let group: GroupSpec = format!("{}-of-{}", threshold, count).parse()?;
+        return Ok((1, vec![group]));
+    }
+    match group_threshold {
+        Some(group_threshold) => Ok((group_threshold, groups)),
+        None if groups.len() == 1 => Ok((1, groups)),
+        None => Err(Error::InvalidArgument(
+            "--group-threshold is required with more than one --group".to_string(),
+        )),
+    }
+}
+
+/// Share mnemonics from the given files, or prompted for one per line
+/// until a blank line
+fn read_shares(share_files: &[PathBuf]) -> Result<Vec<Share>> {
+    let parse = |line: &str, source: &str| -> Result<Share> {
+        let share = Share::parse(line).map_err(|e| Error::InvalidShare(format!("{}: {}", source, e)))?;
+        say!("✓ Share {} of group {} ({})", share.member_index + 1, share.group_index + 1, source);
+        Ok(share)
+    };
+
+    let mut shares = Vec::new();
+
+    for path in share_files {
+        let contents = Zeroizing::new(
+            fs::read_to_string(path).map_err(|e| Error::io(format!("Failed to read {}", path.display()), e))?,
+        );
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            shares.push(parse(line, &path.display().to_string())?);
+        }
+    }
+
+    if share_files.is_empty() {
+        say!("Enter one share per line; finish with an empty line.");
+        loop {
+            output::prompt(&format!("Share {}: ", shares.len() + 1))?;
+            let mut line = Zeroizing::new(String::new());
+            io::stdin()
+                .read_line(&mut line)
+                .map_err(|e| Error::io("Failed to read share", e))?;
+            if line.trim().is_empty() {
+                break;
+            }
+            shares.push(parse(&line, "stdin")?);
+        }
+    }
+    say!();
+    Ok(shares)
+}
+
+/// Read one trimmed line typed at a prompt
+fn read_line(prompt: &str) -> Result<String> {
+    output::prompt(prompt)?;
+
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .map_err(|e| Error::io("Failed to read input", e))?;
+    Ok(input.trim().to_string())
+}
+
+/// Read a mnemonic phrase typed at a prompt
+fn read_mnemonic() -> Result<Mnemonic> {
+    output::prompt("Enter the mnemonic phrase to split:\n> ")?;
+
+    let mut input = Zeroizing::new(String::new());
+    io::stdin()
+        .read_line(&mut input)
+        .map_err(|e| Error::io("Failed to read input", e))?;
+    Mnemonic::parse(input.trim())
+}
+
+// ===== Command Implementations =====
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_tx_build(
+    from: Option<String>,
+    keepbox_path: Option<PathBuf>,
+    entry: Option<String>,
+    to: String,
+    amount: String,
+    fee: String,
+    nonce: u64,
+    output_path: PathBuf,
+) -> Result<Value> {
+    let from = sender_address(from, keepbox_path, entry.as_deref())?;
+    let tx = Transaction::new(&from, &to, parse_amount(&amount)?, nonce, parse_amount(&fee)?)?;
+
+    let json = serde_json::to_string_pretty(&tx)
+        .map_err(|e| Error::Encoding(format!("Failed to serialize transaction: {}", e)))?;
+    fs::write(&output_path, json).map_err(|e| Error::io("Failed to write transaction file", e))?;
+
+    say!("📤 Unsigned transaction:");
+    print_transaction(&tx);
+    say!();
+    say!("✅ Saved to: {}", output_path.display());
+    say!("💡 Sign it offline with: boundless-wallet tx sign --keepbox <file> --tx {}", output_path.display());
+
+    Ok(json!({
+        "output": output_path,
+        "transaction": tx.to_output(),
+    }))
+}
+
+fn cmd_tx_sign(
+    keepbox_path: PathBuf,
+    entry: Option<String>,
+    tx_path: PathBuf,
+    output_path: PathBuf,
+    use_agent: bool,
+) -> Result<Value> {
+    let bytes = read_transaction_file(&tx_path)?;
+    let envelope = match envelope::format_of(&bytes) {
+        Some(_) => Some(UnsignedEnvelope::from_bytes(&bytes)?),
+        None => None,
+    };
+    let tx = match &envelope {
+        Some(envelope) => envelope.transaction.clone(),
+        None => parse_transaction(&bytes)?,
+    };
+    let keepbox = KeepBoxFile::load(&keepbox_path)?;
+
+    // Vault entries default to the one holding the sender's wallet
+    let entry = match (&keepbox, entry) {
+        (_, Some(entry)) => Some(entry),
+        (KeepBoxFile::Vault(_), None) => Some(tx.from.clone()),
+        (KeepBoxFile::Wallet(_), None) => None,
+    };
+    keepbox.address(entry.as_deref())?;
+
+    say!("✍️  Signing transaction:");
+    print_transaction(&tx);
+    if let Some(envelope) = &envelope {
+        say!("✓ Envelope checksum verified");
+        print_envelope(&envelope.summary, &envelope.chain_id, &envelope.checksum);
+    }
+    say!();
+
+    let (keypair, agent) = signer_for(&keepbox, entry.as_deref(), use_agent)?;
+
+    let signed = tx.sign(&*keypair)?;
+    signed.verify(keypair.key_type(), &keypair.public_key_bytes())?;
+    say!("✓ Transaction signed");
+
+    let signed_envelope = envelope.map(|envelope| envelope.sign(&*keypair)).transpose()?;
+    let bytes = match &signed_envelope {
+        Some(signed_envelope) => signed_envelope.to_bytes(Encoding::detect(&bytes))?,
+        None => signed.to_json()?.into_bytes(),
+    };
+    fs::write(&output_path, &bytes).map_err(|e| Error::io("Failed to write transaction file", e))?;
+
+    say!();
+    match &signed_envelope {
+        Some(signed_envelope) => {
+            say!("   Signed checksum: {}", signed_envelope.checksum);
+            say!();
+            say!("✅ Signed envelope saved to: {}", output_path.display());
+            say!("💡 Broadcast it online with: boundless-wallet tx broadcast --tx {}", output_path.display());
+        }
+        None => {
+            say!("{}", String::from_utf8_lossy(&bytes));
+            say!();
+            say!("✅ Signed transaction saved to: {}", output_path.display());
+        }
+    }
+
+    let mut data = json!({
+        "output": output_path,
+        "public_key": hex::encode(keypair.public_key_bytes()),
+        "transaction": signed.to_output(),
+        "agent": agent,
+    });
+    if let Some(signed_envelope) = signed_envelope {
+        data["chain_id"] = json!(signed_envelope.chain_id);
+        data["checksum"] = json!(signed_envelope.checksum);
+        data["unsigned_checksum"] = json!(signed_envelope.unsigned_checksum);
+    }
+    Ok(data)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_tx_create(
+    rpc: &RpcArgs,
+    from: Option<String>,
+    keepbox_path: Option<PathBuf>,
+    entry: Option<String>,
+    to: String,
+    amount: String,
+    fee: String,
+    nonce: Option<u64>,
+    chain_id: String,
+    encoding: Encoding,
+    output_path: PathBuf,
+) -> Result<Value> {
+    let from = sender_address(from, keepbox_path, entry.as_deref())?;
+    let (amount, fee) = (parse_amount(&amount)?, parse_amount(&fee)?);
+    envelope::validate_chain_id(&chain_id)?;
+
+    // Without --nonce this is the online step: ask the node
+    let nonce = match nonce {
+        Some(nonce) => nonce,
+        None => {
+            let client = rpc.client()?;
+            let nonce = client.nonce(&from)?;
+            let balance = client.balance(&from)?;
+            say!("⛓️  Nonce {} from {}", nonce, client.endpoint());
+            if balance < amount.saturating_add(fee) {
+                output::warn(format!(
+                    "balance {} BLS does not cover amount plus fee; the node will reject the transaction unless it is funded first",
+                    format_amount(balance)
+                ));
+            }
+            nonce
+        }
+    };
+
+    let envelope = UnsignedEnvelope::new(Transaction::new(&from, &to, amount, nonce, fee)?, &chain_id)?;
+    fs::write(&output_path, envelope.to_bytes(encoding)?).map_err(|e| Error::io("Failed to write transaction file", e))?;
+
+    say!("📤 Unsigned transaction envelope:");
+    print_transaction(&envelope.transaction);
+    print_envelope(&envelope.summary, &envelope.chain_id, &envelope.checksum);
+    say!();
+    say!("✅ Saved to: {}", output_path.display());
+    say!(
+        "💡 Sign it on the offline machine with: boundless-wallet tx sign --keepbox <file> --tx {}",
+        output_path.display()
+    );
+
+    Ok(json!({
+        "output": output_path,
+        "chain_id": envelope.chain_id,
+        "summary": envelope.summary,
+        "checksum": envelope.checksum,
+        "transaction": envelope.transaction.to_output(),
+    }))
+}
+
+fn cmd_tx_inspect(tx_path: PathBuf) -> Result<Value> {
+    let bytes = read_transaction_file(&tx_path)?;
+    let format = envelope::format_of(&bytes).ok_or_else(|| {
+        Error::Encoding(format!(
+            "{} is a bare transaction without a checksum; create envelopes with `tx create`",
+            tx_path.display()
+        ))
+    })?;
+
+    if format == envelope::SIGNED_FORMAT {
+        let signed = SignedEnvelope::from_bytes(&bytes)?;
+        say!("🔎 Signed transaction envelope (checksum and signature verified):");
+        print_transaction(&signed.transaction.transaction());
+        print_envelope(&signed.summary, &signed.chain_id, &signed.checksum);
+        say!("   Unsigned: {}", signed.unsigned_checksum);
+        say!("   Created:  {}", signed.created);
+        return Ok(json!({
+            "signed": true,
+            "chain_id": signed.chain_id,
+            "created": signed.created,
+            "summary": signed.summary,
+            "checksum": signed.checksum,
+            "unsigned_checksum": signed.unsigned_checksum,
+            "public_key": signed.public_key,
+            "transaction": signed.transaction.to_output(),
+        }));
+    }
+
+    let unsigned = UnsignedEnvelope::from_bytes(&bytes)?;
+    say!("🔎 Unsigned transaction envelope (checksum verified):");
+    print_transaction(&unsigned.transaction);
+    print_envelope(&unsigned.summary, &unsigned.chain_id, &unsigned.checksum);
+    say!("   Created:  {}", unsigned.created);
+    Ok(json!({
+        "signed": false,
+        "chain_id": unsigned.chain_id,
+        "created": unsigned.created,
+        "summary": unsigned.summary,
+        "checksum": unsigned.checksum,
+        "transaction": unsigned.transaction.to_output(),
+    }))
+}
+
+fn cmd_tx_broadcast(client: RpcClient, tx_path: PathBuf, chain_id: String, yes: bool) -> Result<Value> {
+    let envelope = SignedEnvelope::from_bytes(&read_transaction_file(&tx_path)?)?;
+    if envelope.chain_id != chain_id {
+        return Err(Error::InvalidTransaction(format!(
+            "envelope is for chain {}, not {}; pass --chain-id {} if that is intended",
+            envelope.chain_id, chain_id, envelope.chain_id
+        )));
+    }
+
+    say!("📡 Broadcasting signed transaction (checksum and signature verified):");
+    print_transaction(&envelope.transaction.transaction());
+    print_envelope(&envelope.summary, &envelope.chain_id, &envelope.checksum);
+    say!();
+
+    if !yes {
+        let confirmation = read_line("Broadcast transaction? (yes/no): ")?;
+        if confirmation != "yes" {
+            say!("❌ Cancelled");
+            return Ok(json!({
+                "sent": false,
+                "hash": null,
+                "chain_id": envelope.chain_id,
+                "checksum": envelope.checksum,
+                "transaction": envelope.transaction.to_output(),
+            }));
+        }
+        say!();
+    }
+
+    let hash = client.submit_transaction(&envelope.transaction)?;
+    say!("✅ Transaction submitted to {}", client.endpoint());
+    say!("   Hash: {}", hash);
+    say!("💡 Follow it with: boundless-wallet tx-status {}", hash);
+
+    Ok(json!({
+        "sent": true,
+        "hash": hash,
+        "chain_id": envelope.chain_id,
+        "checksum": envelope.checksum,
+        "transaction": envelope.transaction.to_output(),
+    }))
+}
+
+fn cmd_sign(
+    keepbox_path: PathBuf,
+    entry: Option<String>,
+    message_args: MessageArgs,
+    output_path: Option<PathBuf>,
+    password_stdin: bool,
+    use_agent: bool,
+) -> Result<Value> {
+    if password_stdin && message_args.message.is_none() && message_args.message_file.is_none() {
+        return Err(Error::InvalidArgument(
+            "cannot read both the message and the password from stdin; use --message-file or --password-fd".to_string(),
+        ));
+    }
+
+    let message = read_message(&message_args)?;
+    let keepbox = KeepBoxFile::load(&keepbox_path)?;
+
+    say!("✍️  Signing {} byte message{}", message.len(), if message_args.prefixed { " (Boundless Signed Message prefix)" } else { "" });
+    let address = keepbox.address(entry.as_deref())?.to_string();
+    say!("   Address: {}", address);
+    say!();
+
+    let (keypair, agent) = signer_for(&keepbox, entry.as_deref(), use_agent)?;
+    let signature = hex::encode(sign_message(&*keypair, &message, message_args.prefixed)?);
+    say!("✓ Message signed");
+    say!();
+
+    let public_key = hex::encode(keypair.public_key_bytes());
+    say!("🔐 Public Key: {}", public_key);
+    match &output_path {
+        Some(path) => {
+            fs::write(path, format!("{}\n", signature))
+                .map_err(|e| Error::io("Failed to write signature file", e))?;
+            say!("✅ Signature saved to: {}", path.display());
+        }
+        None => say!("✍️  Signature:  {}", signature),
+    }
+
+    Ok(json!({
+        "address": address,
+        "public_key": public_key,
+        "key_type": keypair.key_type(),
+        "prefixed": message_args.prefixed,
+        "signature": signature,
+        "output": output_path,
+        "agent": agent,
+    }))
+}
+
+fn cmd_verify_signature(
+    pubkey: String,
+    address: String,
+    signature: Option<String>,
+    signature_file: Option<PathBuf>,
+    message_args: MessageArgs,
+) -> Result<Value> {
+    let signature = match (signature, signature_file) {
+        (Some(signature), _) => signature,
+        (None, Some(path)) => fs::read_to_string(path).map_err(|e| Error::io("Failed to read signature file", e))?,
+        (None, None) => unreachable!("clap requires --signature or --signature-file"),
+    };
+
+    let public_key = decode_hex_arg("public key", &pubkey)?;
+    let signature = decode_hex_arg("signature", &signature)?;
+    let message = read_message(&message_args)?;
+
+    let key_type = verify_message(&public_key, &address, &message, &signature, message_args.prefixed)?;
+
+    say!("Address:   {}", address);
+    say!("Key type:  {}", key_type);
+    say!();
+    say!("✅ Signature valid and public key matches address.");
+
+    Ok(json!({
+        "address": address,
+        "public_key": pubkey.trim(),
+        "key_type": key_type,
+        "prefixed": message_args.prefixed,
+        "valid": true,
+    }))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_split(
+    keepbox_path: Option<PathBuf>,
+    entry: Option<String>,
+    threshold: Option<u8>,
+    shares: Option<u8>,
+    groups: Vec<GroupSpec>,
+    group_threshold: Option<u8>,
+    output_dir: Option<PathBuf>,
+    iteration_exponent: u8,
+) -> Result<Value> {
+    if output::is_json() && output_dir.is_none() {
+        return Err(Error::InvalidArgument(
+            "--output-dir is required with --output-format json so shares never appear in the output".to_string(),
+        ));
+    }
+    let (group_threshold, groups) = share_groups(threshold, shares, groups, group_threshold)?;
+
+    let paths: Vec<Vec<PathBuf>> = groups
+        .iter()
+        .enumerate()
+        .map(|(g, group)| {
+            (1..=group.count)
+                .map(|m| output_dir.as_deref().unwrap_or(Path::new(".")).join(format!("share-{}-{}.txt", g + 1, m)))
+                .collect()
+        })
+        .collect();
+    if let Some(dir) = &output_dir {
+        for path in paths.iter().flatten() {
+            files::ensure_absent(path, false)?;
+        }
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        builder
+            .create(dir)
+            .map_err(|e| Error::io(format!("Failed to create {}", dir.display()), e))?;
+    }
+
+    // The wallet's address and derivation, when it comes from a KeepBox
+    let (mnemonic, wallet) = match &keepbox_path {
+        Some(path) => {
+            let keepbox = KeepBoxFile::load(path)?;
+            let password = prompt_password("Enter password: ", false)?;
+            say!();
+            let wallet_data = keepbox.unseal_wallet(&password, entry.as_deref())?;
+            say!("✓ Decrypted wallet data");
+            (Mnemonic::parse_in(wallet_data.language, &wallet_data.mnemonic)?, Some(wallet_data))
+        }
+        None => (read_mnemonic()?, None),
+    };
+
+    let split = slip39::split(&mnemonic.entropy(), "", group_threshold, &groups, iteration_exponent)?;
+    let identifier = split[0][0].identifier;
+
+    say!("🧩 Split {}-word mnemonic into SLIP-0039 shares (id {})", mnemonic.word_count(), identifier);
+    if groups.len() == 1 {
+        say!("   Any {} of {} shares recover the wallet", groups[0].threshold, groups[0].count);
+    } else {
+        say!("   Any {} of {} groups recover the wallet:", group_threshold, groups.len());
+        for (g, group) in groups.iter().enumerate() {
+            say!("   Group {}: {} of {} shares", g + 1, group.threshold, group.count);
+        }
+    }
+    say!();
+
+    for (g, group_shares) in split.iter().enumerate() {
+        for (m, share) in group_shares.iter().enumerate() {
+            let mnemonic = share.to_mnemonic();
+            match &output_dir {
+                Some(_) => {
+                    let path = &paths[g][m];
+                    files::create_private_file(path, format!("{}\n", mnemonic.as_str()).as_bytes())?;
+                    say!("✓ Share {}-{} saved to: {}", g + 1, m + 1, path.display());
+                }
+                None => {
+                    say!("Share {}-{}:", g + 1, m + 1);
+                    say!("{}", mnemonic.as_str());
+                    say!();
+                }
+            }
+        }
+    }
+    say!();
+
+    if mnemonic.language() != Language::English {
+        output::warn(format!(
+            "The mnemonic is in {}: combine needs --language {} to rebuild the same words and keys",
+            mnemonic.language(),
+            mnemonic.language()
+        ));
+    }
+    if let Some(wallet) = &wallet {
+        say!("   Address: {}", wallet.address);
+        if wallet.has_passphrase {
+            output::warn("This wallet uses a BIP39 passphrase; the shares do not include it and it is needed again to combine them");
+        }
+        if wallet.key_type != KeyType::Ed25519 || wallet.derivation != Derivation::Legacy {
+            output::warn(format!(
+                "Record the key type ({}) and derivation ({}): combine needs them to rebuild this wallet",
+                wallet.key_type, wallet.derivation
+            ));
+        }
+    }
+    say!("⚠️  Give each share to a different holder and store them separately.");
+    say!("    Fewer shares than the threshold reveal nothing; enough of them rebuild the wallet with:");
+    say!("    boundless-wallet combine --output wallet.keepbox <share files>");
+
+    let mut data = json!({
+        "language": mnemonic.language(),
+        "identifier": identifier,
+        "group_threshold": group_threshold,
+        "groups": groups.iter().zip(&paths).map(|(group, paths)| json!({
+            "threshold": group.threshold,
+            "count": group.count,
+            "files": if output_dir.is_some() { json!(paths) } else { Value::Null },
+        })).collect::<Vec<_>>(),
+    });
+    if let Some(wallet) = &wallet {
+        data["address"] = json!(wallet.address);
+        data["key_type"] = json!(wallet.key_type);
+        data["derivation"] = json!(wallet.derivation);
+    }
+    Ok(data)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_combine(
+    share_files: Vec<PathBuf>,
+    output_path: PathBuf,
+    force: bool,
+    label: Option<String>,
+    address: Option<String>,
+    path: Option<Derivation>,
+    account: Option<u32>,
+    key_type: KeyType,
+    bip39_passphrase: bool,
+    language: Language,
+    password_stdin: bool,
+) -> Result<Value> {
+    if password_stdin && share_files.is_empty() {
+        return Err(Error::InvalidArgument(
+            "cannot read both the shares and the password from stdin; pass share files or use --password-fd".to_string(),
+        ));
+    }
+    files::ensure_absent(&output_path, force)?;
+    let derivation = Derivation::from_options(path, account)?;
+
+    say!("🧩 Combining SLIP-0039 shares...");
+    let shares = read_shares(&share_files)?;
+    let entropy = slip39::combine(&shares, "")?;
+    let mnemonic = Mnemonic::from_entropy_in(language, &entropy)?;
+    say!("✓ Recovered {}-word mnemonic from {} share(s)", mnemonic.word_count(), shares.len());
+
+    let passphrase = if bip39_passphrase {
+        prompt_bip39_passphrase(false)?
+    } else {
+        Zeroizing::new(String::new())
+    };
+    let wallet_data = WalletData::from_mnemonic(&mnemonic, &passphrase, key_type, &derivation)?;
+    say!("  Address: {}", wallet_data.address);
+    say!();
+
+    if let Some(expected) = address {
+        if !expected.trim().eq_ignore_ascii_case(&wallet_data.address) {
+            output::warn("Check --key-type, --path / --account, --language and --bip39-passphrase match the original wallet");
+            return Err(Error::AddressMismatch {
+                expected: expected.trim().to_string(),
+                derived: wallet_data.address.clone(),
+            });
+        }
+        say!("✓ Address matches");
+    }
+
+    say!("⚠️  Choose a strong password to encrypt the recovered wallet.");
+    say!();
+    let password = prompt_password("Enter password: ", true)?;
+    say!();
+
+    let keepbox = KeepBox::seal(&wallet_data, &password, label)?;
+    let backup = if force { files::backup_existing(&output_path)? } else { None };
+    keepbox.save(&output_path)?;
+    if let Some(backup) = &backup {
+        say!("✓ Moved the previous file to: {}", backup.display());
+    }
+
+    say!("✅ Recovered wallet saved to KeepBox: {}", output_path.display());
+
+    Ok(json!({
+        "keepbox": output_path,
+        "address": wallet_data.address,
+        "public_key": wallet_data.public_key,
+        "key_type": wallet_data.key_type,
+        "derivation": wallet_data.derivation,
+        "language": wallet_data.language,
+        "label": keepbox.metadata.label,
+        "version": keepbox.version,
+        "backup": backup,
+        "shares": shares.len(),
+    }))
+}
+
+fn cmd_balance(client: RpcClient, account: AccountArgs) -> Result<Value> {
+    let address = account_address(&account)?;
+    let balance = client.balance(&address)?;
+
+    say!("💰 Balance of {}", address);
+    say!("   {} BLS", format_amount(balance));
+
+    Ok(json!({
+        "address": address,
+        "balance": balance.to_string(),
+        "balance_bls": format_amount(balance),
+    }))
+}
+
+fn cmd_nonce(client: RpcClient, account: AccountArgs) -> Result<Value> {
+    let address = account_address(&account)?;
+    let nonce = client.nonce(&address)?;
+
+    say!("🔢 Next nonce of {}: {}", address, nonce);
+
+    Ok(json!({
+        "address": address,
+        "nonce": nonce,
+    }))
+}
+
+fn cmd_block_number(client: RpcClient) -> Result<Value> {
+    let block_number = client.block_number()?;
+
+    say!("⛓️  Block number: {}", block_number);
+
+    Ok(json!({ "block_number": block_number }))
+}
+
+fn cmd_tx_status(client: RpcClient, hash: String) -> Result<Value> {
+    let hash = hex::encode(decode_hex_arg("transaction hash", &hash)?);
+    let transaction = client.transaction(&hash)?;
+
+    match &transaction {
+        Some(tx) => {
+            say!("🔎 Transaction {}", hash);
+            say!("   Status: {}", tx.status.as_deref().unwrap_or("unknown"));
+            if let Some(block) = tx.block_number {
+                say!("   Block:  {}", block);
+            }
+            if let (Some(from), Some(to)) = (&tx.from, &tx.to) {
+                say!("   From:   {}", from);
+                say!("   To:     {}", to);
+            }
+            if let Some(amount) = tx.amount {
+                say!("   Amount: {} BLS", format_amount(amount));
+            }
+        }
+        None => say!("❓ The node does not know transaction {}", hash),
+    }
+
+    Ok(json!({
+        "hash": hash,
+        "found": transaction.is_some(),
+        "transaction": transaction.as_ref().map(TransactionInfo::to_output),
+    }))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cmd_send(
+    client: RpcClient,
+    keepbox_path: PathBuf,
+    entry: Option<String>,
+    to: String,
+    amount: String,
+    fee: String,
+    nonce: Option<u64>,
+    yes: bool,
+    password_stdin: bool,
+    use_agent: bool,
+) -> Result<Value> {
+    if password_stdin && !yes {
+        return Err(Error::InvalidArgument(
+            "cannot read both the confirmation and the password from stdin; pass --yes".to_string(),
+        ));
+    }
+
+    let keepbox = KeepBoxFile::load(&keepbox_path)?;
+    let from = keepbox.address(entry.as_deref())?.to_string();
+    let (amount, fee) = (parse_amount(&amount)?, parse_amount(&fee)?);
+
+    let block_number = client.block_number()?;
+    say!("⛓️  Connected to {} (block {})", client.endpoint(), block_number);
+    let nonce = match nonce {
+        Some(nonce) => nonce,
+        None => client.nonce(&from)?,
+    };
+    let tx = Transaction::new(&from, &to, amount, nonce, fee)?;
+
+    let balance = client.balance(&from)?;
+    let total = amount
+        .checked_add(fee)
+        .ok_or_else(|| Error::InvalidTransaction("amount plus fee overflows".to_string()))?;
+    if balance < total {
+        return Err(Error::InvalidTransaction(format!(
+            "balance {} BLS does not cover amount plus fee {} BLS",
+            format_amount(balance),
+            format_amount(total)
+        )));
+    }
+
+    say!();
+    say!("📤 Sending transaction:");
+    print_transaction(&tx);
+    say!("   Balance: {} BLS", format_amount(balance));
+    say!();
+
+    if !yes {
+        let confirmation = read_line("Send transaction? (yes/no): ")?;
+        if confirmation != "yes" {
+            say!("❌ Cancelled");
+            return Ok(json!({
+                "sent": false,
+                "hash": null,
+                "transaction": tx.to_output(),
+                "agent": false,
+            }));
+        }
+        say!();
+    }
+
+    let (keypair, agent) = signer_for(&keepbox, entry.as_deref(), use_agent)?;
+    let signed = tx.sign(&*keypair)?;
+    signed.verify(keypair.key_type(), &keypair.public_key_bytes())?;
+    say!("✓ Transaction signed");
+
+    let hash = client.submit_transaction(&signed)?;
+    say!();
+    say!("✅ Transaction submitted");
+    say!("   Hash: {}", hash);
+    say!("💡 Follow it with: boundless-wallet tx-status {}", hash);
+
+    Ok(json!({
+        "sent": true,
+        "hash": hash,
+        "transaction": signed.to_output(),
+        "agent": agent,
+    }))
+}
+
+fn main() {
+    let cli: Cli = output::parse_or_exit("boundless-wallet");
+    output::set_output_format(cli.output_format);
+    set_password_source(cli.password.source());
+
+    let (command, result) = match cli.command {
+        Commands::Tx { command } => match command {
+            TxCommands::Build {
+                from,
+                keepbox,
+                entry,
+                to,
+                amount,
+                fee,
+                nonce,
+                output,
+            } => ("tx build", cmd_tx_build(from, keepbox, entry, to, amount, fee, nonce, output)),
+            TxCommands::Sign {
+                keepbox,
+                entry,
+                tx,
+                output,
+            } => ("tx sign", cmd_tx_sign(keepbox, entry, tx, output, !cli.no_agent)),
+            TxCommands::Create {
+                from,
+                keepbox,
+                entry,
+                to,
+                amount,
+                fee,
+                nonce,
+                chain_id,
+                encoding,
+                output,
+            } => (
+                "tx create",
+                cmd_tx_create(&cli.rpc, from, keepbox, entry, to, amount, fee, nonce, chain_id, encoding, output),
+            ),
+            TxCommands::Inspect { tx } => ("tx inspect", cmd_tx_inspect(tx)),
+            TxCommands::Broadcast { tx, chain_id, yes } => (
+                "tx broadcast",
+                cli.rpc.client().and_then(|c| cmd_tx_broadcast(c, tx, chain_id, yes)),
+            ),
+        },
+        Commands::Sign {
+            keepbox,
+            entry,
+            message,
+            output,
+        } => ("sign", cmd_sign(keepbox, entry, message, output, cli.password.password_stdin, !cli.no_agent)),
+        Commands::VerifySignature {
+            pubkey,
+            address,
+            signature,
+            signature_file,
+            message,
+        } => ("verify-signature", cmd_verify_signature(pubkey, address, signature, signature_file, message)),
+        Commands::Split {
+            keepbox,
+            entry,
+            mnemonic: _,
+            threshold,
+            shares,
+            group,
+            group_threshold,
+            output_dir,
+            iteration_exponent,
+        } => (
+            "split",
+            cmd_split(keepbox, entry, threshold, shares, group, group_threshold, output_dir, iteration_exponent),
+        ),
+        Commands::Combine {
+            share_files,
+            output,
+            force,
+            label,
+            address,
+            path,
+            account,
+            key_type,
+            bip39_passphrase,
+            language,
+        } => (
+            "combine",
+            cmd_combine(
+                share_files,
+                output,
+                force,
+                label,
+                address,
+                path,
+                account,
+                key_type,
+                bip39_passphrase,
+                language,
+                cli.password.password_stdin,
+            ),
+        ),
+        Commands::Balance { account } => ("balance", cli.rpc.client().and_then(|c| cmd_balance(c, account))),
+        Commands::Nonce { account } => ("nonce", cli.rpc.client().and_then(|c| cmd_nonce(c, account))),
+        Commands::Send {
+            keepbox,
+            entry,
+            to,
+            amount,
+            fee,
+            nonce,
+            yes,
+        } => (
+            "send",
+            cli.rpc.client().and_then(|c| {
+                cmd_send(c, keepbox, entry, to, amount, fee, nonce, yes, cli.password.password_stdin, !cli.no_agent)
+            }),
+        ),
+        Commands::TxStatus { hash } => ("tx-status", cli.rpc.client().and_then(|c| cmd_tx_status(c, hash))),
+        Commands::BlockNumber => ("block-number", cli.rpc.client().and_then(cmd_block_number)),
+    };
+
+    output::finish(command, result)
+}
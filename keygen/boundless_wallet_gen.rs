@@ -1,204 +1,28 @@
 #!/usr/bin/env rust-script
-//! Boundless BLS Wallet Generator - Single File Rust Implementation
+//! Boundless BLS Wallet Generator
 //!
-//! Dependencies (add to Cargo.toml):
-//! ```toml
-//! [dependencies]
-//! bip39 = "2.0"
-//! ed25519-dalek = { version = "2.1", features = ["rand_core"] }
-//! sha3 = "0.10"
-//! hex = "0.4"
-//! rand = "0.8"
-//! serde = { version = "1.0", features = ["derive"] }
-//! serde_json = "1.0"
-//! clap = { version = "4.5", features = ["derive"] }
-//! zeroize = { version = "1.7", features = ["derive"] }
-//! ```
+//! Thin CLI over the `boundless_wallet` library (see `src/`), which holds the
+//! mnemonic, key derivation and address logic shared with `boundless-keepbox`.
 //!
 //! Usage:
 //!   cargo run -- generate
 //!   cargo run -- generate --show-private
 //!   cargo run -- generate --output wallet.json
-
-use std::fs;
-use std::path::PathBuf;
-
-use bip39::Mnemonic;
-use ed25519_dalek::{SigningKey, VerifyingKey};
-use sha3::{Digest, Sha3_256};
-use serde::{Serialize, Deserialize};
-use zeroize::{Zeroize, ZeroizeOnDrop};
-
-/// Wallet output structure (matches Boundless conventions)
-#[derive(Debug, Serialize, Deserialize)]
-pub struct WalletOutput {
-    /// BIP39 mnemonic phrase (24 words)
-    pub mnemonic: String,
-
-    /// Public key (hex-encoded)
-    pub public_key: String,
-
-    /// Boundless address (hex-encoded SHA3-256 of public key)
-    pub address: String,
-
-    /// Private key (hex-encoded) - ONLY included if --show-private flag is set
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub private_key: Option<String>,
-
-    /// Key type used ("Ed25519" or "ML-DSA-44")
-    pub key_type: String,
-}
-
-/// Secure private key wrapper with automatic zeroization
-#[derive(ZeroizeOnDrop)]
-struct SecretKeyMaterial {
-    #[zeroize(skip)]
-    _marker: std::marker::PhantomData<u8>,
-    bytes: Vec<u8>,
-}
-
-impl SecretKeyMaterial {
-    fn new(bytes: Vec<u8>) -> Self {
-        Self {
-            _marker: std::marker::PhantomData,
-            bytes,
-        }
-    }
-
-    fn as_bytes(&self) -> &[u8] {
-        &self.bytes
-    }
-}
-
-/// Generate a BIP39 mnemonic with specified entropy (24 words = 256 bits)
-fn generate_mnemonic() -> Mnemonic {
-    // bip39 2.2.0 API: generate from entropy
-    // 32 bytes (256 bits) = 24 words
-    let mut entropy = [0u8; 32];
-    getrandom::getrandom(&mut entropy).expect("Failed to generate entropy");
-    Mnemonic::from_entropy(&entropy).expect("Failed to create mnemonic")
-}
-
-/// Derive seed from mnemonic (BIP39 standard)
-fn mnemonic_to_seed(mnemonic: &Mnemonic, passphrase: &str) -> [u8; 64] {
-    let seed_bytes = mnemonic.to_seed(passphrase);
-    let mut seed = [0u8; 64];
-    // seed_bytes is already 64 bytes from BIP39
-    seed.copy_from_slice(&seed_bytes[..64]);
-    seed
-}
-
-/// Generate Ed25519 keypair from seed
-/// Uses first 32 bytes of seed as private key material
-fn generate_ed25519_keypair(seed: &[u8; 64]) -> (SecretKeyMaterial, VerifyingKey) {
-    // Use first 32 bytes of seed for Ed25519 private key
-    let mut secret_bytes = [0u8; 32];
-    secret_bytes.copy_from_slice(&seed[..32]);
-
-    let signing_key = SigningKey::from_bytes(&secret_bytes);
-    let verifying_key = signing_key.verifying_key();
-
-    // Zeroize the temporary array
-    secret_bytes.zeroize();
-
-    let secret_material = SecretKeyMaterial::new(signing_key.to_bytes().to_vec());
-
-    (secret_material, verifying_key)
-}
-
-/// Derive Boundless address from public key
-///
-/// Address derivation (from boundless-bls-platform/enterprise/src/services/wallet.rs:530-543):
-/// ```rust
-/// fn derive_address(&self) -> String {
-///     let mut hasher = Sha3_256::new();
-///     hasher.update(&self.public_key);
-///     let hash = hasher.finalize();
-///     hex::encode(&hash)  // Full 32 bytes as hex (64 characters)
-/// }
-/// ```
-fn derive_address(public_key: &[u8]) -> String {
-    let mut hasher = Sha3_256::new();
-    hasher.update(public_key);
-    let hash = hasher.finalize();
-
-    // Return full 32-byte hash as hex (64 characters)
-    // NO version byte, NO checksum - matches Boundless exactly
-    hex::encode(&hash)
-}
-
-/// Main wallet generation function
-fn generate_wallet(show_private: bool, passphrase: Option<&str>) -> WalletOutput {
-    // 1. Generate 24-word BIP39 mnemonic
-    let mnemonic = generate_mnemonic();
-    let mnemonic_phrase = mnemonic.to_string();
-
-    println!("✓ Generated 24-word mnemonic");
-
-    // 2. Derive seed from mnemonic
-    let seed = mnemonic_to_seed(&mnemonic, passphrase.unwrap_or(""));
-
-    println!("✓ Derived seed from mnemonic");
-
-    // 3. Generate Ed25519 keypair (classical signature scheme)
-    // Note: For PQC (ML-DSA-44), you would use the oqs crate as in the Boundless codebase
-    let (secret_key, public_key) = generate_ed25519_keypair(&seed);
-
-    println!("✓ Generated Ed25519 keypair");
-
-    // 4. Derive Boundless address from public key
-    let public_key_bytes = public_key.as_bytes();
-    let address = derive_address(public_key_bytes);
-
-    println!("✓ Derived Boundless address");
-
-    // 5. Create output structure
-    let private_key_hex = if show_private {
-        Some(hex::encode(secret_key.as_bytes()))
-    } else {
-        None
-    };
-
-    WalletOutput {
-        mnemonic: mnemonic_phrase,
-        public_key: hex::encode(public_key_bytes),
-        address,
-        private_key: private_key_hex,
-        key_type: "Ed25519".to_string(),
-    }
-}
-
-/// Restore wallet from existing mnemonic
-fn restore_wallet(mnemonic_phrase: &str, show_private: bool, passphrase: Option<&str>) -> Result<WalletOutput, String> {
-    // Parse mnemonic (bip39 2.2.0 API)
-    let mnemonic = Mnemonic::parse(mnemonic_phrase)
-        .map_err(|e| format!("Invalid mnemonic: {}", e))?;
-
-    // Derive seed
-    let seed = mnemonic_to_seed(&mnemonic, passphrase.unwrap_or(""));
-
-    // Generate keypair
-    let (secret_key, public_key) = generate_ed25519_keypair(&seed);
-
-    // Derive address
-    let public_key_bytes = public_key.as_bytes();
-    let address = derive_address(public_key_bytes);
-
-    // Create output
-    let private_key_hex = if show_private {
-        Some(hex::encode(secret_key.as_bytes()))
-    } else {
-        None
-    };
-
-    Ok(WalletOutput {
-        mnemonic: mnemonic_phrase.to_string(),
-        public_key: hex::encode(public_key_bytes),
-        address,
-        private_key: private_key_hex,
-        key_type: "Ed25519".to_string(),
-    })
-}
+//!   cargo run -- generate --words 12 --language japanese
+//!   cargo run -- generate --dice --mix-os-entropy --transcript entropy.txt
+//!   cargo run -- repair --mnemonic "..." --address <hex>
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use boundless_wallet::entropy::{self, UserEntropy};
+use boundless_wallet::files;
+use boundless_wallet::output::{self, OutputFormat};
+use boundless_wallet::repair::{self, Fix};
+use boundless_wallet::wallet::{derive_accounts, generate_wallet, restore_wallet};
+use boundless_wallet::{derive_address, say, Address, Derivation, Error, KeyType, Language, Result, WalletOutput};
+use serde_json::{json, Value};
+use zeroize::Zeroizing;
 
 // ============================================================================
 // CLI Interface
@@ -212,6 +36,10 @@ use clap::{Parser, Subcommand};
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Output format: text, or json for one machine-readable document on stdout
+    #[arg(long, global = true, value_name = "FORMAT", default_value = "text")]
+    output_format: OutputFormat,
 }
 
 #[derive(Subcommand)]
@@ -226,17 +54,68 @@ enum Commands {
         #[arg(short, long, default_value = "wallet.json")]
         output: PathBuf,
 
+        /// Replace an existing output file, moving it to a timestamped backup first
+        #[arg(long)]
+        force: bool,
+
         /// Optional BIP39 passphrase for additional security
         #[arg(short, long)]
         passphrase: Option<String>,
+
+        /// Derivation path: "legacy" (default) or a hardened SLIP-0010 path
+        /// such as "m/44'/777'/0'/0'"
+        #[arg(long, conflicts_with = "account")]
+        path: Option<Derivation>,
+
+        /// Boundless account index (derives m/44'/777'/<account>'/0')
+        #[arg(long)]
+        account: Option<u32>,
+
+        /// Key type: ed25519 (default), ml-dsa-44 or hybrid (Ed25519 +
+        /// ML-DSA-44); the latter two require the `pqc` feature
+        #[arg(short = 't', long, default_value = "ed25519")]
+        key_type: KeyType,
+
+        /// Mnemonic length: 12, 15, 18, 21 or 24 words
+        #[arg(short, long, default_value_t = 24)]
+        words: usize,
+
+        /// Mnemonic wordlist: english (default), chinese-simplified,
+        /// chinese-traditional, czech, french, italian, japanese, korean,
+        /// portuguese or spanish
+        #[arg(short, long, default_value = "english")]
+        language: Language,
+
+        /// Take the entropy from die rolls (1-6) read from stdin instead of
+        /// the OS random number generator
+        #[arg(long, conflicts_with = "entropy_hex")]
+        dice: bool,
+
+        /// Take the entropy as hex read from stdin instead of the OS random
+        /// number generator
+        #[arg(long)]
+        entropy_hex: bool,
+
+        /// XOR the dice or hex entropy with OS randomness
+        #[arg(long)]
+        mix_os_entropy: bool,
+
+        /// Save the transcript of how the mnemonic was derived from the dice
+        /// or hex entropy; required with --output-format json
+        #[arg(long)]
+        transcript: Option<PathBuf>,
     },
 
     /// Restore wallet from mnemonic
     Restore {
-        /// 24-word mnemonic phrase (quoted)
+        /// 12 to 24 word mnemonic phrase (quoted)
         #[arg(short, long)]
         mnemonic: String,
 
+        /// Mnemonic wordlist (detected from the words if not given)
+        #[arg(short, long)]
+        language: Option<Language>,
+
         /// Show private key in output
         #[arg(long)]
         show_private: bool,
@@ -245,9 +124,95 @@ enum Commands {
         #[arg(short, long, default_value = "wallet_restored.json")]
         output: PathBuf,
 
+        /// Replace an existing output file, moving it to a timestamped backup first
+        #[arg(long)]
+        force: bool,
+
         /// Optional BIP39 passphrase
         #[arg(short, long)]
         passphrase: Option<String>,
+
+        /// Derivation path: "legacy" (default) or a hardened SLIP-0010 path
+        /// such as "m/44'/777'/0'/0'"
+        #[arg(long, conflicts_with = "account")]
+        path: Option<Derivation>,
+
+        /// Boundless account index (derives m/44'/777'/<account>'/0')
+        #[arg(long)]
+        account: Option<u32>,
+
+        /// Key type: ed25519 (default), ml-dsa-44 or hybrid (Ed25519 +
+        /// ML-DSA-44); the latter two require the `pqc` feature
+        #[arg(short = 't', long, default_value = "ed25519")]
+        key_type: KeyType,
+    },
+
+    /// List HD account addresses derived from a mnemonic
+    Derive {
+        /// 12 to 24 word mnemonic phrase (quoted)
+        #[arg(short, long)]
+        mnemonic: String,
+
+        /// Mnemonic wordlist (detected from the words if not given)
+        #[arg(short, long)]
+        language: Option<Language>,
+
+        /// Optional BIP39 passphrase
+        #[arg(short, long)]
+        passphrase: Option<String>,
+
+        /// Number of accounts to derive
+        #[arg(short, long, default_value_t = 10)]
+        count: u32,
+
+        /// First account index
+        #[arg(long, default_value_t = 0)]
+        start: u32,
+
+        /// Key type: ed25519 (default), ml-dsa-44 or hybrid (Ed25519 +
+        /// ML-DSA-44); the latter two require the `pqc` feature
+        #[arg(short = 't', long, default_value = "ed25519")]
+        key_type: KeyType,
+    },
+
+    /// Find the intended mnemonic when one word was miscopied: tries
+    /// completing words from their first four letters, replacing one word
+    /// and swapping adjacent words
+    Repair {
+        /// Mnemonic phrase as written down (quoted)
+        #[arg(short, long)]
+        mnemonic: String,
+
+        /// Mnemonic wordlist (guessed from the words if not given)
+        #[arg(short, long)]
+        language: Option<Language>,
+
+        /// Address the wallet is known to have; without it every
+        /// checksum-valid candidate is listed
+        #[arg(short, long)]
+        address: Option<Address>,
+
+        /// Optional BIP39 passphrase
+        #[arg(short, long)]
+        passphrase: Option<String>,
+
+        /// Derivation path: "legacy" (default) or a hardened SLIP-0010 path
+        /// such as "m/44'/777'/0'/0'"
+        #[arg(long, conflicts_with = "account")]
+        path: Option<Derivation>,
+
+        /// Boundless account index (derives m/44'/777'/<account>'/0')
+        #[arg(long)]
+        account: Option<u32>,
+
+        /// Key type: ed25519 (default), ml-dsa-44 or hybrid (Ed25519 +
+        /// ML-DSA-44); the latter two require the `pqc` feature
+        #[arg(short = 't', long, default_value = "ed25519")]
+        key_type: KeyType,
+
+        /// Threads deriving candidate addresses (default: one per core)
+        #[arg(long, default_value_t = 0)]
+        threads: usize,
     },
 
     /// Verify an address matches a public key
@@ -262,151 +227,455 @@ enum Commands {
     },
 }
 
-fn main() {
-    let cli = Cli::parse();
+/// List the individual keys of a hybrid wallet
+fn print_component_keys(wallet: &WalletOutput) {
+    if wallet.keys.len() > 1 {
+        say!("\n🧩 Component Keys:");
+        for key in &wallet.keys {
+            say!("   {:<10} {}", key.key_type, key.public_key);
+        }
+    }
+}
+
+/// Write a generated or restored wallet file (0600, never over an existing
+/// file unless `force`) and describe it (without the mnemonic or private key)
+/// for JSON output
+fn save_wallet(wallet: &WalletOutput, output_path: &PathBuf, force: bool) -> Result<Value> {
+    let json = serde_json::to_string_pretty(wallet)
+        .map_err(|e| Error::Encoding(format!("Failed to serialize wallet: {}", e)))?;
+
+    let backup = if force { files::backup_existing(output_path)? } else { None };
+    files::create_private_file(output_path, json.as_bytes())?;
+    if let Some(backup) = &backup {
+        say!("✓ Moved the previous file to: {}", backup.display());
+    }
+    // Text output explains this in the security notice instead
+    if output::is_json() {
+        output::warn(format!("{} contains the mnemonic in plaintext", output_path.display()));
+    }
 
-    match cli.command {
-        Commands::Generate { show_private, output, passphrase } => {
-            println!("\n🔐 Boundless Wallet Generator");
-            println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+    Ok(json!({
+        "output": output_path,
+        "address": wallet.address,
+        "public_key": wallet.public_key,
+        "key_type": wallet.key_type,
+        "keys": wallet.keys,
+        "derivation": wallet.derivation,
+        "language": wallet.language,
+        "word_count": wallet.mnemonic.split_whitespace().count(),
+        "private_key_included": wallet.private_key.is_some(),
+        "backup": backup,
+    }))
+}
 
-            if show_private {
-                println!("⚠️  WARNING: Private key will be included in output!");
-                println!("⚠️  Only use --show-private in secure, offline environments!\n");
+/// How `generate` takes its entropy from the operator
+struct UserEntropyArgs {
+    dice: bool,
+    entropy_hex: bool,
+    mix_os_entropy: bool,
+    transcript: Option<PathBuf>,
+}
+
+/// Read die rolls from stdin until an empty line or end of input
+fn read_dice(words: usize) -> Result<Zeroizing<String>> {
+    let needed = entropy::min_dice_rolls(words)?;
+    say!("🎲 Roll a fair six-sided die and enter the results; {} words need at least {} rolls.", words, needed);
+    say!("   Spaces and commas are ignored. Finish with an empty line.");
+
+    let mut input = Zeroizing::new(String::new());
+    loop {
+        let count = entropy::parse_rolls(&input)?.len();
+        output::prompt(&format!("[{}/{}] ", count, needed))?;
+
+        let mut line = Zeroizing::new(String::new());
+        let read = io::stdin()
+            .read_line(&mut line)
+            .map_err(|e| Error::io("Failed to read dice rolls", e))?;
+        if read == 0 {
+            break;
+        }
+        if line.trim().is_empty() {
+            if count >= needed {
+                break;
             }
+            say!("   {} more rolls needed", needed - count);
+            continue;
+        }
+        // Reject a typo on the line it was made
+        entropy::parse_rolls(&line)?;
+        input.push_str(&line);
+    }
+    say!();
+    Ok(input)
+}
 
-            // Generate wallet
-            let wallet = generate_wallet(show_private, passphrase.as_deref());
+/// Read hex entropy from one line of stdin
+fn read_entropy_hex(words: usize) -> Result<Zeroizing<String>> {
+    let bytes = entropy::entropy_bytes(words)?;
+    output::prompt(&format!("Enter {} bytes ({} hex digits) of entropy:\n> ", bytes, bytes * 2))?;
+
+    let mut line = Zeroizing::new(String::new());
+    io::stdin()
+        .read_line(&mut line)
+        .map_err(|e| Error::io("Failed to read entropy", e))?;
+    say!();
+    Ok(line)
+}
 
-            // Save to file
-            let json = serde_json::to_string_pretty(&wallet)
-                .expect("Failed to serialize wallet");
+/// Write the entropy transcript (0600), following the same overwrite rules
+/// as the wallet file
+fn save_transcript(transcript: &str, path: &Path, force: bool) -> Result<()> {
+    let backup = if force { files::backup_existing(path)? } else { None };
+    files::create_private_file(path, transcript.as_bytes())?;
+    if let Some(backup) = &backup {
+        say!("✓ Moved the previous file to: {}", backup.display());
+    }
+    Ok(())
+}
 
-            fs::write(&output, &json)
-                .expect("Failed to write wallet file");
+#[allow(clippy::too_many_arguments)]
+fn cmd_generate(
+    show_private: bool,
+    output_path: PathBuf,
+    force: bool,
+    passphrase: Option<String>,
+    path: Option<Derivation>,
+    account: Option<u32>,
+    key_type: KeyType,
+    words: usize,
+    language: Language,
+    user_entropy: UserEntropyArgs,
+) -> Result<Value> {
+    say!("\n🔐 Boundless Wallet Generator");
+    say!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+
+    let derivation = Derivation::from_options(path, account)?;
+    files::ensure_absent(&output_path, force)?;
+    let user_supplied = user_entropy.dice || user_entropy.entropy_hex;
+    if !user_supplied && (user_entropy.mix_os_entropy || user_entropy.transcript.is_some()) {
+        return Err(Error::InvalidArgument(
+            "--mix-os-entropy and --transcript need --dice or --entropy-hex".to_string(),
+        ));
+    }
+    if user_supplied && output::is_json() && user_entropy.transcript.is_none() {
+        return Err(Error::InvalidArgument(
+            "--transcript is required with --output-format json so the entropy transcript is not lost".to_string(),
+        ));
+    }
+    if let Some(transcript) = &user_entropy.transcript {
+        files::ensure_absent(transcript, force)?;
+    }
 
-            println!("\n📝 Wallet Details:");
-            println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-            if !show_private {
-                println!("\n🔑 Mnemonic: {}", wallet.mnemonic);
-            }
-            println!("\n🔐 Public Key:\n   {}", wallet.public_key);
-            println!("\n📬 Address:\n   {}", wallet.address);
-            println!("\n💾 Saved to: {}", output.display());
-
-            if !show_private {
-                println!("\n⚠️  SECURITY NOTICE:");
-                println!("   • Write down your mnemonic phrase on paper");
-                println!("   • Store it in a secure location");
-                println!("   • NEVER share it with anyone");
-                println!("   • Private key NOT saved (use --show-private if needed)");
-            }
+    if show_private {
+        output::warn("Private key will be included in output! Only use --show-private in secure, offline environments!");
+        say!();
+    }
 
-            println!("\n✅ Wallet generated successfully!\n");
-        },
-
-        Commands::Restore { mnemonic, show_private, output, passphrase } => {
-            println!("\n🔓 Restoring Boundless Wallet");
-            println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
-
-            match restore_wallet(&mnemonic, show_private, passphrase.as_deref()) {
-                Ok(wallet) => {
-                    // Save to file
-                    let json = serde_json::to_string_pretty(&wallet)
-                        .expect("Failed to serialize wallet");
-
-                    fs::write(&output, &json)
-                        .expect("Failed to write wallet file");
-
-                    println!("📝 Wallet Details:");
-                    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-                    println!("\n🔐 Public Key:\n   {}", wallet.public_key);
-                    println!("\n📬 Address:\n   {}", wallet.address);
-                    println!("\n💾 Saved to: {}", output.display());
-                    println!("\n✅ Wallet restored successfully!\n");
-                },
-                Err(e) => {
-                    eprintln!("❌ Error: {}", e);
-                    std::process::exit(1);
-                }
-            }
-        },
-
-        Commands::Verify { pubkey, address } => {
-            println!("\n🔍 Verifying Address");
-            println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
-
-            match hex::decode(&pubkey) {
-                Ok(pubkey_bytes) => {
-                    let derived_address = derive_address(&pubkey_bytes);
-
-                    println!("Public Key:  {}", pubkey);
-                    println!("Expected:    {}", address);
-                    println!("Derived:     {}", derived_address);
-
-                    if derived_address.eq_ignore_ascii_case(&address) {
-                        println!("\n✅ Address matches! Verification successful.\n");
-                    } else {
-                        println!("\n❌ Address mismatch! Verification failed.\n");
-                        std::process::exit(1);
-                    }
-                },
-                Err(e) => {
-                    eprintln!("❌ Invalid public key hex: {}", e);
-                    std::process::exit(1);
-                }
-            }
+    // Generate wallet
+    let (wallet, transcript) = if user_supplied {
+        let user = if user_entropy.dice {
+            UserEntropy::dice(&read_dice(words)?, words)?
+        } else {
+            UserEntropy::hex(&read_entropy_hex(words)?, words)?
+        };
+        let (mnemonic, mut transcript) =
+            entropy::mnemonic_from_user_entropy(&user, user_entropy.mix_os_entropy, language, words)?;
+        let wallet = WalletOutput::from_mnemonic(
+            &mnemonic,
+            passphrase.as_deref().unwrap_or(""),
+            key_type,
+            &derivation,
+            show_private,
+        )?;
+        transcript.push_str(&format!(
+            "seed = BIP39 PBKDF2-HMAC-SHA512 of the mnemonic (passphrase: {})\n",
+            if passphrase.is_some() { "set, not recorded" } else { "none" }
+        ));
+        transcript.push_str(&format!("key type: {}\nderivation: {}\naddress: {}\n", wallet.key_type, wallet.derivation, wallet.address));
+        (wallet, Some(transcript))
+    } else {
+        (generate_wallet(show_private, passphrase.as_deref(), key_type, &derivation, language, words)?, None)
+    };
+
+    let source = match (user_entropy.dice, user_entropy.entropy_hex) {
+        (true, _) => "dice",
+        (_, true) => "hex",
+        _ => "os",
+    };
+    if user_supplied {
+        say!(
+            "✓ Generated {}-word {} mnemonic from {} entropy{}",
+            words,
+            language,
+            source,
+            if user_entropy.mix_os_entropy { " mixed with OS randomness" } else { "" }
+        );
+    } else {
+        say!("✓ Generated {}-word {} mnemonic", words, language);
+    }
+    say!("✓ Derived seed from mnemonic");
+    say!("✓ Generated {} keypair", wallet.key_type);
+    say!("✓ Derived Boundless address");
+
+    // Save to file
+    let mut data = save_wallet(&wallet, &output_path, force)?;
+    data["entropy_source"] = json!(source);
+    data["os_mixed"] = json!(!user_supplied || user_entropy.mix_os_entropy);
+    data["transcript"] = json!(user_entropy.transcript);
+    if let (Some(transcript), Some(path)) = (&transcript, &user_entropy.transcript) {
+        save_transcript(transcript, path, force)?;
+        if output::is_json() {
+            output::warn(format!("{} can rebuild the mnemonic; store it as securely", path.display()));
         }
     }
+
+    say!("\n📝 Wallet Details:");
+    say!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    if !show_private {
+        say!("\n🔑 Mnemonic: {}", wallet.mnemonic);
+    }
+    say!("\n🔐 Public Key:\n   {}", wallet.public_key);
+    print_component_keys(&wallet);
+    say!("\n📬 Address:\n   {}", wallet.address);
+    say!("\n🧭 Derivation: {}", wallet.derivation);
+    say!("\n💾 Saved to: {}", output_path.display());
+
+    if let Some(transcript) = &transcript {
+        say!("\n🧾 Entropy Transcript:");
+        say!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        say!("{}", transcript.trim_end());
+        if let Some(path) = &user_entropy.transcript {
+            say!("\n💾 Transcript saved to: {} (it rebuilds the mnemonic; guard it like one)", path.display());
+        }
+    }
+
+    if !show_private {
+        say!("\n⚠️  SECURITY NOTICE:");
+        say!("   • Write down your mnemonic phrase on paper");
+        say!("   • Store it in a secure location");
+        say!("   • NEVER share it with anyone");
+        say!("   • Private key NOT saved (use --show-private if needed)");
+    }
+
+    say!("\n✅ Wallet generated successfully!\n");
+
+    Ok(data)
 }
 
-// ============================================================================
-// Tests
-// ============================================================================
+#[allow(clippy::too_many_arguments)]
+fn cmd_restore(
+    mnemonic: String,
+    language: Option<Language>,
+    show_private: bool,
+    output_path: PathBuf,
+    force: bool,
+    passphrase: Option<String>,
+    path: Option<Derivation>,
+    account: Option<u32>,
+    key_type: KeyType,
+) -> Result<Value> {
+    say!("\n🔓 Restoring Boundless Wallet");
+    say!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+
+    let derivation = Derivation::from_options(path, account)?;
+    files::ensure_absent(&output_path, force)?;
+    let wallet = restore_wallet(&mnemonic, language, show_private, passphrase.as_deref(), key_type, &derivation)?;
+    say!("✓ Mnemonic: {} words, {}", mnemonic.split_whitespace().count(), wallet.language);
+
+    // Save to file
+    let data = save_wallet(&wallet, &output_path, force)?;
+
+    say!("📝 Wallet Details:");
+    say!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    say!("\n🔐 Public Key:\n   {}", wallet.public_key);
+    print_component_keys(&wallet);
+    say!("\n📬 Address:\n   {}", wallet.address);
+    say!("\n🧭 Derivation: {}", wallet.derivation);
+    say!("\n💾 Saved to: {}", output_path.display());
+    say!("\n✅ Wallet restored successfully!\n");
+
+    Ok(data)
+}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn cmd_derive(
+    mnemonic: String,
+    language: Option<Language>,
+    passphrase: Option<String>,
+    count: u32,
+    start: u32,
+    key_type: KeyType,
+) -> Result<Value> {
+    say!("\n🧭 Deriving Boundless Accounts");
+    say!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+
+    let accounts = derive_accounts(&mnemonic, language, passphrase.as_deref(), key_type, start, count)?;
+    for account in &accounts {
+        say!("{:<18} {}", account.path, account.address);
+    }
+    say!("\n💡 Restore an account with: restore --account <N>\n");
 
-    #[test]
-    fn test_address_derivation() {
-        // Test vector: known public key -> expected address
-        let pubkey = hex::decode("1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef")
-            .unwrap();
+    Ok(json!({
+        "key_type": key_type,
+        "accounts": accounts,
+    }))
+}
 
-        let address = derive_address(&pubkey);
+/// One line describing a repair
+fn describe_fix(fix: &Fix) -> String {
+    match fix {
+        Fix::Completed { position, written, word } => format!("word {}: \"{}\" completed to \"{}\"", position, written, word),
+        Fix::Replaced { position, written, word } => format!("word {}: \"{}\" replaced by \"{}\"", position, written, word),
+        Fix::Swapped { position } => format!("words {} and {} swapped", position, position + 1),
+    }
+}
 
-        // Verify it's 64 hex characters (32 bytes)
-        assert_eq!(address.len(), 64);
-        assert!(address.chars().all(|c| c.is_ascii_hexdigit()));
+#[allow(clippy::too_many_arguments)]
+fn cmd_repair(
+    mnemonic: String,
+    language: Option<Language>,
+    address: Option<Address>,
+    passphrase: Option<String>,
+    path: Option<Derivation>,
+    account: Option<u32>,
+    key_type: KeyType,
+    threads: usize,
+) -> Result<Value> {
+    say!("\n🩹 Repairing Mnemonic");
+    say!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+
+    let derivation = Derivation::from_options(path, account)?;
+    let candidates = repair::repair(&mnemonic, language)?;
+    say!("✓ {} checksum-valid phrases within one edit", candidates.len());
+    if let Some(address) = &address {
+        say!("✓ Deriving their addresses to look for {}", address);
     }
 
-    #[test]
-    fn test_mnemonic_deterministic() {
-        // Same mnemonic should produce same keys
-        let mnemonic_phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+    let found = repair::derive_addresses(
+        &candidates,
+        passphrase.as_deref().unwrap_or(""),
+        key_type,
+        &derivation,
+        address.as_ref(),
+        threads,
+    )?;
+    if found.is_empty() {
+        return Err(Error::InvalidMnemonic(match &address {
+            Some(address) => format!("no phrase within one edit derives address {}", address),
+            None => "no phrase within one edit passes the checksum".to_string(),
+        }));
+    }
 
-        let wallet1 = restore_wallet(mnemonic_phrase, false, None).unwrap();
-        let wallet2 = restore_wallet(mnemonic_phrase, false, None).unwrap();
+    let mut results = Vec::new();
+    for (n, (index, derived)) in found.iter().enumerate() {
+        let candidate = &candidates[*index];
+        say!("\n#{} {}", n + 1, derived);
+        if candidate.fixes.is_empty() {
+            say!("   valid as written");
+        }
+        for fix in &candidate.fixes {
+            say!("   {}", describe_fix(fix));
+        }
+        if address.is_some() || found.len() == 1 {
+            say!("   Mnemonic: {}", candidate.mnemonic.phrase());
+        }
+        results.push(json!({
+            "address": derived,
+            "language": candidate.mnemonic.language(),
+            "fixes": candidate.fixes,
+        }));
+    }
 
-        assert_eq!(wallet1.public_key, wallet2.public_key);
-        assert_eq!(wallet1.address, wallet2.address);
+    if address.is_none() && found.len() > 1 {
+        say!("\n💡 Several phrases pass the checksum; pass --address to find the one you wrote down");
+    } else {
+        say!("\n💡 Restore it with: restore --mnemonic \"<phrase>\"");
+    }
+    say!("\n✅ Repair finished\n");
+
+    let mut data = json!({
+        "key_type": key_type,
+        "derivation": derivation.to_string(),
+        "checked": candidates.len(),
+        "candidates": results,
+    });
+    if let Some(address) = address {
+        data["address"] = json!(address);
     }
+    Ok(data)
+}
 
-    #[test]
-    fn test_address_format() {
-        // Verify address format matches Boundless conventions
-        let wallet = generate_wallet(false, None);
+fn cmd_verify(pubkey: String, address: String) -> Result<Value> {
+    say!("\n🔍 Verifying Address");
+    say!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
 
-        // Should be 64 hex characters (32 bytes)
-        assert_eq!(wallet.address.len(), 64);
+    let pubkey_bytes = hex::decode(&pubkey)
+        .map_err(|e| Error::Encoding(format!("Invalid public key hex: {}", e)))?;
+    let derived_address = derive_address(&pubkey_bytes);
 
-        // Should be valid hex
-        assert!(hex::decode(&wallet.address).is_ok());
+    say!("Public Key:  {}", pubkey);
+    say!("Expected:    {}", address);
+    say!("Derived:     {}", derived_address);
 
-        // Should match derived address from public key
-        let pubkey_bytes = hex::decode(&wallet.public_key).unwrap();
-        let expected_address = derive_address(&pubkey_bytes);
-        assert_eq!(wallet.address, expected_address);
+    if !derived_address.eq_ignore_ascii_case(&address) {
+        say!("\n❌ Address mismatch! Verification failed.\n");
+        return Err(Error::InvalidPublicKey(format!("does not derive address {}", address)));
     }
+    say!("\n✅ Address matches! Verification successful.\n");
+
+    Ok(json!({
+        "public_key": pubkey,
+        "address": derived_address,
+        "matches": true,
+    }))
+}
+
+fn main() {
+    let cli: Cli = output::parse_or_exit("boundless-wallet-gen");
+    output::set_output_format(cli.output_format);
+
+    let (command, result) = match cli.command {
+        Commands::Generate {
+            show_private,
+            output,
+            force,
+            passphrase,
+            path,
+            account,
+            key_type,
+            words,
+            language,
+            dice,
+            entropy_hex,
+            mix_os_entropy,
+            transcript,
+        } => (
+            "generate",
+            cmd_generate(
+                show_private,
+                output,
+                force,
+                passphrase,
+                path,
+                account,
+                key_type,
+                words,
+                language,
+                UserEntropyArgs { dice, entropy_hex, mix_os_entropy, transcript },
+            ),
+        ),
+        Commands::Restore { mnemonic, language, show_private, output, force, passphrase, path, account, key_type } => (
+            "restore",
+            cmd_restore(mnemonic, language, show_private, output, force, passphrase, path, account, key_type),
+        ),
+        Commands::Derive { mnemonic, language, passphrase, count, start, key_type } => {
+            ("derive", cmd_derive(mnemonic, language, passphrase, count, start, key_type))
+        }
+        Commands::Repair { mnemonic, language, address, passphrase, path, account, key_type, threads } => (
+            "repair",
+            cmd_repair(mnemonic, language, address, passphrase, path, account, key_type, threads),
+        ),
+        Commands::Verify { pubkey, address } => ("verify", cmd_verify(pubkey, address)),
+    };
+
+    output::finish(command, result)
 }
@@ -0,0 +1,106 @@
+//! Boundless address derivation
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha3::{Digest, Sha3_256};
+
+use crate::error::Error;
+
+/// Boundless address: the full 32-byte SHA3-256 hash of a public key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Address([u8; 32]);
+
+impl Address {
+    /// Derive the address of a public key
+    pub fn from_public_key(public_key: &[u8]) -> Self {
+        let mut hasher = Sha3_256::new();
+        hasher.update(public_key);
+        Address(hasher.finalize().into())
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Hex encoding (64 characters, no prefix)
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl FromStr for Address {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s.trim()).map_err(|e| Error::InvalidAddress(e.to_string()))?;
+        let bytes: [u8; 32] = bytes.try_into().map_err(|b: Vec<u8>| {
+            Error::InvalidAddress(format!("expected 32 bytes, got {}", b.len()))
+        })?;
+        Ok(Address(bytes))
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Derive Boundless address from public key
+///
+/// Address derivation (from boundless-bls-platform/enterprise/src/services/wallet.rs:530-543):
+/// ```rust,ignore
+/// fn derive_address(&self) -> String {
+///     let mut hasher = Sha3_256::new();
+///     hasher.update(&self.public_key);
+///     let hash = hasher.finalize();
+///     hex::encode(&hash)  // Full 32 bytes as hex (64 characters)
+/// }
+/// ```
+///
+/// NO version byte, NO checksum - matches Boundless exactly.
+pub fn derive_address(public_key: &[u8]) -> String {
+    Address::from_public_key(public_key).to_hex()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_derivation() {
+        let pubkey =
+            hex::decode("1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef")
+                .unwrap();
+
+        let address = derive_address(&pubkey);
+
+        assert_eq!(address.len(), 64);
+        assert!(address.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn test_address_parse_roundtrip() {
+        let address = Address::from_public_key(b"boundless");
+        let parsed: Address = address.to_hex().to_uppercase().parse().unwrap();
+        assert_eq!(parsed, address);
+
+        assert!("abcd".parse::<Address>().is_err());
+        assert!("zz".repeat(32).parse::<Address>().is_err());
+    }
+}
@@ -0,0 +1,748 @@
+//! Key agent: unlocked KeepBox keys served over a Unix socket
+//!
+//! Unlocking a KeepBox costs a 64 MB Argon2id run and a password prompt.
+//! `boundless-keepbox agent start` pays that once per KeepBox and then, like
+//! `ssh-agent`, holds the derived keys in memory and signs for clients on a
+//! Unix socket only its owner can connect to. Key memory is `mlock`ed where
+//! the OS allows and kept out of core dumps; keys are wiped when locked or
+//! after an idle timeout, and the agent exits once it holds none.
+//!
+//! Each message is a 4-byte big-endian length followed by that many bytes of
+//! JSON: a [`Request`] from the client, answered by one [`Response`].
+//!
+//! ```text
+//! {"op":"list"}                               -> {"status":"keys","keys":[...]}
+//! {"op":"sign","address":..,"data":"<hex>"}   -> {"status":"signature","signature":"<hex>"}
+//! {"op":"lock","address":null}                -> {"status":"locked","count":2,"remaining":0}
+//! ```
+//!
+//! A refused request gets `{"status":"error","id":..,"message":..}` with the
+//! [`Error::id`] of the failure.
+
+use std::env;
+use std::fs::{self, DirBuilder, OpenOptions, Permissions};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt, PermissionsExt};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::keys::{HeldKey, KeyRing, KeyType, Keypair, Signer};
+
+/// Environment variable naming the agent socket, printed by `agent start`
+pub const SOCKET_ENV: &str = "BOUNDLESS_AGENT_SOCK";
+
+/// Idle time after which the agent wipes its keys and exits
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Largest message either side accepts
+pub const MAX_FRAME_LEN: usize = 1 << 20;
+
+/// How long a connected client may take to send its next request
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often an idle agent checks its timeout
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// ===== Protocol =====
+
+/// Client request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Request {
+    /// Public details of every key held
+    List,
+    /// Sign `data` (hex) with the key for `address`
+    Sign { address: String, data: String },
+    /// Wipe the key for `address`, or every key
+    Lock { address: Option<String> },
+}
+
+/// Agent response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Response {
+    Keys { keys: Vec<HeldKey> },
+    Signature { signature: String },
+    Locked { count: usize, remaining: usize },
+    Error { id: String, message: String },
+}
+
+/// Write one length-prefixed message
+pub fn write_frame(w: &mut impl Write, payload: &[u8]) -> Result<()> {
+    if payload.len() > MAX_FRAME_LEN {
+        return Err(Error::Agent(format!(
+            "message of {} bytes exceeds the {} byte limit",
+            payload.len(),
+            MAX_FRAME_LEN
+        )));
+    }
+    w.write_all(&(payload.len() as u32).to_be_bytes())
+        .and_then(|_| w.write_all(payload))
+        .and_then(|_| w.flush())
+        .map_err(|e| Error::Agent(format!("connection failed: {}", e)))
+}
+
+/// Read one length-prefixed message; `None` if the peer closed the
+/// connection between messages
+pub fn read_frame(r: &mut impl Read) -> Result<Option<Vec<u8>>> {
+    let failed = |e: io::Error| Error::Agent(format!("connection failed: {}", e));
+
+    let mut len = [0u8; 4];
+    let mut read = 0;
+    while read < len.len() {
+        match r.read(&mut len[read..]) {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => return Err(failed(io::ErrorKind::UnexpectedEof.into())),
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(failed(e)),
+        }
+    }
+
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(Error::Agent(format!(
+            "message of {} bytes exceeds the {} byte limit",
+            len, MAX_FRAME_LEN
+        )));
+    }
+    let mut payload = vec![0u8; len];
+    r.read_exact(&mut payload).map_err(failed)?;
+    Ok(Some(payload))
+}
+
+// ===== Agent =====
+
+/// Why [`Agent::serve`] returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// Every key was locked
+    Locked,
+    /// No request arrived within the idle timeout
+    IdleTimeout,
+}
+
+/// Keys held by the agent process
+pub struct Agent {
+    keys: KeyRing,
+    idle_timeout: Duration,
+    last_used: Instant,
+}
+
+impl Agent {
+    pub fn new(idle_timeout: Duration) -> Self {
+        Agent {
+            keys: KeyRing::new(),
+            idle_timeout,
+            last_used: Instant::now(),
+        }
+    }
+
+    pub fn keys(&self) -> &KeyRing {
+        &self.keys
+    }
+
+    /// The keys to serve; see [`KeyRing::lock_memory`] once they are added
+    pub fn keys_mut(&mut self) -> &mut KeyRing {
+        &mut self.keys
+    }
+
+    /// Answer one request; every request resets the idle timer
+    pub fn handle(&mut self, request: Request) -> Response {
+        self.last_used = Instant::now();
+        self.process(request).unwrap_or_else(|e| Response::Error {
+            id: e.id().to_string(),
+            message: match e {
+                Error::Agent(message) => message,
+                e => e.to_string(),
+            },
+        })
+    }
+
+    fn process(&mut self, request: Request) -> Result<Response> {
+        match request {
+            Request::List => Ok(Response::Keys {
+                keys: self.keys.list(),
+            }),
+            Request::Sign { address, data } => {
+                let (_, keypair) = self.keys.get(&address).ok_or_else(|| no_key(&address))?;
+                let data = hex::decode(data.trim())
+                    .map_err(|e| Error::Encoding(format!("Invalid data hex: {}", e)))?;
+                let signature = keypair.sign(&data)?;
+                Ok(Response::Signature {
+                    signature: hex::encode(signature),
+                })
+            }
+            Request::Lock { address } => {
+                let before = self.keys.len();
+                match address {
+                    Some(address) => {
+                        if !self.keys.remove(&address) {
+                            return Err(no_key(&address));
+                        }
+                    }
+                    None => self.keys.clear(),
+                }
+                Ok(Response::Locked {
+                    count: before - self.keys.len(),
+                    remaining: self.keys.len(),
+                })
+            }
+        }
+    }
+
+    /// Serve clients on `listener` until every key is locked or no request
+    /// arrives for the idle timeout. Connections from other users are
+    /// dropped. The keys are wiped when the agent is dropped.
+    pub fn serve(&mut self, listener: &UnixListener) -> Result<StopReason> {
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| Error::io("Failed to configure agent socket", e))?;
+        let uid = unsafe { libc::geteuid() };
+
+        loop {
+            if self.keys.is_empty() {
+                return Ok(StopReason::Locked);
+            }
+            if self.last_used.elapsed() >= self.idle_timeout {
+                return Ok(StopReason::IdleTimeout);
+            }
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    if peer_uid(&stream).is_some_and(|peer| peer != uid) {
+                        continue;
+                    }
+                    // A misbehaving client only loses its own connection
+                    let _ = self.session(stream);
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(Error::io("Failed to accept agent connection", e)),
+            }
+        }
+    }
+
+    fn session(&mut self, mut stream: UnixStream) -> Result<()> {
+        stream
+            .set_nonblocking(false)
+            .and_then(|_| stream.set_read_timeout(Some(CLIENT_TIMEOUT)))
+            .map_err(|e| Error::io("Failed to configure agent connection", e))?;
+
+        while let Some(frame) = read_frame(&mut stream)? {
+            let response = match serde_json::from_slice::<Request>(&frame) {
+                Ok(request) => self.handle(request),
+                Err(e) => Response::Error {
+                    id: "encoding_error".to_string(),
+                    message: format!("malformed request: {}", e),
+                },
+            };
+            let payload = serde_json::to_vec(&response)
+                .map_err(|e| Error::Encoding(format!("Failed to encode response: {}", e)))?;
+            write_frame(&mut stream, &payload)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `path` is a socket (not a symlink to one) in a directory, both
+/// owned by this user
+fn owned_socket(path: &Path) -> bool {
+    let uid = unsafe { libc::geteuid() };
+    let owned = |path: &Path, is_type: fn(&fs::FileType) -> bool| {
+        fs::symlink_metadata(path)
+            .is_ok_and(|metadata| metadata.uid() == uid && is_type(&metadata.file_type()))
+    };
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    owned(path, fs::FileType::is_socket) && owned(parent, fs::FileType::is_dir)
+}
+
+fn no_key(address: &str) -> Error {
+    Error::Agent(format!("no key for address {}", address))
+}
+
+/// `$XDG_RUNTIME_DIR/boundless-agent.sock`, or `agent.sock` in a private
+/// per-user directory under the temp dir
+pub fn default_socket_path() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("boundless-agent.sock"),
+        _ => env::temp_dir()
+            .join(format!("boundless-agent-{}", unsafe { libc::geteuid() }))
+            .join("agent.sock"),
+    }
+}
+
+/// Listen on `path`, which only the owner can connect to. The directory is
+/// created 0700 if missing and must belong to this user (or root). A socket
+/// left behind by an agent that died is replaced; a live one is an error.
+pub fn bind(path: &Path) -> Result<UnixListener> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    if !parent.exists() {
+        DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(parent)
+            .map_err(|e| Error::io("Failed to create agent socket directory", e))?;
+    }
+    let owner = fs::metadata(parent)
+        .map_err(|e| Error::io("Failed to read agent socket directory", e))?
+        .uid();
+    if owner != unsafe { libc::geteuid() } && owner != 0 {
+        return Err(Error::io(
+            format!("{} belongs to another user", parent.display()),
+            io::ErrorKind::PermissionDenied.into(),
+        ));
+    }
+
+    if let Ok(metadata) = fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(Error::io(
+                format!("{} is not a socket", path.display()),
+                io::ErrorKind::AlreadyExists.into(),
+            ));
+        }
+        if UnixStream::connect(path).is_ok() {
+            return Err(Error::Agent(format!(
+                "an agent is already listening on {}",
+                path.display()
+            )));
+        }
+        fs::remove_file(path).map_err(|e| Error::io("Failed to remove stale agent socket", e))?;
+    }
+
+    // Created 0600 from the start, so there is no window in which another
+    // user could connect
+    let umask = unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(path);
+    unsafe { libc::umask(umask) };
+    let listener = listener.map_err(|e| Error::io("Failed to bind agent socket", e))?;
+    fs::set_permissions(path, Permissions::from_mode(0o600))
+        .map_err(|e| Error::io("Failed to restrict agent socket", e))?;
+    Ok(listener)
+}
+
+/// Fork into the background. Returns the child's pid in the parent, and
+/// `None` in the child, which is detached from the terminal with its
+/// standard streams on /dev/null.
+pub fn daemonize() -> Result<Option<u32>> {
+    // Buffered output would otherwise be printed by both processes
+    let _ = io::stdout().flush();
+    match unsafe { libc::fork() } {
+        -1 => Err(Error::io(
+            "Failed to start agent",
+            io::Error::last_os_error(),
+        )),
+        0 => {
+            unsafe { libc::setsid() };
+            let null = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open("/dev/null")
+                .map_err(|e| Error::io("Failed to open /dev/null", e))?;
+            for fd in 0..3 {
+                unsafe { libc::dup2(null.as_raw_fd(), fd) };
+            }
+            Ok(None)
+        }
+        pid => Ok(Some(pid as u32)),
+    }
+}
+
+/// Disable core dumps and `mlock` the memory of each (heap-allocated)
+/// keypair in `keys`
+pub(crate) fn lock_keys<'a>(keys: impl IntoIterator<Item = &'a Keypair>) -> io::Result<()> {
+    disable_core_dumps();
+
+    let regions = keys.into_iter().flat_map(Keypair::memory_regions);
+    for (ptr, len) in regions {
+        if len > 0 && unsafe { libc::mlock(ptr as *const libc::c_void, len) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+fn disable_core_dumps() {
+    let none = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    unsafe { libc::setrlimit(libc::RLIMIT_CORE, &none) };
+    // Also stops other processes of the same user from ptrace-attaching
+    #[cfg(target_os = "linux")]
+    unsafe {
+        libc::prctl(libc::PR_SET_DUMPABLE, 0)
+    };
+}
+
+/// User id of the process at the other end of `stream`, where the OS says
+#[cfg(target_os = "linux")]
+fn peer_uid(stream: &UnixStream) -> Option<u32> {
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    let rc = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    (rc == 0).then_some(cred.uid)
+}
+
+#[cfg(any(
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd"
+))]
+fn peer_uid(stream: &UnixStream) -> Option<u32> {
+    let (mut uid, mut gid) = (0, 0);
+    let rc = unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) };
+    (rc == 0).then_some(uid)
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "openbsd",
+    target_os = "netbsd"
+)))]
+fn peer_uid(_stream: &UnixStream) -> Option<u32> {
+    None
+}
+
+// ===== Client =====
+
+/// Client for an agent listening on a socket; each request is a new
+/// connection
+#[derive(Debug, Clone)]
+pub struct AgentClient {
+    socket: PathBuf,
+}
+
+impl AgentClient {
+    pub fn new(socket: impl Into<PathBuf>) -> Self {
+        AgentClient {
+            socket: socket.into(),
+        }
+    }
+
+    /// The agent named by `$BOUNDLESS_AGENT_SOCK`, or one at the default
+    /// socket path; `None` if neither is set up. The default path is only
+    /// used if this user owns both the socket and its directory, since
+    /// under the temp dir another user could have created them first.
+    pub fn from_env() -> Option<Self> {
+        match env::var_os(SOCKET_ENV) {
+            Some(socket) if !socket.is_empty() => Some(Self::new(socket)),
+            _ => {
+                let socket = default_socket_path();
+                owned_socket(&socket).then(|| Self::new(socket))
+            }
+        }
+    }
+
+    pub fn socket(&self) -> &Path {
+        &self.socket
+    }
+
+    /// Send one request; error responses become [`Error::Agent`]
+    pub fn request(&self, request: &Request) -> Result<Response> {
+        let mut stream = UnixStream::connect(&self.socket).map_err(|e| {
+            Error::Agent(format!(
+                "cannot connect to {}: {}",
+                self.socket.display(),
+                e
+            ))
+        })?;
+        // The agent refuses other users; refuse an agent run by another user
+        let uid = unsafe { libc::geteuid() };
+        if let Some(peer) = peer_uid(&stream).filter(|&peer| peer != uid) {
+            return Err(Error::Agent(format!(
+                "{} is served by another user (uid {}); refusing to use it",
+                self.socket.display(),
+                peer
+            )));
+        }
+        let payload = serde_json::to_vec(request)
+            .map_err(|e| Error::Encoding(format!("Failed to encode request: {}", e)))?;
+        write_frame(&mut stream, &payload)?;
+
+        let frame = read_frame(&mut stream)?
+            .ok_or_else(|| Error::Agent("connection closed without a response".to_string()))?;
+        match serde_json::from_slice(&frame)
+            .map_err(|e| Error::Agent(format!("malformed response: {}", e)))?
+        {
+            Response::Error { message, .. } => Err(Error::Agent(message)),
+            response => Ok(response),
+        }
+    }
+
+    pub fn list(&self) -> Result<Vec<HeldKey>> {
+        match self.request(&Request::List)? {
+            Response::Keys { keys } => Ok(keys),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Signature over `data` by the key for `address`
+    pub fn sign(&self, address: &str, data: &[u8]) -> Result<Vec<u8>> {
+        let request = Request::Sign {
+            address: address.to_string(),
+            data: hex::encode(data),
+        };
+        match self.request(&request)? {
+            Response::Signature { signature } => hex::decode(signature)
+                .map_err(|e| Error::Agent(format!("malformed signature: {}", e))),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Wipe the key for `address`, or every key; returns how many were
+    /// wiped and how many remain
+    pub fn lock(&self, address: Option<&str>) -> Result<(usize, usize)> {
+        let request = Request::Lock {
+            address: address.map(str::to_string),
+        };
+        match self.request(&request)? {
+            Response::Locked { count, remaining } => Ok((count, remaining)),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Signer for `address` if the agent holds its key
+    pub fn signer(&self, address: &str) -> Result<Option<AgentSigner>> {
+        let key = self
+            .list()?
+            .into_iter()
+            .find(|key| key.address.eq_ignore_ascii_case(address.trim()));
+        key.map(|key| {
+            let public_key = hex::decode(&key.public_key)
+                .map_err(|e| Error::Agent(format!("malformed public key: {}", e)))?;
+            Ok(AgentSigner {
+                client: self.clone(),
+                key,
+                public_key,
+            })
+        })
+        .transpose()
+    }
+}
+
+fn unexpected(response: Response) -> Error {
+    Error::Agent(format!("unexpected response {:?}", response))
+}
+
+/// A key held by the agent, usable wherever a [`Keypair`] signs
+#[derive(Debug, Clone)]
+pub struct AgentSigner {
+    client: AgentClient,
+    key: HeldKey,
+    public_key: Vec<u8>,
+}
+
+impl AgentSigner {
+    pub fn key(&self) -> &HeldKey {
+        &self.key
+    }
+}
+
+impl Signer for AgentSigner {
+    fn key_type(&self) -> KeyType {
+        self.key.key_type
+    }
+
+    fn public_key_bytes(&self) -> Vec<u8> {
+        self.public_key.clone()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        self.client.sign(&self.key.address, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::test_support::abandon_keypair as keypair;
+    use crate::keys::verify_signature;
+    use crate::message::{sign_message, verify_message};
+
+    fn agent(idle_timeout: Duration) -> Agent {
+        let mut agent = Agent::new(idle_timeout);
+        agent
+            .keys_mut()
+            .add(keypair(), Some("miner".to_string()), Path::new("w.keepbox"))
+            .unwrap();
+        agent
+    }
+
+    #[test]
+    fn test_frames() {
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, b"{}").unwrap();
+        write_frame(&mut buffer, b"").unwrap();
+        assert_eq!(&buffer[..6], b"\0\0\0\x02{}");
+
+        let mut reader = &buffer[..];
+        assert_eq!(read_frame(&mut reader).unwrap().unwrap(), b"{}");
+        assert_eq!(read_frame(&mut reader).unwrap().unwrap(), b"");
+        assert!(read_frame(&mut reader).unwrap().is_none());
+
+        // Oversized and truncated messages are refused
+        let huge = (MAX_FRAME_LEN as u32 + 1).to_be_bytes();
+        assert!(read_frame(&mut &huge[..]).is_err());
+        assert!(read_frame(&mut &b"\0\0\0\x05{}"[..]).is_err());
+        assert!(read_frame(&mut &b"\0\0"[..]).is_err());
+    }
+
+    #[test]
+    fn test_handle_requests() {
+        let mut agent = agent(DEFAULT_IDLE_TIMEOUT);
+        let keypair = keypair();
+        let address = keypair.address().to_hex();
+        assert!(agent
+            .keys_mut()
+            .add(self::keypair(), None, Path::new("x"))
+            .is_err());
+
+        let Response::Keys { keys } = agent.handle(Request::List) else {
+            panic!("expected keys");
+        };
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].address, address);
+        assert_eq!(keys[0].label.as_deref(), Some("miner"));
+
+        let request = Request::Sign {
+            address: address.to_uppercase(),
+            data: hex::encode(b"payload"),
+        };
+        let Response::Signature { signature } = agent.handle(request) else {
+            panic!("expected a signature");
+        };
+        let signature = hex::decode(signature).unwrap();
+        verify_signature(
+            KeyType::Ed25519,
+            &keypair.public_key_bytes(),
+            b"payload",
+            &signature,
+        )
+        .unwrap();
+
+        let unknown = Request::Sign {
+            address: "ab".repeat(32),
+            data: String::new(),
+        };
+        assert!(matches!(
+            agent.handle(unknown),
+            Response::Error { id, .. } if id == "agent_error"
+        ));
+
+        assert_eq!(
+            agent.handle(Request::Lock {
+                address: Some(address)
+            }),
+            Response::Locked {
+                count: 1,
+                remaining: 0
+            }
+        );
+        assert!(agent.keys().is_empty());
+    }
+
+    #[test]
+    fn test_serve_over_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("agent").join("agent.sock");
+        let listener = bind(&socket).unwrap();
+        let mode = fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // A live agent is not replaced
+        assert!(matches!(bind(&socket), Err(Error::Agent(_))));
+
+        let mut agent = agent(DEFAULT_IDLE_TIMEOUT);
+        agent.keys_mut().lock_memory().ok();
+        let server = thread::spawn(move || agent.serve(&listener));
+
+        let client = AgentClient::new(&socket);
+        let address = keypair().address().to_hex();
+        assert_eq!(client.list().unwrap().len(), 1);
+        assert!(client.signer(&"ab".repeat(32)).unwrap().is_none());
+
+        // The agent's key signs exactly like the keypair it came from
+        let signer = client.signer(&address).unwrap().unwrap();
+        let signature = sign_message(&signer, b"hello", true).unwrap();
+        assert_eq!(signature, sign_message(&keypair(), b"hello", true).unwrap());
+        verify_message(
+            &signer.public_key_bytes(),
+            &address,
+            b"hello",
+            &signature,
+            true,
+        )
+        .unwrap();
+
+        // A malformed request only fails that request
+        let mut stream = UnixStream::connect(&socket).unwrap();
+        write_frame(&mut stream, b"not json").unwrap();
+        let response: Response =
+            serde_json::from_slice(&read_frame(&mut stream).unwrap().unwrap()).unwrap();
+        assert!(matches!(response, Response::Error { .. }));
+        drop(stream);
+
+        assert_eq!(client.lock(None).unwrap(), (1, 0));
+        assert_eq!(server.join().unwrap().unwrap(), StopReason::Locked);
+
+        // Nothing listens any more: the socket is stale and can be replaced
+        assert!(client.list().is_err());
+        bind(&socket).unwrap();
+    }
+
+    #[test]
+    fn test_idle_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let listener = bind(&dir.path().join("agent.sock")).unwrap();
+        let mut agent = agent(Duration::from_millis(200));
+        let started = Instant::now();
+        assert_eq!(agent.serve(&listener).unwrap(), StopReason::IdleTimeout);
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_default_socket_must_be_ours() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("agent").join("agent.sock");
+        assert!(!owned_socket(&socket));
+        let _listener = bind(&socket).unwrap();
+        assert!(owned_socket(&socket));
+
+        let link = dir.path().join("agent").join("link.sock");
+        std::os::unix::fs::symlink(&socket, &link).unwrap();
+        assert!(!owned_socket(&link));
+
+        // Planted by another user; only root can set that up here
+        if unsafe { libc::geteuid() } == 0 {
+            std::os::unix::fs::chown(socket.parent().unwrap(), Some(4242), None).unwrap();
+            assert!(!owned_socket(&socket));
+        }
+    }
+}
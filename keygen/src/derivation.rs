@@ -0,0 +1,275 @@
+//! Key derivation schemes: legacy seed truncation and SLIP-0010 Ed25519
+//!
+//! Wallets created before HD support take the first 32 bytes of the BIP39
+//! seed as the Ed25519 private key, so one mnemonic yields exactly one
+//! address. That scheme is kept as [`Derivation::Legacy`] and remains the
+//! default so existing mnemonics restore to the same address.
+//!
+//! [`Derivation::Slip10`] implements SLIP-0010 for Ed25519, which only
+//! defines hardened child derivation. Boundless accounts live under
+//! `m/44'/<BOUNDLESS_COIN_TYPE>'/<account>'/0'`.
+//!
+//! ML-DSA-44 keys are generated from a 32-byte seed (FIPS 204 `xi`). It is
+//! derived with the same SLIP-0010 hardened tree but a separate master key
+//! (`"ml-dsa-44 seed"`), so Ed25519 and ML-DSA keys from one mnemonic never
+//! share secret material. For [`Derivation::Legacy`] the ML-DSA seed is the
+//! master key of that tree (path `m`).
+
+use std::fmt;
+use std::str::FromStr;
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::Sha512;
+use zeroize::Zeroizing;
+
+use crate::error::{Error, Result};
+
+/// BIP44 coin type used for Boundless account paths.
+///
+/// Not registered in SLIP-0044; changing it changes every HD address.
+pub const BOUNDLESS_COIN_TYPE: u32 = 777;
+
+const HARDENED: u32 = 0x8000_0000;
+const SLIP10_ED25519_KEY: &[u8] = b"ed25519 seed";
+const ML_DSA_44_KEY: &[u8] = b"ml-dsa-44 seed";
+
+/// Path of hardened child indices, e.g. `m/44'/777'/0'/0'`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// Standard Boundless account path `m/44'/777'/<account>'/0'`
+    pub fn account(account: u32) -> Result<Self> {
+        if account >= HARDENED {
+            return Err(Error::InvalidDerivationPath(format!(
+                "account index {} out of range",
+                account
+            )));
+        }
+        Ok(DerivationPath(vec![44, BOUNDLESS_COIN_TYPE, account, 0]))
+    }
+
+    /// Child indices without the hardened bit
+    pub fn indices(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("m")?;
+        for index in &self.0 {
+            write!(f, "/{}'", index)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = |msg: String| Error::InvalidDerivationPath(format!("{}: {}", s, msg));
+
+        let mut parts = s.trim().split('/');
+        if parts.next() != Some("m") {
+            return Err(invalid("path must start with 'm'".into()));
+        }
+
+        let mut indices = Vec::new();
+        for part in parts {
+            let digits = part
+                .strip_suffix(['\'', 'h', 'H'])
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "component '{}' is not hardened (SLIP-0010 Ed25519 supports hardened derivation only)",
+                        part
+                    ))
+                })?;
+            let index: u32 = digits
+                .parse()
+                .map_err(|_| invalid(format!("invalid index '{}'", part)))?;
+            if index >= HARDENED {
+                return Err(invalid(format!("index {} out of range", index)));
+            }
+            indices.push(index);
+        }
+
+        Ok(DerivationPath(indices))
+    }
+}
+
+/// How the Ed25519 private key is obtained from the BIP39 seed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum Derivation {
+    /// First 32 bytes of the seed (original single-address scheme)
+    #[default]
+    Legacy,
+
+    /// SLIP-0010 hardened derivation along a path
+    Slip10(DerivationPath),
+}
+
+impl Derivation {
+    /// SLIP-0010 derivation for a Boundless account index
+    pub fn account(account: u32) -> Result<Self> {
+        DerivationPath::account(account).map(Derivation::Slip10)
+    }
+
+    /// Resolve the CLI `--path` / `--account` options; legacy when neither
+    /// is given
+    pub fn from_options(path: Option<Derivation>, account: Option<u32>) -> Result<Self> {
+        match (path, account) {
+            (Some(path), _) => Ok(path),
+            (None, Some(account)) => Self::account(account),
+            (None, None) => Ok(Derivation::Legacy),
+        }
+    }
+
+    /// Derive the 32-byte Ed25519 private key from a BIP39 seed
+    pub fn derive_ed25519(&self, seed: &[u8]) -> Zeroizing<[u8; 32]> {
+        match self {
+            Derivation::Legacy => {
+                let mut key = Zeroizing::new([0u8; 32]);
+                key.copy_from_slice(&seed[..32]);
+                key
+            }
+            Derivation::Slip10(path) => slip10_hardened(SLIP10_ED25519_KEY, seed, path),
+        }
+    }
+
+    /// Derive the 32-byte ML-DSA-44 key generation seed from a BIP39 seed
+    pub fn derive_ml_dsa_44(&self, seed: &[u8]) -> Zeroizing<[u8; 32]> {
+        match self {
+            Derivation::Legacy => slip10_hardened(ML_DSA_44_KEY, seed, &DerivationPath(Vec::new())),
+            Derivation::Slip10(path) => slip10_hardened(ML_DSA_44_KEY, seed, path),
+        }
+    }
+}
+
+impl fmt::Display for Derivation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Derivation::Legacy => f.write_str("legacy"),
+            Derivation::Slip10(path) => path.fmt(f),
+        }
+    }
+}
+
+impl FromStr for Derivation {
+    type Err = Error;
+
+    /// Accepts `legacy` or a hardened path such as `m/44'/777'/0'/0'`
+    fn from_str(s: &str) -> Result<Self> {
+        if s.trim().eq_ignore_ascii_case("legacy") {
+            Ok(Derivation::Legacy)
+        } else {
+            s.parse().map(Derivation::Slip10)
+        }
+    }
+}
+
+impl Serialize for Derivation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Derivation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+fn hmac_sha512(key: &[u8], parts: &[&[u8]]) -> Zeroizing<[u8; 64]> {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts any key length");
+    for part in parts {
+        mac.update(part);
+    }
+    Zeroizing::new(mac.finalize().into_bytes().into())
+}
+
+/// SLIP-0010 master key generation and hardened CKDpriv
+fn slip10_hardened(master_key: &[u8], seed: &[u8], path: &DerivationPath) -> Zeroizing<[u8; 32]> {
+    // I = HMAC-SHA512(master_key, seed); key = I_L, chain code = I_R
+    let mut i = hmac_sha512(master_key, &[seed]);
+
+    for index in path.indices() {
+        // I = HMAC-SHA512(c_par, 0x00 || k_par || ser32(index | 2^31))
+        let (key, chain_code) = i.split_at(32);
+        i = hmac_sha512(
+            chain_code,
+            &[&[0u8], key, &(index | HARDENED).to_be_bytes()],
+        );
+    }
+
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&i[..32]);
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // SLIP-0010 test vector 1 for ed25519
+    const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+
+    fn derive(path: &str) -> String {
+        let seed = hex::decode(SEED).unwrap();
+        let derivation: Derivation = path.parse().unwrap();
+        hex::encode(*derivation.derive_ed25519(&seed))
+    }
+
+    #[test]
+    fn test_slip10_vector_1() {
+        assert_eq!(
+            derive("m"),
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"
+        );
+        assert_eq!(
+            derive("m/0'"),
+            "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3"
+        );
+        assert_eq!(
+            derive("m/0H/1H/2H"),
+            "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9"
+        );
+        assert_eq!(
+            derive("m/0'/1'/2'/2'/1000000000'"),
+            "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793"
+        );
+    }
+
+    #[test]
+    fn test_path_parsing() {
+        let path: DerivationPath = "m/44'/777'/3'/0'".parse().unwrap();
+        assert_eq!(path, DerivationPath::account(3).unwrap());
+        assert_eq!(path.to_string(), "m/44'/777'/3'/0'");
+
+        assert!("44'/0'".parse::<DerivationPath>().is_err());
+        assert!("m/44'/0".parse::<DerivationPath>().is_err());
+        assert!("m/2147483648'".parse::<DerivationPath>().is_err());
+
+        assert_eq!("legacy".parse::<Derivation>().unwrap(), Derivation::Legacy);
+    }
+
+    #[test]
+    fn test_ml_dsa_seed_separated_from_ed25519() {
+        let seed = [7u8; 64];
+        for derivation in [Derivation::Legacy, Derivation::account(0).unwrap()] {
+            assert_ne!(
+                *derivation.derive_ml_dsa_44(&seed),
+                *derivation.derive_ed25519(&seed)
+            );
+        }
+    }
+
+    #[test]
+    fn test_legacy_is_seed_prefix() {
+        let seed = [7u8; 64];
+        assert_eq!(*Derivation::Legacy.derive_ed25519(&seed), [7u8; 32]);
+    }
+}
@@ -0,0 +1,289 @@
+//! User-supplied entropy for air-gapped mnemonic generation
+//!
+//! Operators who do not trust the OS random number generator on a freshly
+//! booted machine can supply their own entropy as dice rolls or hex. Dice
+//! rolls are turned into entropy by hashing them, which is free of the bias
+//! a direct base-6 conversion would have:
+//!
+//! ```text
+//! entropy = SHA-256(rolls as ASCII digits)[..len]
+//! ```
+//!
+//! so `printf '%s' <rolls> | sha256sum` reproduces it on any machine. The
+//! result can optionally be XORed with OS randomness, which is as strong as
+//! the better of the two sources. Every step is written to a transcript so
+//! the mnemonic can be rebuilt and audited offline; the transcript is as
+//! secret as the mnemonic itself.
+
+use std::fmt::Write as _;
+
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::error::{Error, Result};
+use crate::mnemonic::{Language, Mnemonic, WORD_COUNTS};
+
+/// Bits of entropy in one fair six-sided die roll
+const BITS_PER_ROLL: f64 = 2.584_962_500_721_156; // log2(6)
+
+/// Entropy bytes behind a mnemonic of `word_count` words
+pub fn entropy_bytes(word_count: usize) -> Result<usize> {
+    if !WORD_COUNTS.contains(&word_count) {
+        return Err(Error::InvalidArgument(format!(
+            "a mnemonic has 12, 15, 18, 21 or 24 words, not {}",
+            word_count
+        )));
+    }
+    Ok(word_count / 3 * 4)
+}
+
+/// Fewest fair die rolls that carry the entropy of a `word_count` word
+/// mnemonic (50 for 12 words, 100 for 24)
+pub fn min_dice_rolls(word_count: usize) -> Result<usize> {
+    let bits = entropy_bytes(word_count)? * 8;
+    Ok((bits as f64 / BITS_PER_ROLL).ceil() as usize)
+}
+
+/// Entropy supplied by the operator
+pub enum UserEntropy {
+    /// Die rolls as the digits 1-6
+    Dice(Zeroizing<String>),
+    /// Raw entropy of exactly the mnemonic's length
+    Hex(Zeroizing<Vec<u8>>),
+}
+
+impl UserEntropy {
+    /// Collect die rolls from `input`, ignoring whitespace and commas. Any
+    /// other character, including 0 or 7 from the wrong kind of die, is
+    /// rejected rather than skipped.
+    pub fn dice(input: &str, word_count: usize) -> Result<Self> {
+        let rolls = parse_rolls(input)?;
+        let needed = min_dice_rolls(word_count)?;
+        if rolls.len() < needed {
+            return Err(Error::InvalidArgument(format!(
+                "{} dice rolls given; {} words need at least {}",
+                rolls.len(),
+                word_count,
+                needed
+            )));
+        }
+        Ok(UserEntropy::Dice(rolls))
+    }
+
+    /// Decode hex entropy, which must be exactly as long as a `word_count`
+    /// word mnemonic needs
+    pub fn hex(input: &str, word_count: usize) -> Result<Self> {
+        let digits: Zeroizing<String> =
+            Zeroizing::new(input.chars().filter(|c| !c.is_whitespace()).collect());
+        let bytes = Zeroizing::new(
+            hex::decode(digits.as_str())
+                .map_err(|e| Error::Encoding(format!("Invalid entropy hex: {}", e)))?,
+        );
+        let needed = entropy_bytes(word_count)?;
+        if bytes.len() != needed {
+            return Err(Error::InvalidArgument(format!(
+                "{} words need {} bytes ({} hex digits) of entropy, got {} bytes",
+                word_count,
+                needed,
+                needed * 2,
+                bytes.len()
+            )));
+        }
+        Ok(UserEntropy::Hex(bytes))
+    }
+
+    /// The entropy bytes this input stands for
+    fn to_entropy(&self, len: usize) -> Zeroizing<Vec<u8>> {
+        match self {
+            UserEntropy::Dice(rolls) => {
+                let digest = Sha256::digest(rolls.as_bytes());
+                Zeroizing::new(digest[..len].to_vec())
+            }
+            UserEntropy::Hex(bytes) => bytes.clone(),
+        }
+    }
+}
+
+/// Digits 1-6 from `input`
+pub fn parse_rolls(input: &str) -> Result<Zeroizing<String>> {
+    let mut rolls = Zeroizing::new(String::new());
+    for c in input.chars() {
+        match c {
+            '1'..='6' => rolls.push(c),
+            ',' => {}
+            c if c.is_whitespace() => {}
+            c => {
+                return Err(Error::InvalidArgument(format!(
+                    "'{}' is not a die roll (expected 1-6)",
+                    c
+                )))
+            }
+        }
+    }
+    Ok(rolls)
+}
+
+/// Build a mnemonic from user entropy, XORed with OS randomness if
+/// `mix_os` is set. Returns the mnemonic and a transcript of every step.
+pub fn mnemonic_from_user_entropy(
+    user: &UserEntropy,
+    mix_os: bool,
+    language: Language,
+    word_count: usize,
+) -> Result<(Mnemonic, Zeroizing<String>)> {
+    let len = entropy_bytes(word_count)?;
+    let user_entropy = user.to_entropy(len);
+
+    let mut transcript = Zeroizing::new(String::new());
+    // Writing to a String cannot fail
+    let t = &mut *transcript;
+    let _ = writeln!(t, "# Boundless entropy transcript v1");
+    let _ = writeln!(
+        t,
+        "# Rebuilds the mnemonic: keep it as secret as the mnemonic itself."
+    );
+    let _ = writeln!(
+        t,
+        "mnemonic: {} words ({} bits), {}",
+        word_count,
+        len * 8,
+        language
+    );
+
+    match user {
+        UserEntropy::Dice(rolls) => {
+            let _ = writeln!(
+                t,
+                "dice rolls: {} (minimum {})",
+                rolls.len(),
+                min_dice_rolls(word_count)?
+            );
+            for chunk in rolls.as_bytes().chunks(50) {
+                let groups: Vec<&str> = chunk
+                    .chunks(10)
+                    .map(|g| std::str::from_utf8(g).expect("rolls are ASCII digits"))
+                    .collect();
+                let _ = writeln!(t, "  {}", groups.join(" "));
+            }
+            let _ = writeln!(t, "user entropy = SHA-256(rolls without spaces)[..{}]", len);
+            let _ = writeln!(
+                t,
+                "  (printf '%s' <rolls> | sha256sum | cut -c1-{})",
+                len * 2
+            );
+        }
+        UserEntropy::Hex(_) => {
+            let _ = writeln!(t, "user entropy = supplied hex");
+        }
+    }
+    write_hex(t, &user_entropy);
+
+    let entropy = if mix_os {
+        let mut os = Zeroizing::new(vec![0u8; len]);
+        getrandom::getrandom(&mut os).map_err(|e| Error::Entropy(e.to_string()))?;
+        let mixed: Zeroizing<Vec<u8>> = Zeroizing::new(
+            user_entropy
+                .iter()
+                .zip(os.iter())
+                .map(|(u, o)| u ^ o)
+                .collect(),
+        );
+        let _ = writeln!(t, "os entropy (getrandom)");
+        write_hex(t, &os);
+        let _ = writeln!(t, "entropy = user entropy XOR os entropy");
+        write_hex(t, &mixed);
+        mixed
+    } else {
+        let _ = writeln!(t, "entropy = user entropy (not mixed with OS randomness)");
+        user_entropy
+    };
+
+    let mnemonic = Mnemonic::from_entropy_in(language, &entropy)?;
+    let _ = writeln!(t, "mnemonic = BIP39 {} wordlist of entropy", language);
+    let _ = writeln!(t, "  {}", mnemonic.phrase());
+    Ok((mnemonic, transcript))
+}
+
+/// Indented hex line, written without a temporary String
+fn write_hex(t: &mut String, bytes: &[u8]) {
+    t.push_str("  ");
+    for b in bytes {
+        let _ = write!(t, "{:02x}", b);
+    }
+    t.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_dice_rolls() {
+        let rolls: Vec<usize> = WORD_COUNTS
+            .iter()
+            .map(|&w| min_dice_rolls(w).unwrap())
+            .collect();
+        assert_eq!(rolls, [50, 62, 75, 87, 100]);
+        assert!(min_dice_rolls(13).is_err());
+    }
+
+    #[test]
+    fn test_dice_entropy_is_sha256_of_rolls() {
+        let rolls = "1234561234".repeat(5);
+        let user = UserEntropy::dice(&rolls, 12).unwrap();
+        let (mnemonic, transcript) =
+            mnemonic_from_user_entropy(&user, false, Language::English, 12).unwrap();
+
+        // printf '%s' 1234561234...(x5) | sha256sum | cut -c1-32
+        let expected = hex::decode("9a3d3866e4165f1ba2a0abd57f2526e7").unwrap();
+        assert_eq!(mnemonic.entropy().as_slice(), expected.as_slice());
+        assert!(transcript.contains("9a3d3866e4165f1ba2a0abd57f2526e7"));
+        assert!(transcript.contains(&mnemonic.phrase()));
+
+        // Separators do not change the rolls
+        let spaced = UserEntropy::dice(&rolls.replace("12", "1 2,"), 12).unwrap();
+        let (again, _) = mnemonic_from_user_entropy(&spaced, false, Language::English, 12).unwrap();
+        assert_eq!(again, mnemonic);
+    }
+
+    #[test]
+    fn test_bad_dice_rejected() {
+        let rolls = "6".repeat(99);
+        assert!(UserEntropy::dice(&rolls, 24).is_err());
+        assert!(UserEntropy::dice(&format!("{}6", rolls), 24).is_ok());
+        assert!(UserEntropy::dice(&format!("{}0", rolls), 24).is_err());
+        assert!(UserEntropy::dice(&format!("{}7", rolls), 24).is_err());
+        assert!(UserEntropy::dice(&format!("{}x", rolls), 24).is_err());
+    }
+
+    #[test]
+    fn test_hex_entropy_and_mixing() {
+        let hex_input = "7f".repeat(16);
+        assert!(UserEntropy::hex(&hex_input, 24).is_err());
+        let user = UserEntropy::hex(&hex_input, 12).unwrap();
+
+        let (plain, _) = mnemonic_from_user_entropy(&user, false, Language::English, 12).unwrap();
+        assert_eq!(
+            plain.phrase(),
+            "legal winner thank year wave sausage worth useful legal winner thank yellow"
+        );
+
+        // The transcript's OS bytes undo the XOR
+        let (mixed, transcript) =
+            mnemonic_from_user_entropy(&user, true, Language::English, 12).unwrap();
+        assert_ne!(mixed, plain);
+        let lines: Vec<&str> = transcript.lines().collect();
+        let os_line = lines
+            .iter()
+            .position(|l| l.starts_with("os entropy"))
+            .unwrap();
+        let os = hex::decode(lines[os_line + 1].trim()).unwrap();
+        let user_bytes: Vec<u8> = mixed
+            .entropy()
+            .iter()
+            .zip(&os)
+            .map(|(m, o)| m ^ o)
+            .collect();
+        assert_eq!(user_bytes, vec![0x7f; 16]);
+    }
+}
@@ -0,0 +1,554 @@
+//! Transaction envelopes for air-gapped signing
+//!
+//! An online, watch-only machine writes an [`UnsignedEnvelope`]; the offline
+//! machine checks it, shows its summary and signs it into a
+//! [`SignedEnvelope`]; the online machine checks that and broadcasts the
+//! transaction. Both are versioned files, JSON by default:
+//!
+//! ```json
+//! {
+//!   "format": "boundless-unsigned-tx",
+//!   "version": 1,
+//!   "chain_id": "boundless-mainnet",
+//!   "created": "2026-10-17T09:30:00Z",
+//!   "transaction": {"from": "..", "to": "..", "amount": 1500000000000000000, "nonce": 7, "fee": 0},
+//!   "summary": "Send 1.5 BLS from .. to .. with fee 0.0 BLS, nonce 7, on boundless-mainnet",
+//!   "checksum": "<SHA3-256 hex>"
+//! }
+//! ```
+//!
+//! A signed envelope adds `public_key` and the transaction's `signature`,
+//! and records the unsigned envelope's checksum as `unsigned_checksum`.
+//!
+//! Checksums are SHA3-256 over the fields joined with `\n`:
+//!
+//! - unsigned: format, version, chain_id, created, from, to, amount, nonce, fee
+//! - signed: format, version, unsigned_checksum, public_key, signature
+//!
+//! They catch files damaged or edited in transit, and give operators a
+//! fingerprint to compare across machines. They are not authenticated:
+//! the summary is rebuilt from the fields rather than trusted, and the
+//! signature is checked against the public key and sender before broadcast.
+//! The chain id is not part of the signed message (the node's format has no
+//! room for it), so it only guards against broadcasting to the wrong network.
+//!
+//! With [`Encoding::Cbor`] the same fields are written as a CBOR map, with
+//! amounts as integers (bignums above `u64`). Readers detect the encoding
+//! from the first byte. The checksums cover field values rather than the
+//! file's bytes, so they are the same in either encoding.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+use crate::error::{Error, Result};
+use crate::keys::{KeyType, Signer};
+use crate::tx::{format_amount, SignedTransaction, Transaction};
+
+/// `format` of unsigned envelopes
+pub const UNSIGNED_FORMAT: &str = "boundless-unsigned-tx";
+
+/// `format` of signed envelopes
+pub const SIGNED_FORMAT: &str = "boundless-signed-tx";
+
+/// Envelope layout version written by this build
+pub const ENVELOPE_VERSION: u32 = 1;
+
+/// Chain id used when none is given
+pub const DEFAULT_CHAIN_ID: &str = "boundless-mainnet";
+
+/// File encoding of an envelope
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// Pretty-printed JSON (default)
+    #[default]
+    Json,
+    /// CBOR map with the same fields
+    Cbor,
+}
+
+impl Encoding {
+    /// Encoding of a file: CBOR envelopes are maps (major type 5), which
+    /// never start with a byte JSON can start with
+    pub fn detect(bytes: &[u8]) -> Self {
+        match bytes.first() {
+            Some(0xa0..=0xbf) => Encoding::Cbor,
+            _ => Encoding::Json,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Json => "json",
+            Encoding::Cbor => "cbor",
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Encoding::Json),
+            "cbor" => Ok(Encoding::Cbor),
+            other => Err(Error::InvalidArgument(format!(
+                "unknown envelope encoding '{}' (expected json or cbor)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Check a chain id: 1 to 64 letters, digits, `.`, `_` or `-`
+pub fn validate_chain_id(chain_id: &str) -> Result<()> {
+    let valid = !chain_id.is_empty()
+        && chain_id.len() <= 64
+        && chain_id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"._-".contains(&b));
+    if !valid {
+        return Err(Error::InvalidArgument(format!(
+            "invalid chain id {:?}: use 1-64 letters, digits, '.', '_' or '-'",
+            chain_id
+        )));
+    }
+    Ok(())
+}
+
+/// Human-readable description of `tx`, shown before signing
+pub fn summary(tx: &Transaction, chain_id: &str) -> String {
+    format!(
+        "Send {} BLS from {} to {} with fee {} BLS, nonce {}, on {}",
+        format_amount(tx.amount),
+        tx.from,
+        tx.to,
+        format_amount(tx.fee),
+        tx.nonce,
+        chain_id
+    )
+}
+
+/// The `format` of an envelope in either encoding, or `None` for a bare
+/// transaction from `tx build` (or anything else that is not an envelope)
+pub fn format_of(bytes: &[u8]) -> Option<String> {
+    probe(bytes).ok().flatten()
+}
+
+/// `format` field of a JSON or CBOR document; `Some("")` if it is not a
+/// string
+fn probe(bytes: &[u8]) -> Result<Option<String>> {
+    let format = match Encoding::detect(bytes) {
+        Encoding::Json => {
+            let value: serde_json::Value = serde_json::from_slice(bytes).map_err(invalid)?;
+            value
+                .get("format")
+                .map(|format| format.as_str().unwrap_or_default().to_string())
+        }
+        Encoding::Cbor => {
+            let value: ciborium::Value = ciborium::from_reader(bytes).map_err(invalid)?;
+            value.as_map().and_then(|fields| {
+                fields
+                    .iter()
+                    .find(|(key, _)| key.as_text() == Some("format"))
+                    .map(|(_, format)| format.as_text().unwrap_or_default().to_string())
+            })
+        }
+    };
+    Ok(format)
+}
+
+fn invalid(e: impl fmt::Display) -> Error {
+    Error::Encoding(format!("Failed to parse transaction envelope: {}", e))
+}
+
+fn sha3_hex(fields: &[&str]) -> String {
+    hex::encode(Sha3_256::digest(fields.join("\n").as_bytes()))
+}
+
+fn unsigned_checksum(chain_id: &str, created: &str, tx: &Transaction) -> String {
+    sha3_hex(&[
+        UNSIGNED_FORMAT,
+        &ENVELOPE_VERSION.to_string(),
+        chain_id,
+        created,
+        &tx.from,
+        &tx.to,
+        &tx.amount.to_string(),
+        &tx.nonce.to_string(),
+        &tx.fee.to_string(),
+    ])
+}
+
+/// Format, version, chain id, timestamp, addresses and summary of an
+/// envelope as read from a file
+fn check_fields(
+    format: &str,
+    expected_format: &str,
+    version: u32,
+    chain_id: &str,
+    created: &str,
+    tx: &Transaction,
+    written_summary: &str,
+) -> Result<()> {
+    if format != expected_format {
+        return Err(Error::Encoding(format!(
+            "expected a {} envelope, found {:?}",
+            expected_format, format
+        )));
+    }
+    if version != ENVELOPE_VERSION {
+        return Err(Error::Unsupported(format!(
+            "{} version {} (this build reads version {})",
+            format, version, ENVELOPE_VERSION
+        )));
+    }
+    validate_chain_id(chain_id)?;
+    chrono::DateTime::parse_from_rfc3339(created)
+        .map_err(|e| Error::Encoding(format!("Invalid created timestamp: {}", e)))?;
+    Transaction::new(&tx.from, &tx.to, tx.amount, tx.nonce, tx.fee)?;
+    if written_summary != summary(tx, chain_id) {
+        return Err(Error::IntegrityMismatch(
+            "summary does not describe the transaction".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Parse an envelope in either encoding, checking its format first so a
+/// signed envelope given for an unsigned one (or a bare transaction) is
+/// named as such
+fn parse<T: DeserializeOwned>(bytes: &[u8], expected_format: &str) -> Result<T> {
+    match probe(bytes)? {
+        Some(format) if format == expected_format => match Encoding::detect(bytes) {
+            Encoding::Json => serde_json::from_slice(bytes).map_err(invalid),
+            Encoding::Cbor => ciborium::from_reader(bytes).map_err(invalid),
+        },
+        Some(format) => Err(Error::Encoding(format!(
+            "expected a {} envelope, found {:?}",
+            expected_format, format
+        ))),
+        None => Err(Error::Encoding(format!(
+            "expected a {} envelope, found a bare transaction",
+            expected_format
+        ))),
+    }
+}
+
+fn encode(value: &impl Serialize, encoding: Encoding) -> Result<Vec<u8>> {
+    let failed =
+        |e: String| Error::Encoding(format!("Failed to serialize transaction envelope: {}", e));
+    match encoding {
+        Encoding::Json => serde_json::to_vec_pretty(value).map_err(|e| failed(e.to_string())),
+        Encoding::Cbor => {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(value, &mut bytes).map_err(|e| failed(e.to_string()))?;
+            Ok(bytes)
+        }
+    }
+}
+
+/// Transaction awaiting an offline signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UnsignedEnvelope {
+    pub format: String,
+    pub version: u32,
+    pub chain_id: String,
+    /// RFC 3339 creation time
+    pub created: String,
+    pub transaction: Transaction,
+    pub summary: String,
+    /// SHA3-256 integrity checksum (hex)
+    pub checksum: String,
+}
+
+impl UnsignedEnvelope {
+    /// Wrap `tx` for `chain_id`, stamped with the current time
+    pub fn new(tx: Transaction, chain_id: &str) -> Result<Self> {
+        validate_chain_id(chain_id)?;
+        let tx = Transaction::new(&tx.from, &tx.to, tx.amount, tx.nonce, tx.fee)?;
+        let created = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+
+        Ok(UnsignedEnvelope {
+            format: UNSIGNED_FORMAT.to_string(),
+            version: ENVELOPE_VERSION,
+            chain_id: chain_id.to_string(),
+            checksum: unsigned_checksum(chain_id, &created, &tx),
+            summary: summary(&tx, chain_id),
+            created,
+            transaction: tx,
+        })
+    }
+
+    /// Parse and [`verify`](Self::verify) a JSON or CBOR envelope
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let envelope: Self = parse(bytes, UNSIGNED_FORMAT)?;
+        envelope.verify()?;
+        Ok(envelope)
+    }
+
+    pub fn to_bytes(&self, encoding: Encoding) -> Result<Vec<u8>> {
+        encode(self, encoding)
+    }
+
+    /// Check the format, version, fields, summary and checksum
+    pub fn verify(&self) -> Result<()> {
+        check_fields(
+            &self.format,
+            UNSIGNED_FORMAT,
+            self.version,
+            &self.chain_id,
+            &self.created,
+            &self.transaction,
+            &self.summary,
+        )?;
+        let checksum = unsigned_checksum(&self.chain_id, &self.created, &self.transaction);
+        if !checksum.eq_ignore_ascii_case(&self.checksum) {
+            return Err(Error::IntegrityMismatch(format!(
+                "unsigned transaction checksum is {}, file says {}",
+                checksum, self.checksum
+            )));
+        }
+        Ok(())
+    }
+
+    /// Sign with the sender's keypair (or the agent holding it)
+    pub fn sign(&self, signer: &(impl Signer + ?Sized)) -> Result<SignedEnvelope> {
+        self.verify()?;
+        let signed = self.transaction.sign(signer)?;
+        let public_key = hex::encode(signer.public_key_bytes());
+
+        Ok(SignedEnvelope {
+            format: SIGNED_FORMAT.to_string(),
+            version: ENVELOPE_VERSION,
+            chain_id: self.chain_id.clone(),
+            created: self.created.clone(),
+            checksum: sha3_hex(&[
+                SIGNED_FORMAT,
+                &ENVELOPE_VERSION.to_string(),
+                &self.checksum,
+                &public_key,
+                &signed.signature,
+            ]),
+            transaction: signed,
+            public_key,
+            summary: self.summary.clone(),
+            unsigned_checksum: self.checksum.clone(),
+        })
+    }
+}
+
+/// Signed transaction ready to broadcast
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignedEnvelope {
+    pub format: String,
+    pub version: u32,
+    pub chain_id: String,
+    /// Creation time of the unsigned envelope
+    pub created: String,
+    pub transaction: SignedTransaction,
+    /// Signer's public key (hex)
+    pub public_key: String,
+    pub summary: String,
+    /// Checksum of the unsigned envelope that was signed
+    pub unsigned_checksum: String,
+    /// SHA3-256 integrity checksum (hex)
+    pub checksum: String,
+}
+
+impl SignedEnvelope {
+    /// Parse and [`verify`](Self::verify) a JSON or CBOR envelope
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let envelope: Self = parse(bytes, SIGNED_FORMAT)?;
+        envelope.verify()?;
+        Ok(envelope)
+    }
+
+    pub fn to_bytes(&self, encoding: Encoding) -> Result<Vec<u8>> {
+        encode(self, encoding)
+    }
+
+    /// Check the format, version, fields, summary and both checksums, and
+    /// that the signature verifies and the public key owns the sender
+    pub fn verify(&self) -> Result<()> {
+        let tx = self.transaction.transaction();
+        check_fields(
+            &self.format,
+            SIGNED_FORMAT,
+            self.version,
+            &self.chain_id,
+            &self.created,
+            &tx,
+            &self.summary,
+        )?;
+        let unsigned = unsigned_checksum(&self.chain_id, &self.created, &tx);
+        if !unsigned.eq_ignore_ascii_case(&self.unsigned_checksum) {
+            return Err(Error::IntegrityMismatch(format!(
+                "transaction does not match the unsigned checksum {}",
+                self.unsigned_checksum
+            )));
+        }
+        let checksum = sha3_hex(&[
+            SIGNED_FORMAT,
+            &ENVELOPE_VERSION.to_string(),
+            &self.unsigned_checksum,
+            &self.public_key,
+            &self.transaction.signature,
+        ]);
+        if !checksum.eq_ignore_ascii_case(&self.checksum) {
+            return Err(Error::IntegrityMismatch(format!(
+                "signed transaction checksum is {}, file says {}",
+                checksum, self.checksum
+            )));
+        }
+
+        let public_key = hex::decode(&self.public_key)
+            .map_err(|e| Error::InvalidPublicKey(format!("public key is not hex: {}", e)))?;
+        self.transaction
+            .verify(KeyType::from_public_key(&public_key)?, &public_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::test_support::abandon_keypair as keypair;
+
+    fn unsigned() -> UnsignedEnvelope {
+        let from = keypair().address().to_hex();
+        let tx =
+            Transaction::new(&from, &"bb".repeat(32), 1_500_000_000_000_000_000, 7, 0).unwrap();
+        UnsignedEnvelope::new(tx, DEFAULT_CHAIN_ID).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let envelope = unsigned();
+        assert!(envelope.summary.starts_with("Send 1.5 BLS from 10e8a4f8"));
+        let json = envelope.to_bytes(Encoding::Json).unwrap();
+        assert_eq!(format_of(&json).as_deref(), Some(UNSIGNED_FORMAT));
+        assert_eq!(
+            format_of(&serde_json::to_vec(&envelope.transaction).unwrap()),
+            None
+        );
+        assert_eq!(UnsignedEnvelope::from_bytes(&json).unwrap(), envelope);
+
+        let signed = envelope.sign(&keypair()).unwrap();
+        assert_eq!(signed.unsigned_checksum, envelope.checksum);
+        assert_eq!(
+            SignedEnvelope::from_bytes(&signed.to_bytes(Encoding::Json).unwrap()).unwrap(),
+            signed
+        );
+
+        // Envelopes of one kind are not accepted as the other
+        assert!(matches!(
+            SignedEnvelope::from_bytes(&json),
+            Err(Error::Encoding(_))
+        ));
+        assert!(validate_chain_id("boundless-testnet.2").is_ok());
+        for bad in ["", "main net", "chain\n", &"x".repeat(65)] {
+            assert!(validate_chain_id(bad).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_cbor() {
+        let mut envelope = unsigned();
+        // Above u64, so written as a CBOR bignum
+        envelope.transaction.amount = 10u128.pow(30);
+        envelope.summary = summary(&envelope.transaction, &envelope.chain_id);
+        envelope.checksum =
+            unsigned_checksum(&envelope.chain_id, &envelope.created, &envelope.transaction);
+
+        let cbor = envelope.to_bytes(Encoding::Cbor).unwrap();
+        assert_eq!(Encoding::detect(&cbor), Encoding::Cbor);
+        assert_eq!(format_of(&cbor).as_deref(), Some(UNSIGNED_FORMAT));
+        assert_eq!(UnsignedEnvelope::from_bytes(&cbor).unwrap(), envelope);
+        assert!(matches!(
+            SignedEnvelope::from_bytes(&cbor),
+            Err(Error::Encoding(_))
+        ));
+
+        // Same checksums as the JSON encoding of the same envelope
+        let signed = envelope.sign(&keypair()).unwrap();
+        let cbor = signed.to_bytes(Encoding::Cbor).unwrap();
+        let json = signed.to_bytes(Encoding::Json).unwrap();
+        assert_eq!(
+            SignedEnvelope::from_bytes(&cbor).unwrap(),
+            SignedEnvelope::from_bytes(&json).unwrap()
+        );
+
+        // The checksum is the last field
+        let mut tampered = cbor;
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            SignedEnvelope::from_bytes(&tampered),
+            Err(Error::IntegrityMismatch(_))
+        ));
+        assert_eq!("CBOR".parse::<Encoding>().unwrap(), Encoding::Cbor);
+    }
+
+    #[test]
+    fn test_tampering() {
+        let envelope = unsigned();
+
+        let mut changed = envelope.clone();
+        changed.transaction.amount += 1;
+        changed.summary = summary(&changed.transaction, &changed.chain_id);
+        let error = changed.verify().unwrap_err();
+        assert_eq!((error.id(), error.exit_code()), ("integrity_mismatch", 4));
+
+        let mut misleading = envelope.clone();
+        misleading.summary = misleading.summary.replace("1.5 BLS", "0.15 BLS");
+        assert!(matches!(
+            misleading.verify(),
+            Err(Error::IntegrityMismatch(_))
+        ));
+
+        let mut future = envelope.clone();
+        future.version = 2;
+        assert!(matches!(future.verify(), Err(Error::Unsupported(_))));
+
+        let signed = envelope.sign(&keypair()).unwrap();
+        let mut redirected = signed.clone();
+        redirected.transaction.to = "cc".repeat(32);
+        assert!(matches!(
+            redirected.verify(),
+            Err(Error::IntegrityMismatch(_))
+        ));
+
+        // A consistent file with a forged signature still fails
+        let mut forged = signed.clone();
+        forged.transaction.signature = "00".repeat(64);
+        forged.checksum = sha3_hex(&[
+            SIGNED_FORMAT,
+            "1",
+            &forged.unsigned_checksum,
+            &forged.public_key,
+            &forged.transaction.signature,
+        ]);
+        assert!(matches!(forged.verify(), Err(Error::InvalidSignature(_))));
+
+        // Signing checks the sender
+        let mut foreign = envelope;
+        foreign.transaction.from = "cc".repeat(32);
+        foreign.summary = summary(&foreign.transaction, &foreign.chain_id);
+        foreign.checksum =
+            unsigned_checksum(&foreign.chain_id, &foreign.created, &foreign.transaction);
+        assert!(matches!(
+            foreign.sign(&keypair()),
+            Err(Error::InvalidTransaction(_))
+        ));
+    }
+}
@@ -0,0 +1,346 @@
+//! Error type shared by all wallet operations
+//!
+//! Every variant maps to one of the process exit codes documented in
+//! `CLI_SPECIFICATION.md` ([`Error::exit_code`]) and to a stable
+//! machine-readable identifier ([`Error::id`]) that the binaries print with
+//! each error, so scripts can tell a wrong password from a missing file.
+
+use std::fmt;
+use std::io;
+
+/// Result alias used throughout the library
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Process exit codes from `CLI_SPECIFICATION.md`
+pub mod exit_code {
+    pub const SUCCESS: i32 = 0;
+    pub const INVALID_ARGUMENTS: i32 = 1;
+    pub const KEYSTORE_NOT_FOUND: i32 = 2;
+    pub const INVALID_PASSWORD: i32 = 3;
+    pub const ENCRYPTION_ERROR: i32 = 4;
+    pub const INVALID_MNEMONIC: i32 = 5;
+    pub const FILE_IO_ERROR: i32 = 6;
+    pub const PERMISSION_DENIED: i32 = 7;
+    pub const KEYSTORE_EXISTS: i32 = 8;
+    pub const INVALID_KEY_FORMAT: i32 = 9;
+    pub const SIGNATURE_FAILED: i32 = 10;
+    pub const NODE_ERROR: i32 = 11;
+}
+
+/// Errors produced by wallet, key and KeepBox operations
+#[derive(Debug)]
+pub enum Error {
+    /// Command-line options that are individually valid but cannot be
+    /// combined
+    InvalidArgument(String),
+
+    /// Mnemonic phrase failed BIP39 validation
+    InvalidMnemonic(String),
+
+    /// SLIP-0039 share is malformed, fails its checksum, or the shares given
+    /// cannot be combined
+    InvalidShare(String),
+
+    /// Public key bytes are not a valid key
+    InvalidPublicKey(String),
+
+    /// Address is not 32 bytes of hex
+    InvalidAddress(String),
+
+    /// Amount string is not a valid non-negative decimal
+    InvalidAmount(String),
+
+    /// Transaction is malformed or does not belong to the signing wallet
+    InvalidTransaction(String),
+
+    /// Signature is malformed or does not verify
+    InvalidSignature(String),
+
+    /// Derivation path is malformed or not fully hardened
+    InvalidDerivationPath(String),
+
+    /// Password rejected by the strength policy
+    WeakPassword(String),
+
+    /// KeepBox file format version this build cannot read
+    UnsupportedVersion { found: String, supported: String },
+
+    /// Clear-text KeepBox metadata disagrees with the decrypted wallet
+    MetadataMismatch(String),
+
+    /// Transaction envelope fails its checksum, or its summary does not
+    /// describe its transaction
+    IntegrityMismatch(String),
+
+    /// Vault entry label is empty, too long or contains control characters
+    InvalidLabel(String),
+
+    /// Vault already holds a wallet with this label or address
+    DuplicateEntry(String),
+
+    /// No vault entry with this label or address
+    EntryNotFound(String),
+
+    /// Vault holds several wallets and none was selected
+    EntryRequired { count: usize },
+
+    /// KeepBox KDF parameters are outside the accepted bounds
+    InvalidKdfParams(String),
+
+    /// AES-GCM authentication failed (wrong password or corrupted data)
+    DecryptionFailed,
+
+    /// Decrypted wallet does not derive to its recorded address
+    AddressMismatch { expected: String, derived: String },
+
+    /// Key derivation or cipher setup failure
+    Crypto(String),
+
+    /// Feature or algorithm not available in this build
+    Unsupported(String),
+
+    /// OS random number generator failure
+    Entropy(String),
+
+    /// Hex, base64 or JSON decoding/encoding failure
+    Encoding(String),
+
+    /// Key agent unreachable, or it refused a request
+    Agent(String),
+
+    /// Remote signer policy or double-sign protection refused to sign
+    PolicyViolation(String),
+
+    /// Node did not answer, or not with a JSON-RPC response
+    NodeUnreachable(String),
+
+    /// Node answered a JSON-RPC call with an error
+    Rpc { code: i64, message: String },
+
+    /// Filesystem or terminal I/O failure
+    Io { context: String, source: io::Error },
+}
+
+impl Error {
+    /// Wrap an I/O error with a short description of what was being done
+    pub fn io(context: impl Into<String>, source: io::Error) -> Self {
+        Error::Io {
+            context: context.into(),
+            source,
+        }
+    }
+
+    /// Exit code for this error (see [`exit_code`])
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::InvalidArgument(_)
+            | Error::InvalidAmount(_)
+            | Error::InvalidTransaction(_)
+            | Error::InvalidDerivationPath(_)
+            | Error::InvalidLabel(_)
+            | Error::EntryRequired { .. }
+            | Error::InvalidKdfParams(_) => exit_code::INVALID_ARGUMENTS,
+            Error::EntryNotFound(_) => exit_code::KEYSTORE_NOT_FOUND,
+            Error::WeakPassword(_) | Error::DecryptionFailed => exit_code::INVALID_PASSWORD,
+            Error::MetadataMismatch(_)
+            | Error::IntegrityMismatch(_)
+            | Error::Crypto(_)
+            | Error::Entropy(_) => exit_code::ENCRYPTION_ERROR,
+            Error::InvalidMnemonic(_) | Error::InvalidShare(_) | Error::AddressMismatch { .. } => {
+                exit_code::INVALID_MNEMONIC
+            }
+            Error::DuplicateEntry(_) => exit_code::KEYSTORE_EXISTS,
+            Error::PolicyViolation(_) => exit_code::PERMISSION_DENIED,
+            Error::InvalidPublicKey(_)
+            | Error::InvalidAddress(_)
+            | Error::UnsupportedVersion { .. }
+            | Error::Unsupported(_)
+            | Error::Encoding(_) => exit_code::INVALID_KEY_FORMAT,
+            Error::InvalidSignature(_) => exit_code::SIGNATURE_FAILED,
+            Error::Agent(_) => exit_code::FILE_IO_ERROR,
+            Error::NodeUnreachable(_) | Error::Rpc { .. } => exit_code::NODE_ERROR,
+            Error::Io { source, .. } => match source.kind() {
+                io::ErrorKind::NotFound => exit_code::KEYSTORE_NOT_FOUND,
+                io::ErrorKind::PermissionDenied => exit_code::PERMISSION_DENIED,
+                io::ErrorKind::AlreadyExists => exit_code::KEYSTORE_EXISTS,
+                _ => exit_code::FILE_IO_ERROR,
+            },
+        }
+    }
+
+    /// Stable snake_case identifier for scripts. Identifiers are never
+    /// renamed or reused; new variants get new ones.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Error::InvalidArgument(_) => "invalid_argument",
+            Error::InvalidMnemonic(_) => "invalid_mnemonic",
+            Error::InvalidShare(_) => "invalid_share",
+            Error::InvalidPublicKey(_) => "invalid_public_key",
+            Error::InvalidAddress(_) => "invalid_address",
+            Error::InvalidAmount(_) => "invalid_amount",
+            Error::InvalidTransaction(_) => "invalid_transaction",
+            Error::InvalidSignature(_) => "invalid_signature",
+            Error::InvalidDerivationPath(_) => "invalid_derivation_path",
+            Error::WeakPassword(_) => "weak_password",
+            Error::UnsupportedVersion { .. } => "unsupported_version",
+            Error::MetadataMismatch(_) => "metadata_mismatch",
+            Error::IntegrityMismatch(_) => "integrity_mismatch",
+            Error::InvalidLabel(_) => "invalid_label",
+            Error::DuplicateEntry(_) => "duplicate_entry",
+            Error::EntryNotFound(_) => "entry_not_found",
+            Error::EntryRequired { .. } => "entry_required",
+            Error::InvalidKdfParams(_) => "invalid_kdf_params",
+            Error::DecryptionFailed => "decryption_failed",
+            Error::AddressMismatch { .. } => "address_mismatch",
+            Error::Crypto(_) => "crypto_error",
+            Error::Unsupported(_) => "unsupported",
+            Error::Entropy(_) => "entropy_error",
+            Error::Encoding(_) => "encoding_error",
+            Error::Agent(_) => "agent_error",
+            Error::PolicyViolation(_) => "policy_violation",
+            Error::NodeUnreachable(_) => "node_unreachable",
+            Error::Rpc { .. } => "rpc_error",
+            Error::Io { source, .. } => match source.kind() {
+                io::ErrorKind::NotFound => "file_not_found",
+                io::ErrorKind::PermissionDenied => "permission_denied",
+                io::ErrorKind::AlreadyExists => "file_exists",
+                _ => "io_error",
+            },
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidArgument(e) => write!(f, "Invalid arguments: {}", e),
+            Error::InvalidMnemonic(e) => write!(f, "Invalid mnemonic: {}", e),
+            Error::InvalidShare(e) => write!(f, "Invalid share: {}", e),
+            Error::InvalidPublicKey(e) => write!(f, "Invalid public key: {}", e),
+            Error::InvalidAddress(e) => write!(f, "Invalid address: {}", e),
+            Error::InvalidAmount(e) => write!(f, "Invalid amount: {}", e),
+            Error::InvalidTransaction(e) => write!(f, "Invalid transaction: {}", e),
+            Error::InvalidSignature(e) => write!(f, "Invalid signature: {}", e),
+            Error::InvalidDerivationPath(e) => write!(f, "Invalid derivation path: {}", e),
+            Error::WeakPassword(e) => write!(f, "{}", e),
+            Error::UnsupportedVersion { found, supported } => write!(
+                f,
+                "Unsupported KeepBox format version {} (this build reads {}); upgrade boundless-keepbox to open it",
+                found, supported
+            ),
+            Error::MetadataMismatch(e) => {
+                write!(
+                    f,
+                    "KeepBox metadata does not match the encrypted wallet: {}",
+                    e
+                )
+            }
+            Error::IntegrityMismatch(e) => write!(
+                f,
+                "Transaction file is damaged or was modified: {}",
+                e
+            ),
+            Error::InvalidLabel(e) => write!(f, "Invalid label: {}", e),
+            Error::DuplicateEntry(e) => write!(f, "Vault already holds a wallet {}", e),
+            Error::EntryNotFound(e) => write!(f, "No wallet labelled or addressed '{}' in vault", e),
+            Error::EntryRequired { count: 0 } => write!(f, "Vault holds no wallets"),
+            Error::EntryRequired { count } => write!(
+                f,
+                "Vault holds {} wallets; select one by label or address",
+                count
+            ),
+            Error::InvalidKdfParams(e) => write!(f, "Invalid KDF parameters: {}", e),
+            Error::DecryptionFailed => {
+                write!(
+                    f,
+                    "Decryption failed - incorrect password or corrupted data"
+                )
+            }
+            Error::AddressMismatch { expected, derived } => write!(
+                f,
+                "Address mismatch - wallet data may be corrupted (expected {}, derived {})",
+                expected, derived
+            ),
+            Error::Crypto(e) => write!(f, "{}", e),
+            Error::Unsupported(e) => write!(f, "Unsupported: {}", e),
+            Error::Entropy(e) => write!(f, "Failed to generate entropy: {}", e),
+            Error::Encoding(e) => write!(f, "{}", e),
+            Error::Agent(e) => write!(f, "Key agent: {}", e),
+            Error::PolicyViolation(e) => write!(f, "Refused by signing policy: {}", e),
+            Error::NodeUnreachable(e) => write!(f, "Cannot reach node: {}", e),
+            Error::Rpc { code, message } => write!(f, "Node returned error {}: {}", code, message),
+            Error::Io { context, source } => write!(f, "{}: {}", context, source),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_and_ids() {
+        let cases = [
+            (Error::InvalidArgument(String::new()), 1, "invalid_argument"),
+            (Error::DecryptionFailed, 3, "decryption_failed"),
+            (Error::WeakPassword(String::new()), 3, "weak_password"),
+            (Error::Crypto(String::new()), 4, "crypto_error"),
+            (
+                Error::IntegrityMismatch(String::new()),
+                4,
+                "integrity_mismatch",
+            ),
+            (Error::InvalidMnemonic(String::new()), 5, "invalid_mnemonic"),
+            (Error::InvalidShare(String::new()), 5, "invalid_share"),
+            (
+                Error::InvalidPublicKey(String::new()),
+                9,
+                "invalid_public_key",
+            ),
+            (
+                Error::InvalidSignature(String::new()),
+                10,
+                "invalid_signature",
+            ),
+            (Error::Agent(String::new()), 6, "agent_error"),
+            (Error::PolicyViolation(String::new()), 7, "policy_violation"),
+            (
+                Error::NodeUnreachable(String::new()),
+                11,
+                "node_unreachable",
+            ),
+            (
+                Error::Rpc {
+                    code: -32000,
+                    message: String::new(),
+                },
+                11,
+                "rpc_error",
+            ),
+        ];
+        for (error, code, id) in cases {
+            assert_eq!((error.exit_code(), error.id()), (code, id));
+        }
+
+        // I/O errors are classified by kind
+        let io_cases = [
+            (io::ErrorKind::NotFound, 2, "file_not_found"),
+            (io::ErrorKind::Other, 6, "io_error"),
+            (io::ErrorKind::PermissionDenied, 7, "permission_denied"),
+            (io::ErrorKind::AlreadyExists, 8, "file_exists"),
+        ];
+        for (kind, code, id) in io_cases {
+            let error = Error::io("context", kind.into());
+            assert_eq!((error.exit_code(), error.id()), (code, id));
+        }
+    }
+}
@@ -0,0 +1,333 @@
+//! Crash-safe creation and replacement of wallet and KeepBox files
+//!
+//! Wallet files hold mnemonics, so commands never overwrite one by accident:
+//! new files are created with create-new semantics and owner-only
+//! permissions, and replacing an existing file first moves it to a
+//! timestamped backup next to it.
+//!
+//! Every write goes through a temporary file in the same directory that is
+//! fsynced before it is linked or renamed into place, followed by an fsync
+//! of the directory, so a crash or full disk leaves either the old file or
+//! the complete new one - never a truncated wallet.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+
+/// Fail with an "already exists" error (exit code 8) if `path` exists and
+/// `force` is not set, so commands can refuse before prompting for anything
+pub fn ensure_absent(path: &Path, force: bool) -> Result<()> {
+    if force || fs::symlink_metadata(path).is_err() {
+        return Ok(());
+    }
+    Err(already_exists(path))
+}
+
+/// Create `path` with owner-only (0600) permissions, failing if anything
+/// already exists there
+pub fn create_private_file(path: &Path, contents: &[u8]) -> Result<()> {
+    ensure_absent(path, false)?;
+    let tmp = TempFile::write(path, contents, None)?;
+
+    // Unlike rename, a hard link never replaces a file created meanwhile
+    match fs::hard_link(&tmp.path, path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => return Err(already_exists(path)),
+        Err(_) => {
+            // Filesystems without hard links
+            ensure_absent(path, false)?;
+            fs::rename(&tmp.path, path)
+                .map_err(|e| Error::io(format!("Failed to create {}", path.display()), e))?;
+        }
+    }
+    drop(tmp);
+    sync_parent(path)
+}
+
+/// Create or replace `path` with owner-only (0600) permissions. The new
+/// contents are written to a temporary file, fsynced and read back into
+/// `verify` before being renamed over `path`; if any step fails the
+/// original is left untouched, and a failed check is reported as
+/// [`Error::Crypto`]. A replaced file's owner and group are kept.
+pub fn replace_private_file(
+    path: &Path,
+    contents: &[u8],
+    verify: impl FnOnce(&[u8]) -> Result<()>,
+) -> Result<()> {
+    let original = fs::metadata(path).ok();
+    let tmp = TempFile::write(path, contents, original.as_ref())?;
+
+    let written = fs::read(&tmp.path)
+        .map_err(|e| Error::io(format!("Failed to read back {}", tmp.path.display()), e))?;
+    verify(&written).map_err(|e| {
+        Error::Crypto(format!(
+            "new contents of {} failed verification ({}); the original was left unchanged",
+            path.display(),
+            e
+        ))
+    })?;
+
+    fs::rename(&tmp.path, path)
+        .map_err(|e| Error::io(format!("Failed to replace {}", path.display()), e))?;
+    drop(tmp);
+    sync_parent(path)
+}
+
+/// Move an existing file at `path` aside to a timestamped backup, returning
+/// the backup's path (`None` if there was nothing to move)
+pub fn backup_existing(path: &Path) -> Result<Option<PathBuf>> {
+    if fs::symlink_metadata(path).is_err() {
+        return Ok(None);
+    }
+    let backup = backup_path(path, None);
+    fs::rename(path, &backup)
+        .map_err(|e| Error::io(format!("Failed to back up {}", path.display()), e))?;
+    Ok(Some(backup))
+}
+
+/// Copy `path` to a timestamped backup, leaving the original in place
+pub fn backup_copy(path: &Path) -> Result<PathBuf> {
+    backup_copy_tagged(path, None)
+}
+
+/// [`backup_copy`], with `tag` (e.g. the format version being replaced)
+/// put before the timestamp: `<name>.<tag>-<UTC timestamp>.bak`
+pub fn backup_copy_tagged(path: &Path, tag: Option<&str>) -> Result<PathBuf> {
+    let backup = backup_path(path, tag);
+    fs::copy(path, &backup)
+        .map_err(|e| Error::io(format!("Failed to back up {}", path.display()), e))?;
+    Ok(backup)
+}
+
+/// `<name>.[<tag>-]<UTC timestamp>.bak` next to `path`, with a counter
+/// added if a backup was already taken this second
+fn backup_path(path: &Path, tag: Option<&str>) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ");
+    let stamp = match tag {
+        Some(tag) => format!("{}-{}", tag, stamp),
+        None => stamp.to_string(),
+    };
+
+    let mut candidate = path.with_file_name(format!("{}.{}.bak", name, stamp));
+    let mut n = 1;
+    while fs::symlink_metadata(&candidate).is_ok() {
+        candidate = path.with_file_name(format!("{}.{}-{}.bak", name, stamp, n));
+        n += 1;
+    }
+    candidate
+}
+
+/// A fsynced 0600 file next to its destination, removed on drop unless it
+/// has been renamed away
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    /// Write `contents` to `.<name>.<pid>.<random>.tmp` beside `dest`,
+    /// giving it the owner and group of `original` when replacing a file.
+    /// The random part keeps files left by a crashed run with the same pid
+    /// from blocking later writes.
+    fn write(dest: &Path, contents: &[u8], original: Option<&fs::Metadata>) -> Result<Self> {
+        let (tmp, mut file) = Self::create(dest)?;
+
+        let context = || format!("Failed to write {}", tmp.path.display());
+        file.write_all(contents)
+            .map_err(|e| Error::io(context(), e))?;
+        #[cfg(unix)]
+        if let Some(original) = original {
+            keep_owner(&file, original).map_err(|e| Error::io(context(), e))?;
+        }
+        #[cfg(not(unix))]
+        let _ = original;
+        file.sync_all().map_err(|e| Error::io(context(), e))?;
+        Ok(tmp)
+    }
+
+    fn create(dest: &Path) -> Result<(Self, fs::File)> {
+        Self::create_with(dest, rand::random::<u32>)
+    }
+
+    /// [`TempFile::create`] drawing the random part from `suffix`
+    fn create_with(dest: &Path, mut suffix: impl FnMut() -> u32) -> Result<(Self, fs::File)> {
+        let name = dest.file_name().unwrap_or_default().to_string_lossy();
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut attempts = 0;
+        loop {
+            let path = dest.with_file_name(format!(
+                ".{}.{}.{:08x}.tmp",
+                name,
+                std::process::id(),
+                suffix()
+            ));
+            match options.open(&path) {
+                Ok(file) => return Ok((TempFile { path }, file)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists && attempts < 16 => {
+                    attempts += 1
+                }
+                Err(e) => return Err(Error::io(format!("Failed to write {}", path.display()), e)),
+            }
+        }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Give `file` the owner and group of `original` if they differ, e.g. when
+/// root edits a service user's KeepBox
+#[cfg(unix)]
+fn keep_owner(file: &fs::File, original: &fs::Metadata) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let current = file.metadata()?;
+    if (current.uid(), current.gid()) == (original.uid(), original.gid()) {
+        return Ok(());
+    }
+    std::os::unix::fs::fchown(file, Some(original.uid()), Some(original.gid()))
+}
+
+/// Fsync the directory holding `path` so a rename or link survives a crash
+fn sync_parent(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        fs::File::open(dir)
+            .and_then(|dir| dir.sync_all())
+            .map_err(|e| Error::io(format!("Failed to sync {}", dir.display()), e))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+fn already_exists(path: &Path) -> Error {
+    Error::io(
+        format!(
+            "{} already exists (use --force to back it up and replace it)",
+            path.display()
+        ),
+        io::Error::from(io::ErrorKind::AlreadyExists),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_refuses_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.json");
+
+        ensure_absent(&path, false).unwrap();
+        create_private_file(&path, b"first").unwrap();
+
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+        let err = create_private_file(&path, b"second").unwrap_err();
+        assert_eq!(err.exit_code(), 8);
+        assert_eq!(err.id(), "file_exists");
+        assert!(ensure_absent(&path, false).is_err());
+        ensure_absent(&path, true).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"first");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_replace_verifies_before_renaming() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.keepbox");
+        fs::write(&path, b"old").unwrap();
+
+        let err = replace_private_file(&path, b"new", |written| {
+            assert_eq!(written, b"new");
+            Err(Error::DecryptionFailed)
+        })
+        .unwrap_err();
+        assert!(matches!(err, Error::Crypto(_)));
+        assert_eq!(fs::read(&path).unwrap(), b"old");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        replace_private_file(&path, b"new", |_| Ok(())).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_backups_are_unique() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.keepbox");
+        assert!(backup_existing(&path).unwrap().is_none());
+
+        fs::write(&path, b"one").unwrap();
+        let first = backup_existing(&path).unwrap().unwrap();
+        assert!(!path.exists());
+
+        fs::write(&path, b"two").unwrap();
+        let copy = backup_copy(&path).unwrap();
+        let second = backup_existing(&path).unwrap().unwrap();
+
+        assert_ne!(first, second);
+        assert_ne!(copy, second);
+        assert_eq!(fs::read(&first).unwrap(), b"one");
+        assert_eq!(fs::read(&copy).unwrap(), b"two");
+        assert_eq!(fs::read(&second).unwrap(), b"two");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 3);
+
+        let tagged = backup_copy_tagged(&copy, Some("v1")).unwrap();
+        let again = backup_copy_tagged(&copy, Some("v1")).unwrap();
+        assert_ne!(tagged, again);
+        assert!(tagged.to_string_lossy().contains(".bak.v1-"));
+        assert_eq!(fs::read(&again).unwrap(), b"two");
+    }
+
+    #[test]
+    fn test_stale_temp_file_does_not_block_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.keepbox");
+        // As left behind by a run with our pid that crashed mid-write
+        let stale = dir.path().join(format!(
+            ".wallet.keepbox.{}.{:08x}.tmp",
+            std::process::id(),
+            1
+        ));
+        fs::write(&stale, b"partial").unwrap();
+
+        let mut suffixes = [1, 1, 2].into_iter();
+        let (tmp, _) = TempFile::create_with(&path, || suffixes.next().unwrap()).unwrap();
+        assert_ne!(tmp.path, stale);
+        assert!(tmp.path.to_string_lossy().ends_with(".00000002.tmp"));
+        drop(tmp);
+        assert_eq!(fs::read(&stale).unwrap(), b"partial");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,444 @@
+//! Minimal HTTP/1.1 for local JSON services
+//!
+//! Just enough HTTP for `boundless-signer serve` and the node JSON-RPC
+//! client: one request per connection, bodies sized by `Content-Length`, and
+//! no chunked encoding, keep-alive or TLS. It is meant for loopback and
+//! trusted networks, not for facing the internet.
+
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+use std::str::FromStr;
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::error::{Error, Result};
+
+/// Largest request or response body accepted
+pub const MAX_BODY_LEN: usize = 1 << 20;
+
+/// Largest request line plus headers accepted
+const MAX_HEAD_LEN: usize = 16 * 1024;
+
+/// Parsed HTTP request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    /// Path without the query string
+    pub path: String,
+    /// Header names are lowercased
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Read one request. Malformed or oversized requests are
+    /// [`Error::Encoding`] errors, to be answered with a 400.
+    pub fn read_from(reader: &mut impl BufRead) -> Result<Self> {
+        let malformed = |e: String| Error::Encoding(format!("Malformed HTTP request: {}", e));
+
+        let (request_line, headers) = read_head(reader).map_err(malformed)?;
+        let mut parts = request_line.split_whitespace();
+        let (method, target) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/1.") => {
+                (method.to_string(), target)
+            }
+            _ => return Err(malformed(format!("bad request line {:?}", request_line))),
+        };
+        let path = target.split('?').next().unwrap_or_default().to_string();
+
+        let mut request = Request {
+            method,
+            path,
+            headers,
+            body: Vec::new(),
+        };
+        let len = content_length(&request.headers)
+            .map_err(malformed)?
+            .unwrap_or(0);
+        request.body = vec![0u8; len];
+        reader
+            .read_exact(&mut request.body)
+            .map_err(|e| malformed(format!("truncated body: {}", e)))?;
+        Ok(request)
+    }
+
+    /// First header called `name` (lowercase)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        header(&self.headers, name)
+    }
+}
+
+/// HTTP response with a JSON body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// `status` with `body` serialized as JSON
+    pub fn json(status: u16, body: &impl Serialize) -> Self {
+        Response {
+            status,
+            body: serde_json::to_vec(body).unwrap_or_default(),
+        }
+    }
+
+    /// `{"error":{"id":..,"message":..}}` with the status for `error`
+    pub fn error(error: &Error) -> Self {
+        Response::json(
+            status_for(error),
+            &json!({
+                "error": {
+                    "id": error.id(),
+                    "message": error.to_string(),
+                }
+            }),
+        )
+    }
+
+    /// Read a response; without `Content-Length` the body runs to the end
+    /// of the connection. Malformed responses are [`Error::Encoding`]
+    /// errors.
+    pub fn read_from(reader: &mut impl BufRead) -> Result<Self> {
+        let malformed = |e: String| Error::Encoding(format!("Malformed HTTP response: {}", e));
+
+        let (status_line, headers) = read_head(reader).map_err(malformed)?;
+        let mut parts = status_line.split_whitespace();
+        let status = match (parts.next(), parts.next().map(str::parse::<u16>)) {
+            (Some(version), Some(Ok(status))) if version.starts_with("HTTP/1.") => status,
+            _ => return Err(malformed(format!("bad status line {:?}", status_line))),
+        };
+
+        let mut body = Vec::new();
+        match content_length(&headers).map_err(malformed)? {
+            Some(len) => {
+                body.resize(len, 0);
+                reader
+                    .read_exact(&mut body)
+                    .map_err(|e| malformed(format!("truncated body: {}", e)))?;
+            }
+            None => {
+                (&mut *reader)
+                    .take(MAX_BODY_LEN as u64 + 1)
+                    .read_to_end(&mut body)
+                    .map_err(|e| malformed(format!("failed to read body: {}", e)))?;
+                if body.len() > MAX_BODY_LEN {
+                    return Err(malformed("body too large".to_string()));
+                }
+            }
+        }
+        Ok(Response { status, body })
+    }
+
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.status,
+            reason(self.status),
+            self.body.len()
+        )?;
+        writer.write_all(&self.body)?;
+        writer.flush()
+    }
+}
+
+/// `http://host[:port][/path]` address of an HTTP service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    /// Host name or IP address, without IPv6 brackets
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+}
+
+impl Endpoint {
+    /// `host:port` with IPv6 addresses bracketed, as in a `Host` header
+    pub fn authority(&self) -> String {
+        if self.host.contains(':') {
+            format!("[{}]:{}", self.host, self.port)
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+
+    /// Write a POST of the JSON `body` to this endpoint
+    pub fn write_post(&self, writer: &mut impl Write, body: &[u8]) -> io::Result<()> {
+        write!(
+            writer,
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.path,
+            self.authority(),
+            body.len()
+        )?;
+        writer.write_all(body)?;
+        writer.flush()
+    }
+}
+
+impl FromStr for Endpoint {
+    type Err = Error;
+
+    fn from_str(url: &str) -> Result<Self> {
+        let invalid = |why: &str| Error::InvalidArgument(format!("invalid URL '{}': {}", url, why));
+
+        let rest = match url.split_once("://") {
+            Some((scheme, rest)) if scheme.eq_ignore_ascii_case("http") => rest,
+            Some((scheme, _)) if scheme.eq_ignore_ascii_case("https") => {
+                return Err(Error::Unsupported(
+                    "https:// endpoints; reach TLS nodes through a local proxy".to_string(),
+                ))
+            }
+            Some(_) => return Err(invalid("only http:// is supported")),
+            None => url,
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        if authority.contains('@') {
+            return Err(invalid("credentials in URLs are not supported"));
+        }
+
+        let (host, port) = match authority.strip_prefix('[') {
+            Some(bracketed) => match bracketed.split_once(']') {
+                Some((host, "")) => (host, None),
+                Some((host, port)) => (host, Some(port.strip_prefix(':').unwrap_or(port))),
+                None => return Err(invalid("unclosed '['")),
+            },
+            None => match authority.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            },
+        };
+        if host.is_empty() {
+            return Err(invalid("no host"));
+        }
+        let port = match port {
+            Some(port) => port.parse().map_err(|_| invalid("bad port"))?,
+            None => 80,
+        };
+
+        Ok(Endpoint {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "http://{}{}", self.authority(), self.path)
+    }
+}
+
+/// Status code reporting `error` to an HTTP client
+pub fn status_for(error: &Error) -> u16 {
+    match error {
+        Error::PolicyViolation(_) => 403,
+        Error::EntryNotFound(_) => 404,
+        Error::Crypto(_) | Error::Entropy(_) | Error::Io { .. } => 500,
+        _ => 400,
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+/// Start line and headers (names lowercased) of a request or response
+fn read_head(
+    reader: &mut impl BufRead,
+) -> std::result::Result<(String, Vec<(String, String)>), String> {
+    let mut head_len = 0;
+    let start = read_line(reader, &mut head_len)?;
+
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader, &mut head_len)?;
+        if line.is_empty() {
+            break;
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| format!("bad header {:?}", line))?;
+        headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+    }
+    if header(&headers, "transfer-encoding").is_some() {
+        return Err("chunked bodies are not supported".to_string());
+    }
+    Ok((start, headers))
+}
+
+/// One CRLF- (or LF-) terminated line of the head
+fn read_line(
+    reader: &mut impl BufRead,
+    head_len: &mut usize,
+) -> std::result::Result<String, String> {
+    let mut line = Vec::new();
+    let limit = (MAX_HEAD_LEN - *head_len) as u64 + 1;
+    (&mut *reader)
+        .take(limit)
+        .read_until(b'\n', &mut line)
+        .map_err(|e| format!("failed to read head: {}", e))?;
+    *head_len += line.len();
+    if *head_len > MAX_HEAD_LEN {
+        return Err("head too large".to_string());
+    }
+    if line.pop() != Some(b'\n') {
+        return Err("connection closed mid-head".to_string());
+    }
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    String::from_utf8(line).map_err(|_| "head is not UTF-8".to_string())
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, v)| v.as_str())
+}
+
+/// `Content-Length`, checked against [`MAX_BODY_LEN`]
+fn content_length(headers: &[(String, String)]) -> std::result::Result<Option<usize>, String> {
+    let Some(len) = header(headers, "content-length") else {
+        return Ok(None);
+    };
+    let len = len
+        .parse::<usize>()
+        .map_err(|_| format!("bad Content-Length {:?}", len))?;
+    if len > MAX_BODY_LEN {
+        return Err(format!(
+            "body of {} bytes exceeds the {} byte limit",
+            len, MAX_BODY_LEN
+        ));
+    }
+    Ok(Some(len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_request() {
+        let raw = b"POST /sign/message?x=1 HTTP/1.1\r\nHost: localhost\r\nContent-Length: 4\r\nAuthorization: Bearer t\r\n\r\n{}\r\nextra";
+        let request = Request::read_from(&mut &raw[..]).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/sign/message");
+        assert_eq!(request.header("authorization"), Some("Bearer t"));
+        assert_eq!(request.body, b"{}\r\n");
+
+        let get = Request::read_from(&mut &b"GET /keys HTTP/1.0\n\n"[..]).unwrap();
+        assert_eq!((get.method.as_str(), get.body.len()), ("GET", 0));
+
+        for bad in [
+            &b"GET /keys\r\n\r\n"[..],
+            b"GET /keys HTTP/1.1\r\nno-colon\r\n\r\n",
+            b"POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nshort",
+            b"POST / HTTP/1.1\r\nContent-Length: 99999999\r\n\r\n",
+            b"GET /keys HTTP/1.1\r\nHost: x",
+        ] {
+            assert!(
+                matches!(Request::read_from(&mut &bad[..]), Err(Error::Encoding(_))),
+                "{:?}",
+                String::from_utf8_lossy(bad)
+            );
+        }
+        let huge = format!("GET / HTTP/1.1\r\nX: {}\r\n\r\n", "a".repeat(MAX_HEAD_LEN));
+        assert!(Request::read_from(&mut huge.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_write_and_read_response() {
+        let mut out = Vec::new();
+        Response::error(&Error::PolicyViolation("no".to_string()))
+            .write_to(&mut out)
+            .unwrap();
+        assert!(out.starts_with(b"HTTP/1.1 403 Forbidden\r\n"));
+
+        let response = Response::read_from(&mut &out[..]).unwrap();
+        assert_eq!(response.status, 403);
+        let body: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(body["error"]["id"], "policy_violation");
+
+        // Without Content-Length the body runs to the end of the connection
+        let raw = b"HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n{\"result\":1}";
+        assert_eq!(
+            Response::read_from(&mut &raw[..]).unwrap().body,
+            b"{\"result\":1}"
+        );
+        assert!(Response::read_from(&mut &b"garbage\r\n\r\n"[..]).is_err());
+    }
+
+    #[test]
+    fn test_endpoint() {
+        let parse = |url: &str| url.parse::<Endpoint>();
+
+        let endpoint = parse("http://localhost:9933").unwrap();
+        assert_eq!(
+            (
+                endpoint.host.as_str(),
+                endpoint.port,
+                endpoint.path.as_str()
+            ),
+            ("localhost", 9933, "/")
+        );
+        assert_eq!(endpoint.to_string(), "http://localhost:9933/");
+
+        let endpoint = parse("HTTP://[::1]:8545/rpc/v1").unwrap();
+        assert_eq!(
+            (
+                endpoint.host.as_str(),
+                endpoint.port,
+                endpoint.path.as_str()
+            ),
+            ("::1", 8545, "/rpc/v1")
+        );
+        assert_eq!(endpoint.authority(), "[::1]:8545");
+        assert_eq!(parse("10.0.0.5").unwrap().port, 80);
+
+        assert!(matches!(
+            parse("https://node.example"),
+            Err(Error::Unsupported(_))
+        ));
+        for bad in [
+            "ftp://x",
+            "http://:80",
+            "http://host:port",
+            "http://user:pw@host",
+            "http://[::1",
+        ] {
+            assert!(
+                matches!(parse(bad), Err(Error::InvalidArgument(_))),
+                "{}",
+                bad
+            );
+        }
+
+        let mut out = Vec::new();
+        endpoint.write_post(&mut out, b"{}").unwrap();
+        let request = Request::read_from(&mut &out[..]).unwrap();
+        assert_eq!(
+            (request.method.as_str(), request.path.as_str()),
+            ("POST", "/rpc/v1")
+        );
+        assert_eq!(request.header("host"), Some("[::1]:8545"));
+        assert_eq!(request.body, b"{}");
+    }
+}
@@ -0,0 +1,1090 @@
+//! KeepBox - Encrypted Wallet Storage
+//!
+//! Secure encrypted storage for Boundless blockchain wallets
+//! Using AES-256-GCM with Argon2id key derivation
+//!
+//! Security Features:
+//! - AES-256-GCM authenticated encryption (AEAD), with the file header as
+//!   associated data
+//! - Argon2id key derivation (memory-hard, 64 MB RAM by default; the
+//!   parameters are stored in the file and bounds-checked before use)
+//! - Zeroization of sensitive data
+//! - File permissions (0600)
+//! - Password strength validation
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use argon2::{Argon2, ParamsBuilder, Version};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::derivation::Derivation;
+use crate::error::{Error, Result};
+use crate::files;
+use crate::keys::{KeyType, Keypair, PublicKeyInfo};
+use crate::mnemonic::{Language, Mnemonic};
+use crate::wallet::WalletOutput;
+
+/// Current KeepBox file format version
+pub const KEEPBOX_VERSION: &str = FormatVersion::CURRENT.as_str();
+
+// Accepted Argon2id parameter ranges. Files outside them are rejected before
+// any key derivation, so a crafted file cannot demand gigabytes of RAM or
+// hours of CPU.
+pub const KDF_MEMORY_RANGE: std::ops::RangeInclusive<u32> = 8 * 1024..=1024 * 1024; // 8 MB - 1 GB
+pub const KDF_TIME_RANGE: std::ops::RangeInclusive<u32> = 1..=16;
+pub const KDF_PARALLELISM_RANGE: std::ops::RangeInclusive<u32> = 1..=16;
+
+// ===== Data Structures =====
+
+/// Released KeepBox file format versions. All share the JSON layout below;
+/// they differ in how the payload is sealed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FormatVersion {
+    /// Original format; the header is stored in clear and not authenticated
+    V1_0_0,
+    /// Version, crypto parameters and metadata are authenticated as AES-GCM
+    /// associated data
+    V2_0_0,
+    /// Adds multi-wallet vault files (`"kind": "vault"`, see
+    /// [`crate::vault`]). Single-wallet files are unchanged and still written
+    /// as 2.0.0, so older builds keep reading them.
+    V2_1_0,
+}
+
+impl FormatVersion {
+    /// Version written by this build for single-wallet files
+    pub const CURRENT: FormatVersion = FormatVersion::V2_0_0;
+
+    /// Version written for vault files; older builds report it as
+    /// unsupported instead of failing on the vault layout
+    pub const VAULT: FormatVersion = FormatVersion::V2_1_0;
+
+    /// Every version this build can read, oldest first
+    pub const SUPPORTED: [FormatVersion; 3] = [
+        FormatVersion::V1_0_0,
+        FormatVersion::V2_0_0,
+        FormatVersion::V2_1_0,
+    ];
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            FormatVersion::V1_0_0 => "1.0.0",
+            FormatVersion::V2_0_0 => "2.0.0",
+            FormatVersion::V2_1_0 => "2.1.0",
+        }
+    }
+
+    /// Whether the file header is covered by the AEAD tag
+    pub fn authenticates_header(&self) -> bool {
+        *self >= FormatVersion::V2_0_0
+    }
+}
+
+impl fmt::Display for FormatVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for FormatVersion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::SUPPORTED
+            .into_iter()
+            .find(|version| version.as_str() == s.trim())
+            .ok_or_else(|| Error::UnsupportedVersion {
+                found: s.to_string(),
+                supported: Self::SUPPORTED.map(|v| v.as_str()).join(", "),
+            })
+    }
+}
+
+impl Serialize for FormatVersion {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for FormatVersion {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let version = String::deserialize(deserializer)?;
+        version.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// On-disk KeepBox file
+#[derive(Serialize, Deserialize)]
+pub struct KeepBox {
+    pub version: FormatVersion,
+    pub crypto: CryptoParams,
+    pub encrypted_data: String, // Base64 encoded
+    pub metadata: Metadata,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CryptoParams {
+    pub cipher: String,
+    pub kdf: String,
+    pub kdf_params: KdfParams,
+    pub nonce: String, // Base64 encoded (12 bytes for GCM)
+}
+
+impl CryptoParams {
+    /// AES-256-GCM / Argon2id parameters with the salt and nonce left empty
+    /// until the first [`CryptoParams::refresh`]
+    pub(crate) fn new(kdf: &KdfCost) -> Self {
+        CryptoParams {
+            cipher: "aes-256-gcm".to_string(),
+            kdf: "argon2id".to_string(),
+            kdf_params: KdfParams {
+                memory_cost: kdf.memory_cost,
+                time_cost: kdf.time_cost,
+                parallelism: kdf.parallelism,
+                salt: String::new(),
+            },
+            nonce: String::new(),
+        }
+    }
+
+    /// Set the KDF cost and a fresh salt and nonce, returning the raw salt
+    /// and nonce to encrypt with
+    pub(crate) fn refresh(&mut self, kdf: &KdfCost) -> Result<([u8; 32], [u8; 12])> {
+        let salt: [u8; 32] = random_bytes()?;
+        let nonce: [u8; 12] = random_bytes()?;
+
+        self.kdf_params.memory_cost = kdf.memory_cost;
+        self.kdf_params.time_cost = kdf.time_cost;
+        self.kdf_params.parallelism = kdf.parallelism;
+        self.kdf_params.salt = BASE64.encode(salt);
+        self.nonce = BASE64.encode(nonce);
+        Ok((salt, nonce))
+    }
+
+    /// Check that the algorithms are supported and the KDF parameters are
+    /// in bounds, and decode the ciphertext, salt and nonce
+    pub(crate) fn decode(&self, encrypted_data: &str) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+        if self.cipher != "aes-256-gcm" {
+            return Err(Error::Unsupported(format!("cipher '{}'", self.cipher)));
+        }
+        if self.kdf != "argon2id" {
+            return Err(Error::Unsupported(format!("KDF '{}'", self.kdf)));
+        }
+        self.kdf_params.cost().validate()?;
+
+        let ciphertext = BASE64
+            .decode(encrypted_data)
+            .map_err(|_| Error::Encoding("Invalid base64 encoding in encrypted_data".into()))?;
+        let salt = BASE64
+            .decode(&self.kdf_params.salt)
+            .map_err(|_| Error::Encoding("Invalid base64 encoding in salt".into()))?;
+        let nonce = BASE64
+            .decode(&self.nonce)
+            .map_err(|_| Error::Encoding("Invalid base64 encoding in nonce".into()))?;
+        if nonce.len() != 12 {
+            return Err(Error::Encoding(format!(
+                "Invalid nonce length: expected 12 bytes, got {}",
+                nonce.len()
+            )));
+        }
+        Ok((ciphertext, salt, nonce))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct KdfParams {
+    pub memory_cost: u32,
+    pub time_cost: u32,
+    pub parallelism: u32,
+    pub salt: String, // Base64 encoded (32 bytes)
+}
+
+impl KdfParams {
+    /// The cost part of the parameters
+    pub fn cost(&self) -> KdfCost {
+        KdfCost {
+            memory_cost: self.memory_cost,
+            time_cost: self.time_cost,
+            parallelism: self.parallelism,
+        }
+    }
+}
+
+/// Argon2id cost parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfCost {
+    /// Memory in KiB
+    pub memory_cost: u32,
+    /// Iterations
+    pub time_cost: u32,
+    /// Lanes
+    pub parallelism: u32,
+}
+
+impl KdfCost {
+    /// Reject parameters outside the accepted ranges
+    pub fn validate(&self) -> Result<()> {
+        let check = |name: &str, value: u32, range: std::ops::RangeInclusive<u32>| {
+            if range.contains(&value) {
+                Ok(())
+            } else {
+                Err(Error::InvalidKdfParams(format!(
+                    "{} {} outside accepted range {}..={}",
+                    name,
+                    value,
+                    range.start(),
+                    range.end()
+                )))
+            }
+        };
+        check("memory_cost", self.memory_cost, KDF_MEMORY_RANGE)?;
+        check("time_cost", self.time_cost, KDF_TIME_RANGE)?;
+        check("parallelism", self.parallelism, KDF_PARALLELISM_RANGE)
+    }
+}
+
+/// The standard preset: parameters matching ENCRYPTED_KEYSTORE_DESIGN.md
+impl Default for KdfCost {
+    fn default() -> Self {
+        KdfPreset::Standard.cost()
+    }
+}
+
+impl fmt::Display for KdfCost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "m={} KiB, t={}, p={}",
+            self.memory_cost, self.time_cost, self.parallelism
+        )
+    }
+}
+
+/// Named Argon2id cost presets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KdfPreset {
+    /// 19 MB, 2 iterations: fast unlocks on constrained machines
+    Interactive,
+    /// 64 MB, 3 iterations, 4 lanes
+    #[default]
+    Standard,
+    /// 256 MB, 4 iterations, 4 lanes: cold storage
+    Paranoid,
+}
+
+impl KdfPreset {
+    pub fn cost(&self) -> KdfCost {
+        let (memory_cost, time_cost, parallelism) = match self {
+            KdfPreset::Interactive => (19 * 1024, 2, 1),
+            KdfPreset::Standard => (64 * 1024, 3, 4),
+            KdfPreset::Paranoid => (256 * 1024, 4, 4),
+        };
+        KdfCost {
+            memory_cost,
+            time_cost,
+            parallelism,
+        }
+    }
+}
+
+impl FromStr for KdfPreset {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "interactive" => Ok(KdfPreset::Interactive),
+            "standard" => Ok(KdfPreset::Standard),
+            "paranoid" => Ok(KdfPreset::Paranoid),
+            other => Err(Error::InvalidKdfParams(format!(
+                "unknown preset '{}' (expected interactive, standard or paranoid)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Unencrypted metadata, readable without the password
+#[derive(Serialize, Deserialize)]
+pub struct Metadata {
+    pub created: String,
+    pub modified: String,
+    pub label: Option<String>,
+    pub address: String,
+}
+
+/// Secret wallet contents stored inside the encrypted payload
+#[derive(Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct WalletData {
+    pub mnemonic: String,
+    pub public_key: String,
+    pub address: String,
+    #[zeroize(skip)]
+    pub key_type: KeyType,
+
+    /// Component public keys (two for hybrid wallets)
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub keys: Vec<PublicKeyInfo>,
+
+    /// Key derivation scheme; wallets from before HD support are legacy
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub derivation: Derivation,
+
+    /// Keys were derived with a BIP39 passphrase. The passphrase itself is
+    /// never stored; it must be supplied again to re-derive the keys.
+    #[serde(default)]
+    pub has_passphrase: bool,
+
+    /// Wordlist of the mnemonic; wallets from before multi-language
+    /// support are English
+    #[serde(default)]
+    #[zeroize(skip)]
+    pub language: Language,
+}
+
+impl WalletData {
+    /// Derive wallet data from a mnemonic, BIP39 passphrase and derivation
+    pub fn from_mnemonic(
+        mnemonic: &Mnemonic,
+        passphrase: &str,
+        key_type: KeyType,
+        derivation: &Derivation,
+    ) -> Result<Self> {
+        let keypair = Keypair::derive(&mnemonic.to_seed(passphrase), key_type, derivation)?;
+
+        Ok(WalletData {
+            mnemonic: mnemonic.phrase(),
+            public_key: hex::encode(keypair.public_key_bytes()),
+            address: keypair.address().to_hex(),
+            key_type: keypair.key_type(),
+            keys: keypair.public_keys(),
+            derivation: derivation.clone(),
+            has_passphrase: !passphrase.is_empty(),
+            language: mnemonic.language(),
+        })
+    }
+
+    /// Re-derive the keys with `passphrase` and build a plaintext wallet
+    /// file, failing if they do not match the stored address
+    pub fn to_output(&self, passphrase: &str, show_private: bool) -> Result<WalletOutput> {
+        let mnemonic = Mnemonic::parse_in(self.language, &self.mnemonic)?;
+        let output = WalletOutput::from_mnemonic(
+            &mnemonic,
+            passphrase,
+            self.key_type,
+            &self.derivation,
+            show_private,
+        )?;
+        if output.address != self.address {
+            return Err(Error::AddressMismatch {
+                expected: self.address.clone(),
+                derived: output.address.clone(),
+            });
+        }
+        Ok(output)
+    }
+
+    /// Re-derive the address from the stored mnemonic and compare
+    pub fn verify(&self, passphrase: &str) -> Result<()> {
+        self.keypair(passphrase).map(|_| ())
+    }
+
+    /// Re-derive the signing keypair, failing if it does not match the
+    /// stored address
+    pub fn keypair(&self, passphrase: &str) -> Result<Keypair> {
+        let mnemonic = Mnemonic::parse_in(self.language, &self.mnemonic)?;
+        let keypair = Keypair::derive(
+            &mnemonic.to_seed(passphrase),
+            self.key_type,
+            &self.derivation,
+        )?;
+        let derived = keypair.address().to_hex();
+        if derived != self.address {
+            return Err(Error::AddressMismatch {
+                expected: self.address.clone(),
+                derived,
+            });
+        }
+        Ok(keypair)
+    }
+}
+
+/// Header fields authenticated as AES-GCM associated data from format 2.0.0
+#[derive(Serialize)]
+struct AuthenticatedHeader<'a> {
+    version: &'a str,
+    crypto: &'a CryptoParams,
+    metadata: &'a Metadata,
+}
+
+impl KeepBox {
+    /// Encrypt a wallet under a password into a new KeepBox using the
+    /// standard KDF cost
+    pub fn seal(wallet_data: &WalletData, password: &str, label: Option<String>) -> Result<Self> {
+        Self::seal_with(wallet_data, password, label, &KdfCost::default())
+    }
+
+    /// Encrypt a wallet with explicit Argon2id cost parameters
+    pub fn seal_with(
+        wallet_data: &WalletData,
+        password: &str,
+        label: Option<String>,
+        kdf: &KdfCost,
+    ) -> Result<Self> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let mut keepbox = KeepBox {
+            version: FormatVersion::CURRENT,
+            crypto: CryptoParams::new(kdf),
+            encrypted_data: String::new(),
+            metadata: Metadata {
+                created: now.clone(),
+                modified: now,
+                label,
+                address: wallet_data.address.clone(),
+            },
+        };
+        keepbox.encrypt(wallet_data, password, kdf)?;
+        Ok(keepbox)
+    }
+
+    /// Decrypt the wallet data using the file's own KDF parameters. For
+    /// 2.0.0 files this also authenticates the version, crypto parameters
+    /// and metadata; see [`KeepBox::check_metadata`] for 1.0.0 files.
+    pub fn unseal(&self, password: &str) -> Result<WalletData> {
+        let (ciphertext, salt, nonce) = self.crypto.decode(&self.encrypted_data)?;
+        decrypt_payload(
+            &ciphertext,
+            password,
+            &salt,
+            &nonce,
+            &self.crypto.kdf_params.cost(),
+            &self.associated_data()?,
+        )
+    }
+
+    /// Re-encrypt with a new password, fresh salt and nonce, keeping the
+    /// current KDF cost. Older files are upgraded to the current format.
+    pub fn reseal(&mut self, wallet_data: &WalletData, password: &str) -> Result<()> {
+        let kdf = self.crypto.kdf_params.cost();
+        self.reseal_with(wallet_data, password, &kdf)
+    }
+
+    /// Re-encrypt with a new password and KDF cost
+    pub fn reseal_with(
+        &mut self,
+        wallet_data: &WalletData,
+        password: &str,
+        kdf: &KdfCost,
+    ) -> Result<()> {
+        self.version = FormatVersion::CURRENT;
+        self.metadata.address = wallet_data.address.clone();
+        self.metadata.modified = chrono::Utc::now().to_rfc3339();
+        self.encrypt(wallet_data, password, kdf)
+    }
+
+    /// Upgrade an older file to the current format in memory, keeping the
+    /// password and KDF cost. Refuses files whose clear-text metadata does
+    /// not match the encrypted wallet. Returns whether anything changed.
+    pub fn migrate(&mut self, password: &str) -> Result<bool> {
+        if self.version == FormatVersion::CURRENT {
+            return Ok(false);
+        }
+
+        let wallet_data = self.unseal(password)?;
+        self.check_metadata(&wallet_data)?;
+        self.reseal(&wallet_data, password)?;
+        Ok(true)
+    }
+
+    /// Whether the header is covered by the AEAD tag (format 2.0.0+)
+    pub fn is_authenticated(&self) -> bool {
+        self.version.authenticates_header()
+    }
+
+    /// Compare the clear-text metadata with the decrypted wallet. Always
+    /// passes for authenticated files; catches edited 1.0.0 files.
+    pub fn check_metadata(&self, wallet_data: &WalletData) -> Result<()> {
+        if self.metadata.address != wallet_data.address {
+            return Err(Error::MetadataMismatch(format!(
+                "metadata address {} but encrypted wallet address {}",
+                self.metadata.address, wallet_data.address
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that the algorithms are supported, the KDF parameters are in
+    /// bounds and the base64 fields decode, without a password
+    pub fn check_encoding(&self) -> Result<()> {
+        self.crypto.decode(&self.encrypted_data).map(|_| ())
+    }
+
+    /// Read and parse a KeepBox file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let keepbox_json =
+            fs::read_to_string(path).map_err(|e| Error::io("Failed to read KeepBox file", e))?;
+        Self::from_json(&keepbox_json)
+    }
+
+    /// Parse a KeepBox of any supported format version. The version is
+    /// checked first, so files from a newer release fail with
+    /// [`Error::UnsupportedVersion`] rather than a field-level parse error.
+    pub fn from_json(json: &str) -> Result<Self> {
+        if let (_, Some(kind)) = probe(json)? {
+            return Err(Error::Unsupported(format!(
+                "KeepBox kind '{}' where a single-wallet KeepBox is expected",
+                kind
+            )));
+        }
+
+        serde_json::from_str(json)
+            .map_err(|e| Error::Encoding(format!("Failed to parse KeepBox: {}", e)))
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Encoding(format!("Failed to serialize KeepBox: {}", e)))
+    }
+
+    /// Create or replace `path` crash-safely (see
+    /// [`files::replace_private_file`]). The written file must parse and
+    /// decrypt with `password` to a wallet matching its metadata before it
+    /// replaces the original.
+    pub fn save_atomic(&self, path: impl AsRef<Path>, password: &str) -> Result<()> {
+        files::replace_private_file(path.as_ref(), self.to_json()?.as_bytes(), |written| {
+            let written = KeepBox::from_json(&String::from_utf8_lossy(written))?;
+            let wallet_data = written.unseal(password)?;
+            written.check_metadata(&wallet_data)?;
+            if wallet_data.address != self.metadata.address {
+                return Err(Error::MetadataMismatch(
+                    "written KeepBox holds a different wallet".to_string(),
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    /// Write the KeepBox to a new file with owner-only (0600) permissions.
+    /// Fails with an "already exists" error rather than overwriting; use
+    /// [`KeepBox::save_atomic`] to replace a KeepBox.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        files::create_private_file(path.as_ref(), self.to_json()?.as_bytes())
+    }
+
+    /// Encrypt under a fresh salt and nonce, authenticating the header as
+    /// it stands with those filled in
+    fn encrypt(&mut self, wallet_data: &WalletData, password: &str, kdf: &KdfCost) -> Result<()> {
+        let (salt, nonce) = self.crypto.refresh(kdf)?;
+        let aad = self.associated_data()?;
+        let ciphertext = encrypt_payload(wallet_data, password, &salt, &nonce, kdf, &aad)?;
+        self.encrypted_data = BASE64.encode(ciphertext);
+        Ok(())
+    }
+
+    /// AES-GCM associated data: empty for 1.0.0 files, the JSON-encoded
+    /// header (with `encrypted_data` excluded) from 2.0.0
+    fn associated_data(&self) -> Result<Vec<u8>> {
+        match self.version {
+            FormatVersion::V1_0_0 => Ok(Vec::new()),
+            FormatVersion::V2_0_0 | FormatVersion::V2_1_0 => {
+                serde_json::to_vec(&AuthenticatedHeader {
+                    version: self.version.as_str(),
+                    crypto: &self.crypto,
+                    metadata: &self.metadata,
+                })
+                .map_err(|e| Error::Encoding(format!("Failed to encode KeepBox header: {}", e)))
+            }
+        }
+    }
+}
+
+/// Read the version and kind (`None` for single-wallet files) of a KeepBox
+/// before parsing the rest, so files from a newer release fail with
+/// [`Error::UnsupportedVersion`] rather than a field-level parse error
+pub(crate) fn probe(json: &str) -> Result<(FormatVersion, Option<String>)> {
+    #[derive(Deserialize)]
+    struct Probe {
+        version: String,
+        #[serde(default)]
+        kind: Option<String>,
+    }
+
+    if json.starts_with("BNDLS") {
+        return Err(Error::Unsupported(
+            "binary BNDLS KeepBox layout (never released; files are JSON)".to_string(),
+        ));
+    }
+
+    let probe: Probe = serde_json::from_str(json)
+        .map_err(|e| Error::Encoding(format!("Failed to parse KeepBox: {}", e)))?;
+    Ok((probe.version.parse()?, probe.kind))
+}
+
+// ===== Encryption Functions =====
+
+fn derive_key_from_password(
+    password: &str,
+    salt: &[u8],
+    kdf: &KdfCost,
+) -> Result<Zeroizing<[u8; 32]>> {
+    kdf.validate()?;
+    let params = ParamsBuilder::new()
+        .m_cost(kdf.memory_cost)
+        .t_cost(kdf.time_cost)
+        .p_cost(kdf.parallelism)
+        .build()
+        .map_err(|e| Error::Crypto(format!("Failed to build Argon2 params: {}", e)))?;
+
+    let argon2 = Argon2::new(argon2::Algorithm::Argon2id, Version::V0x13, params);
+
+    // Derive 32-byte key
+    let mut key = Zeroizing::new([0u8; 32]);
+    argon2
+        .hash_password_into(password.as_bytes(), salt, key.as_mut())
+        .map_err(|e| Error::Crypto(format!("Failed to derive key: {}", e)))?;
+
+    Ok(key)
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).map_err(|e| Error::Entropy(e.to_string()))?;
+    Ok(bytes)
+}
+
+/// Serialize `payload` to JSON and encrypt it under a password-derived key
+pub(crate) fn encrypt_payload<T: Serialize>(
+    payload: &T,
+    password: &str,
+    salt: &[u8],
+    nonce: &[u8],
+    kdf: &KdfCost,
+    aad: &[u8],
+) -> Result<Vec<u8>> {
+    // Derive encryption key
+    let key = derive_key_from_password(password, salt, kdf)?;
+
+    let cipher = Aes256Gcm::new_from_slice(key.as_ref())
+        .map_err(|e| Error::Crypto(format!("Failed to create cipher: {}", e)))?;
+
+    // Serialize wallet data
+    let plaintext = Zeroizing::new(
+        serde_json::to_vec(payload)
+            .map_err(|e| Error::Encoding(format!("Failed to serialize wallet data: {}", e)))?,
+    );
+
+    cipher
+        .encrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: plaintext.as_slice(),
+                aad,
+            },
+        )
+        .map_err(|e| Error::Crypto(format!("Encryption failed: {}", e)))
+}
+
+/// Decrypt and deserialize a payload sealed by [`encrypt_payload`]
+pub(crate) fn decrypt_payload<T: DeserializeOwned>(
+    ciphertext: &[u8],
+    password: &str,
+    salt: &[u8],
+    nonce: &[u8],
+    kdf: &KdfCost,
+    aad: &[u8],
+) -> Result<T> {
+    // Derive decryption key
+    let key = derive_key_from_password(password, salt, kdf)?;
+
+    let cipher = Aes256Gcm::new_from_slice(key.as_ref())
+        .map_err(|e| Error::Crypto(format!("Failed to create cipher: {}", e)))?;
+
+    let plaintext = Zeroizing::new(
+        cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| Error::DecryptionFailed)?,
+    );
+
+    serde_json::from_slice(&plaintext)
+        .map_err(|e| Error::Encoding(format!("Failed to deserialize wallet data: {}", e)))
+}
+
+// ===== Password Functions =====
+
+/// Enforce the KeepBox password policy
+pub fn validate_password_strength(password: &str) -> Result<()> {
+    if password.len() < 12 {
+        return Err(Error::WeakPassword(
+            "Password must be at least 12 characters long".to_string(),
+        ));
+    }
+
+    let has_lowercase = password.chars().any(|c| c.is_lowercase());
+    let has_uppercase = password.chars().any(|c| c.is_uppercase());
+    let has_digit = password.chars().any(|c| c.is_numeric());
+    let has_special = password.chars().any(|c| !c.is_alphanumeric());
+
+    let strength_score =
+        has_lowercase as u8 + has_uppercase as u8 + has_digit as u8 + has_special as u8;
+
+    if strength_score < 3 {
+        return Err(Error::WeakPassword(
+            "Password must contain at least 3 of: lowercase, uppercase, digits, special characters"
+                .to_string(),
+        ));
+    }
+
+    // Check for common weak passwords
+    let weak_passwords = ["password123", "qwerty123456", "admin123456", "123456789012"];
+    if weak_passwords.contains(&password.to_lowercase().as_str()) {
+        return Err(Error::WeakPassword(
+            "Password is too common, please choose a stronger password".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWORD: &str = "Test-Password-2025";
+
+    fn test_wallet() -> WalletData {
+        WalletData::from_mnemonic(
+            &Mnemonic::generate().unwrap(),
+            "",
+            KeyType::Ed25519,
+            &Derivation::Legacy,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_seal_unseal_roundtrip() {
+        let wallet = test_wallet();
+        let keepbox = KeepBox::seal(&wallet, PASSWORD, Some("miner".into())).unwrap();
+        assert_eq!(keepbox.metadata.address, wallet.address);
+
+        let reloaded = KeepBox::from_json(&keepbox.to_json().unwrap()).unwrap();
+        let opened = reloaded.unseal(PASSWORD).unwrap();
+        assert_eq!(opened.mnemonic, wallet.mnemonic);
+        opened.verify("").unwrap();
+
+        assert!(matches!(
+            reloaded.unseal("Wrong-Password-2025"),
+            Err(Error::DecryptionFailed)
+        ));
+    }
+
+    #[test]
+    fn test_header_is_authenticated() {
+        let keepbox = KeepBox::seal(&test_wallet(), PASSWORD, Some("cold".into())).unwrap();
+        assert_eq!(keepbox.version, FormatVersion::CURRENT);
+        assert!(keepbox.is_authenticated());
+
+        let tampered: [fn(&mut KeepBox); 4] = [
+            |k| k.metadata.address = "ab".repeat(32),
+            |k| k.metadata.label = Some("hot".into()),
+            |k| k.crypto.kdf_params.memory_cost *= 2,
+            |k| k.version = FormatVersion::V1_0_0,
+        ];
+        for tamper in tampered {
+            let mut copy = KeepBox::from_json(&keepbox.to_json().unwrap()).unwrap();
+            tamper(&mut copy);
+            assert!(matches!(
+                copy.unseal(PASSWORD),
+                Err(Error::DecryptionFailed)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_legacy_1_0_0_file() {
+        // Written by `boundless-keepbox import` built from the 1.0.0 baseline
+        // (abandon ... art, PASSWORD, its fixed 64 MiB / 3 / 4 Argon2id cost)
+        let legacy = include_str!("../testdata/keepbox-1.0.0.keepbox");
+        let mut keepbox = KeepBox::from_json(legacy).unwrap();
+        assert!(!keepbox.is_authenticated());
+
+        let wallet = keepbox.unseal(PASSWORD).unwrap();
+        keepbox.check_metadata(&wallet).unwrap();
+
+        // Unauthenticated metadata can be edited without detection by the
+        // AEAD, but not past the comparison with the decrypted wallet
+        keepbox.metadata.address = "ab".repeat(32);
+        let wallet = keepbox.unseal(PASSWORD).unwrap();
+        assert!(matches!(
+            keepbox.check_metadata(&wallet),
+            Err(Error::MetadataMismatch(_))
+        ));
+
+        // Re-encrypting upgrades the format and restores the address
+        keepbox.reseal(&wallet, PASSWORD).unwrap();
+        assert_eq!(keepbox.version, FormatVersion::CURRENT);
+        assert_eq!(keepbox.metadata.address, wallet.address);
+        assert_eq!(keepbox.crypto.kdf_params.memory_cost, 65536);
+        keepbox.unseal(PASSWORD).unwrap();
+    }
+
+    #[test]
+    fn test_format_versions() {
+        assert_eq!(KEEPBOX_VERSION, "2.0.0");
+        for version in FormatVersion::SUPPORTED {
+            assert_eq!(version.as_str().parse::<FormatVersion>().unwrap(), version);
+        }
+
+        let json = KeepBox::seal(&test_wallet(), PASSWORD, None)
+            .unwrap()
+            .to_json()
+            .unwrap();
+        assert!(json.contains("\"version\": \"2.0.0\""));
+
+        // A future release may change any other field too; the version is
+        // reported before those are looked at
+        let future = json
+            .replace("\"2.0.0\"", "\"3.0.0\"")
+            .replace("\"crypto\"", "\"crypto_v3\"");
+        match KeepBox::from_json(&future) {
+            Err(Error::UnsupportedVersion { found, supported }) => {
+                assert_eq!(found, "3.0.0");
+                assert_eq!(supported, "1.0.0, 2.0.0, 2.1.0");
+            }
+            other => panic!("unexpected {:?}", other.map(|_| ())),
+        }
+
+        assert!(matches!(
+            KeepBox::from_json("BNDLS\x01..."),
+            Err(Error::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_migrate_legacy_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("legacy.keepbox");
+        fs::write(&path, include_str!("../testdata/keepbox-1.0.0.keepbox")).unwrap();
+
+        let mut keepbox = KeepBox::load(&path).unwrap();
+        assert_eq!(keepbox.version, FormatVersion::V1_0_0);
+        let created = keepbox.metadata.created.clone();
+
+        assert!(keepbox.migrate(PASSWORD).unwrap());
+        keepbox.save_atomic(&path, PASSWORD).unwrap();
+
+        let migrated = KeepBox::load(&path).unwrap();
+        assert_eq!(migrated.version, FormatVersion::CURRENT);
+        assert_eq!(migrated.metadata.created, created);
+        assert_eq!(migrated.metadata.label.as_deref(), Some("legacy"));
+        migrated.unseal(PASSWORD).unwrap().verify("").unwrap();
+        assert!(!KeepBox::load(&path).unwrap().migrate(PASSWORD).unwrap());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_migrate_refuses_edited_metadata() {
+        let mut keepbox =
+            KeepBox::from_json(include_str!("../testdata/keepbox-1.0.0.keepbox")).unwrap();
+        keepbox.metadata.address = "ab".repeat(32);
+        assert!(matches!(
+            keepbox.migrate(PASSWORD),
+            Err(Error::MetadataMismatch(_))
+        ));
+        assert_eq!(keepbox.version, FormatVersion::V1_0_0);
+    }
+
+    #[test]
+    fn test_save_atomic_verifies_before_replacing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.keepbox");
+        let kdf = KdfCost {
+            memory_cost: 8 * 1024,
+            time_cost: 1,
+            parallelism: 1,
+        };
+        let mut keepbox = KeepBox::seal_with(&test_wallet(), PASSWORD, None, &kdf).unwrap();
+        keepbox.save(&path).unwrap();
+        let original = fs::read(&path).unwrap();
+
+        keepbox
+            .reseal(&test_wallet(), "Other-Password-2025")
+            .unwrap();
+        assert!(matches!(
+            keepbox.save_atomic(&path, PASSWORD),
+            Err(Error::Crypto(_))
+        ));
+        assert_eq!(fs::read(&path).unwrap(), original);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        keepbox.save_atomic(&path, "Other-Password-2025").unwrap();
+        let replaced = KeepBox::load(&path).unwrap();
+        replaced.unseal("Other-Password-2025").unwrap();
+    }
+
+    #[test]
+    fn test_unseal_uses_file_kdf_params() {
+        let wallet = test_wallet();
+        let kdf = KdfCost {
+            memory_cost: 8 * 1024,
+            time_cost: 1,
+            parallelism: 1,
+        };
+        let mut keepbox = KeepBox::seal_with(&wallet, PASSWORD, None, &kdf).unwrap();
+        assert_eq!(keepbox.crypto.kdf_params.cost(), kdf);
+
+        let reloaded = KeepBox::from_json(&keepbox.to_json().unwrap()).unwrap();
+        assert_eq!(reloaded.unseal(PASSWORD).unwrap().address, wallet.address);
+
+        // Tampered parameters derive a different key
+        keepbox.crypto.kdf_params.time_cost = 2;
+        assert!(matches!(
+            keepbox.unseal(PASSWORD),
+            Err(Error::DecryptionFailed)
+        ));
+    }
+
+    #[test]
+    fn test_out_of_bounds_kdf_params_rejected() {
+        let mut keepbox = KeepBox::seal(&test_wallet(), PASSWORD, None).unwrap();
+        keepbox.crypto.kdf_params.memory_cost = u32::MAX;
+        assert!(matches!(
+            keepbox.unseal(PASSWORD),
+            Err(Error::InvalidKdfParams(_))
+        ));
+        assert!(keepbox.check_encoding().is_err());
+
+        let zero_time = KdfCost {
+            time_cost: 0,
+            ..KdfCost::default()
+        };
+        assert!(KeepBox::seal_with(&test_wallet(), PASSWORD, None, &zero_time).is_err());
+    }
+
+    #[test]
+    fn test_kdf_presets() {
+        assert_eq!(KdfCost::default(), KdfPreset::Standard.cost());
+        assert_eq!(KdfCost::default().memory_cost, 65536);
+        for preset in [
+            KdfPreset::Interactive,
+            KdfPreset::Standard,
+            KdfPreset::Paranoid,
+        ] {
+            preset.cost().validate().unwrap();
+        }
+        assert_eq!(
+            "Paranoid".parse::<KdfPreset>().unwrap(),
+            KdfPreset::Paranoid
+        );
+        assert!("extreme".parse::<KdfPreset>().is_err());
+    }
+
+    #[test]
+    fn test_passphrase_required_to_rederive() {
+        let mnemonic = Mnemonic::generate().unwrap();
+        let wallet = WalletData::from_mnemonic(
+            &mnemonic,
+            "hunter2 battery",
+            KeyType::Ed25519,
+            &Derivation::account(0).unwrap(),
+        )
+        .unwrap();
+        assert!(wallet.has_passphrase);
+
+        wallet.verify("hunter2 battery").unwrap();
+        assert!(matches!(
+            wallet.verify(""),
+            Err(Error::AddressMismatch { .. })
+        ));
+
+        let output = wallet.to_output("hunter2 battery", true).unwrap();
+        assert_eq!(output.address, wallet.address);
+        assert!(output.private_key.is_some());
+        assert!(wallet.to_output("", false).is_err());
+    }
+
+    #[cfg(feature = "pqc")]
+    #[test]
+    fn test_pqc_keepbox_roundtrip() {
+        let mnemonic = Mnemonic::generate().unwrap();
+        for key_type in [KeyType::MlDsa44, KeyType::Hybrid] {
+            let wallet =
+                WalletData::from_mnemonic(&mnemonic, "", key_type, &Derivation::Legacy).unwrap();
+
+            let keepbox = KeepBox::seal(&wallet, PASSWORD, None).unwrap();
+            let opened = keepbox.unseal(PASSWORD).unwrap();
+            assert_eq!(opened.key_type, key_type);
+            assert_eq!(opened.public_key, wallet.public_key);
+            assert_eq!(opened.keys, wallet.keys);
+            opened.verify("").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_wallet_data_without_passphrase_field() {
+        // Wallets encrypted before passphrase support omit the field
+        let json = r#"{"mnemonic":"m","public_key":"p","address":"a","key_type":"Ed25519"}"#;
+        let wallet: WalletData = serde_json::from_str(json).unwrap();
+        assert!(!wallet.has_passphrase);
+        assert_eq!(wallet.derivation, Derivation::Legacy);
+        assert!(wallet.keys.is_empty());
+        assert_eq!(wallet.language, Language::English);
+    }
+
+    #[test]
+    fn test_language_recorded() {
+        let mnemonic = Mnemonic::generate_in(Language::Spanish, 12).unwrap();
+        let wallet =
+            WalletData::from_mnemonic(&mnemonic, "", KeyType::Ed25519, &Derivation::Legacy)
+                .unwrap();
+        assert_eq!(wallet.language, Language::Spanish);
+
+        let opened = KeepBox::seal(&wallet, PASSWORD, None)
+            .unwrap()
+            .unseal(PASSWORD)
+            .unwrap();
+        assert_eq!(opened.language, Language::Spanish);
+        opened.verify("").unwrap();
+        assert_eq!(
+            opened.to_output("", false).unwrap().language,
+            Language::Spanish
+        );
+    }
+
+    #[test]
+    fn test_password_strength() {
+        assert!(validate_password_strength("short").is_err());
+        assert!(validate_password_strength("alllowercaseletters").is_err());
+        assert!(validate_password_strength("Password123").is_err());
+        assert!(validate_password_strength(PASSWORD).is_ok());
+    }
+}
@@ -0,0 +1,643 @@
+//! Key derivation from BIP39 seeds, signing and verification
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use ed25519_dalek::{Signature, Signer as _, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::address::Address;
+use crate::derivation::Derivation;
+use crate::error::{Error, Result};
+use crate::mnemonic::Seed;
+#[cfg(feature = "pqc")]
+use crate::pqc::MlDsa44Keypair;
+
+/// Version byte of the hybrid composite public key encoding
+pub const HYBRID_ENCODING_V1: u8 = 0x01;
+
+const ED25519_PUBLIC_KEY_LEN: usize = 32;
+const ED25519_SIGNATURE_LEN: usize = 64;
+const ML_DSA_44_PUBLIC_KEY_LEN: usize = 1312;
+
+/// Signature scheme of a wallet key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum KeyType {
+    /// Classical Ed25519 (32-byte public key)
+    #[default]
+    #[serde(rename = "Ed25519")]
+    Ed25519,
+
+    /// Post-quantum ML-DSA-44 / Dilithium2 (1312-byte public key).
+    /// Key generation requires the `pqc` feature.
+    #[serde(rename = "ML-DSA-44")]
+    MlDsa44,
+
+    /// Ed25519 and ML-DSA-44 keys from the same seed, see [`hybrid`]
+    #[serde(rename = "Ed25519+ML-DSA-44")]
+    Hybrid,
+}
+
+impl KeyType {
+    /// Name as stored in wallet files
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyType::Ed25519 => "Ed25519",
+            KeyType::MlDsa44 => "ML-DSA-44",
+            KeyType::Hybrid => "Ed25519+ML-DSA-44",
+        }
+    }
+
+    /// Infer the key type from an encoded public key's length / version
+    pub fn from_public_key(public_key: &[u8]) -> Result<Self> {
+        const HYBRID_PUBLIC_KEY_LEN: usize = 1 + ED25519_PUBLIC_KEY_LEN + ML_DSA_44_PUBLIC_KEY_LEN;
+
+        match public_key.len() {
+            ED25519_PUBLIC_KEY_LEN => Ok(KeyType::Ed25519),
+            ML_DSA_44_PUBLIC_KEY_LEN => Ok(KeyType::MlDsa44),
+            HYBRID_PUBLIC_KEY_LEN if public_key[0] == HYBRID_ENCODING_V1 => Ok(KeyType::Hybrid),
+            _ => Err(Error::InvalidPublicKey(format!(
+                "unrecognized public key length {}",
+                public_key.len()
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for KeyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for KeyType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ed25519" => Ok(KeyType::Ed25519),
+            "ml-dsa-44" | "mldsa44" | "dilithium2" => Ok(KeyType::MlDsa44),
+            "hybrid" | "ed25519+ml-dsa-44" => Ok(KeyType::Hybrid),
+            other => Err(Error::Unsupported(format!(
+                "unknown key type '{}' (expected ed25519, ml-dsa-44 or hybrid)",
+                other
+            ))),
+        }
+    }
+}
+
+/// One component public key of a wallet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKeyInfo {
+    pub key_type: KeyType,
+
+    /// Public key (hex-encoded)
+    pub public_key: String,
+}
+
+/// Hybrid Ed25519 + ML-DSA-44 encodings
+///
+/// Composite public key (hashed with SHA3-256 for the address):
+///
+/// ```text
+/// 0x01 || ed25519_public_key (32) || ml_dsa_44_public_key (1312)   = 1345 bytes
+/// ```
+///
+/// Hybrid signature over a message `m`:
+///
+/// ```text
+/// ed25519_sign(m) (64) || ml_dsa_44_sign(m, ctx = "") (2420)        = 2484 bytes
+/// ```
+///
+/// A hybrid signature is valid only if BOTH halves verify.
+pub mod hybrid {
+    use super::*;
+
+    /// Build the composite public key encoding
+    pub fn encode_public_key(ed25519: &[u8], ml_dsa_44: &[u8]) -> Vec<u8> {
+        let mut encoded = Vec::with_capacity(1 + ed25519.len() + ml_dsa_44.len());
+        encoded.push(HYBRID_ENCODING_V1);
+        encoded.extend_from_slice(ed25519);
+        encoded.extend_from_slice(ml_dsa_44);
+        encoded
+    }
+
+    /// Split a composite public key into (Ed25519, ML-DSA-44) parts
+    pub fn decode_public_key(encoded: &[u8]) -> Result<(&[u8], &[u8])> {
+        match encoded.split_first() {
+            Some((&HYBRID_ENCODING_V1, rest)) if rest.len() > ED25519_PUBLIC_KEY_LEN => {
+                Ok(rest.split_at(ED25519_PUBLIC_KEY_LEN))
+            }
+            Some((&HYBRID_ENCODING_V1, _)) => Err(Error::InvalidPublicKey(
+                "hybrid public key too short".to_string(),
+            )),
+            _ => Err(Error::InvalidPublicKey(
+                "unknown hybrid public key encoding version".to_string(),
+            )),
+        }
+    }
+
+    /// Split a hybrid signature into (Ed25519, ML-DSA-44) parts
+    pub fn split_signature(signature: &[u8]) -> Result<(&[u8], &[u8])> {
+        if signature.len() <= ED25519_SIGNATURE_LEN {
+            return Err(Error::InvalidSignature(
+                "hybrid signature too short".to_string(),
+            ));
+        }
+        Ok(signature.split_at(ED25519_SIGNATURE_LEN))
+    }
+}
+
+/// Something that signs for one address: a [`Keypair`] in memory, or a key
+/// held by the key agent
+pub trait Signer {
+    fn key_type(&self) -> KeyType;
+
+    /// Encoded public key, see [`Keypair::public_key_bytes`]
+    fn public_key_bytes(&self) -> Vec<u8>;
+
+    /// Sign a message; hybrid signatures are the concatenated pair
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>>;
+
+    fn address(&self) -> Address {
+        Address::from_public_key(&self.public_key_bytes())
+    }
+}
+
+/// Wallet keypair; secret key material is zeroized on drop
+#[derive(Clone)]
+pub struct Keypair(Inner);
+
+// ML-DSA keys are several KB, so those variants are boxed; the remaining
+// size difference is just the Ed25519 key's expanded form
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+enum Inner {
+    Ed25519(SigningKey),
+    #[cfg(feature = "pqc")]
+    MlDsa44(Box<MlDsa44Keypair>),
+    #[cfg(feature = "pqc")]
+    Hybrid(SigningKey, Box<MlDsa44Keypair>),
+}
+
+impl Keypair {
+    /// Derive an Ed25519 keypair from a BIP39 seed using the legacy scheme
+    /// Uses first 32 bytes of seed as private key material
+    pub fn from_seed(seed: &Seed) -> Self {
+        let secret_bytes = Derivation::Legacy.derive_ed25519(seed.as_bytes());
+        Keypair(Inner::Ed25519(SigningKey::from_bytes(&secret_bytes)))
+    }
+
+    /// Derive a keypair of `key_type` from a BIP39 seed along `derivation`
+    pub fn derive(seed: &Seed, key_type: KeyType, derivation: &Derivation) -> Result<Self> {
+        let ed25519 = || SigningKey::from_bytes(&derivation.derive_ed25519(seed.as_bytes()));
+        #[cfg(feature = "pqc")]
+        let ml_dsa_44 = || {
+            Box::new(MlDsa44Keypair::from_seed(
+                &derivation.derive_ml_dsa_44(seed.as_bytes()),
+            ))
+        };
+
+        match key_type {
+            KeyType::Ed25519 => Ok(Keypair(Inner::Ed25519(ed25519()))),
+            #[cfg(feature = "pqc")]
+            KeyType::MlDsa44 => Ok(Keypair(Inner::MlDsa44(ml_dsa_44()))),
+            #[cfg(feature = "pqc")]
+            KeyType::Hybrid => Ok(Keypair(Inner::Hybrid(ed25519(), ml_dsa_44()))),
+            #[cfg(not(feature = "pqc"))]
+            KeyType::MlDsa44 | KeyType::Hybrid => Err(pqc_unsupported(key_type)),
+        }
+    }
+
+    /// Key type identifier as stored in wallet files
+    pub fn key_type(&self) -> KeyType {
+        match &self.0 {
+            Inner::Ed25519(_) => KeyType::Ed25519,
+            #[cfg(feature = "pqc")]
+            Inner::MlDsa44(_) => KeyType::MlDsa44,
+            #[cfg(feature = "pqc")]
+            Inner::Hybrid(..) => KeyType::Hybrid,
+        }
+    }
+
+    /// Encoded public key the address is derived from: 32 bytes for
+    /// Ed25519, 1312 for ML-DSA-44, the composite encoding for hybrid
+    pub fn public_key_bytes(&self) -> Vec<u8> {
+        match &self.0 {
+            Inner::Ed25519(key) => key.verifying_key().to_bytes().to_vec(),
+            #[cfg(feature = "pqc")]
+            Inner::MlDsa44(keys) => keys.public_key_bytes(),
+            #[cfg(feature = "pqc")]
+            Inner::Hybrid(ed, pq) => {
+                hybrid::encode_public_key(ed.verifying_key().as_bytes(), &pq.public_key_bytes())
+            }
+        }
+    }
+
+    /// Individual public keys (one entry, or two for hybrid)
+    pub fn public_keys(&self) -> Vec<PublicKeyInfo> {
+        match &self.0 {
+            #[cfg(feature = "pqc")]
+            Inner::Hybrid(ed, pq) => vec![
+                PublicKeyInfo {
+                    key_type: KeyType::Ed25519,
+                    public_key: hex::encode(ed.verifying_key().as_bytes()),
+                },
+                PublicKeyInfo {
+                    key_type: KeyType::MlDsa44,
+                    public_key: hex::encode(pq.public_key_bytes()),
+                },
+            ],
+            _ => vec![PublicKeyInfo {
+                key_type: self.key_type(),
+                public_key: hex::encode(self.public_key_bytes()),
+            }],
+        }
+    }
+
+    /// Private key bytes, wiped when the returned buffer is dropped.
+    /// Hybrid keys are the Ed25519 key followed by the ML-DSA-44 key.
+    pub fn secret_bytes(&self) -> Zeroizing<Vec<u8>> {
+        match &self.0 {
+            Inner::Ed25519(key) => Zeroizing::new(key.to_bytes().to_vec()),
+            #[cfg(feature = "pqc")]
+            Inner::MlDsa44(keys) => keys.secret_bytes(),
+            #[cfg(feature = "pqc")]
+            Inner::Hybrid(ed, pq) => {
+                let mut bytes = Zeroizing::new(ed.to_bytes().to_vec());
+                bytes.extend_from_slice(&pq.secret_bytes());
+                bytes
+            }
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        Address::from_public_key(&self.public_key_bytes())
+    }
+
+    /// Memory holding the secret key, for the agent to `mlock`: the keypair
+    /// itself and, for ML-DSA-44, its boxed key
+    #[cfg(unix)]
+    pub(crate) fn memory_regions(&self) -> Vec<(*const u8, usize)> {
+        #[allow(unused_mut)]
+        let mut regions = vec![(
+            self as *const Self as *const u8,
+            std::mem::size_of::<Self>(),
+        )];
+        match &self.0 {
+            Inner::Ed25519(_) => {}
+            #[cfg(feature = "pqc")]
+            Inner::MlDsa44(pq) | Inner::Hybrid(_, pq) => regions.push((
+                &**pq as *const MlDsa44Keypair as *const u8,
+                std::mem::size_of::<MlDsa44Keypair>(),
+            )),
+        }
+        regions
+    }
+
+    /// Sign a message; hybrid signatures are the concatenated pair
+    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        match &self.0 {
+            Inner::Ed25519(key) => Ok(key.sign(message).to_bytes().to_vec()),
+            #[cfg(feature = "pqc")]
+            Inner::MlDsa44(keys) => keys.sign(message),
+            #[cfg(feature = "pqc")]
+            Inner::Hybrid(ed, pq) => {
+                let mut signature = ed.sign(message).to_bytes().to_vec();
+                signature.extend_from_slice(&pq.sign(message)?);
+                Ok(signature)
+            }
+        }
+    }
+}
+
+impl Signer for Keypair {
+    fn key_type(&self) -> KeyType {
+        Keypair::key_type(self)
+    }
+
+    fn public_key_bytes(&self) -> Vec<u8> {
+        Keypair::public_key_bytes(self)
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        Keypair::sign(self, message)
+    }
+}
+
+impl fmt::Debug for Keypair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keypair")
+            .field("key_type", &self.key_type())
+            .field("address", &self.address().to_hex())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Public details of an unlocked key held by the key agent or remote signer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeldKey {
+    pub address: String,
+    pub key_type: KeyType,
+    /// Encoded public key (hex), see [`Keypair::public_key_bytes`]
+    pub public_key: String,
+    /// KeepBox or vault entry label
+    pub label: Option<String>,
+    /// KeepBox file the key was unlocked from
+    pub keepbox: PathBuf,
+}
+
+/// Unlocked keypairs of a long-running process, one per address. Each
+/// keypair is boxed so growing or shifting the list never leaves a stale,
+/// unwiped copy of secret key material behind.
+#[derive(Default)]
+pub struct KeyRing {
+    keys: Vec<(HeldKey, Box<Keypair>)>,
+    #[cfg(unix)]
+    locked: bool,
+}
+
+impl KeyRing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold `keypair`; each address can be added once. After
+    /// [`KeyRing::lock_memory`] the new key is locked as well.
+    pub fn add(
+        &mut self,
+        keypair: Keypair,
+        label: Option<String>,
+        keepbox: &Path,
+    ) -> Result<&HeldKey> {
+        let address = keypair.address().to_hex();
+        if self.keys.iter().any(|(key, _)| key.address == address) {
+            return Err(Error::DuplicateEntry(format!("with address {}", address)));
+        }
+        let key = HeldKey {
+            address,
+            key_type: keypair.key_type(),
+            public_key: hex::encode(keypair.public_key_bytes()),
+            label,
+            keepbox: keepbox.to_path_buf(),
+        };
+        let keypair = Box::new(keypair);
+        #[cfg(unix)]
+        if self.locked {
+            crate::agent::lock_keys([&*keypair])
+                .map_err(|e| Error::io("Failed to lock key memory", e))?;
+        }
+        self.keys.push((key, keypair));
+        Ok(&self.keys[self.keys.len() - 1].0)
+    }
+
+    /// The key for `address` (any case)
+    pub fn get(&self, address: &str) -> Option<(&HeldKey, &Keypair)> {
+        self.position(address)
+            .map(|index| (&self.keys[index].0, &*self.keys[index].1))
+    }
+
+    /// Wipe the key for `address`; `false` if there is none
+    pub fn remove(&mut self, address: &str) -> bool {
+        self.position(address)
+            .map(|index| self.keys.remove(index))
+            .is_some()
+    }
+
+    /// Wipe every key
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    pub fn list(&self) -> Vec<HeldKey> {
+        self.iter().cloned().collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &HeldKey> {
+        self.keys.iter().map(|(key, _)| key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Keep key memory out of swap and core dumps, including keys added
+    /// later. Locks do not survive `fork`; call again in the child.
+    #[cfg(unix)]
+    pub fn lock_memory(&mut self) -> std::io::Result<()> {
+        self.locked = true;
+        crate::agent::lock_keys(self.keys.iter().map(|(_, keypair)| &**keypair))
+    }
+
+    fn position(&self, address: &str) -> Option<usize> {
+        self.keys
+            .iter()
+            .position(|(key, _)| key.address.eq_ignore_ascii_case(address.trim()))
+    }
+}
+
+/// Verify `signature` over `message` for an encoded public key of `key_type`
+/// (as returned by [`Keypair::public_key_bytes`])
+pub fn verify_signature(
+    key_type: KeyType,
+    public_key: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Result<()> {
+    match key_type {
+        KeyType::Ed25519 => verify_ed25519(public_key, message, signature),
+        #[cfg(feature = "pqc")]
+        KeyType::MlDsa44 => crate::pqc::verify(public_key, message, signature),
+        #[cfg(feature = "pqc")]
+        KeyType::Hybrid => {
+            let (ed_key, pq_key) = hybrid::decode_public_key(public_key)?;
+            let (ed_sig, pq_sig) = hybrid::split_signature(signature)?;
+            verify_ed25519(ed_key, message, ed_sig)?;
+            crate::pqc::verify(pq_key, message, pq_sig)
+        }
+        #[cfg(not(feature = "pqc"))]
+        KeyType::MlDsa44 | KeyType::Hybrid => Err(pqc_unsupported(key_type)),
+    }
+}
+
+fn verify_ed25519(public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<()> {
+    let public_key: [u8; ED25519_PUBLIC_KEY_LEN] = public_key.try_into().map_err(|_| {
+        Error::InvalidPublicKey(format!(
+            "Ed25519 public key must be 32 bytes, got {}",
+            public_key.len()
+        ))
+    })?;
+    let verifying_key = VerifyingKey::from_bytes(&public_key)
+        .map_err(|e| Error::InvalidPublicKey(e.to_string()))?;
+    let signature =
+        Signature::from_slice(signature).map_err(|e| Error::InvalidSignature(e.to_string()))?;
+    verifying_key
+        .verify(message, &signature)
+        .map_err(|_| Error::InvalidSignature("Ed25519 signature does not verify".to_string()))
+}
+
+#[cfg(not(feature = "pqc"))]
+fn pqc_unsupported(key_type: KeyType) -> Error {
+    Error::Unsupported(format!(
+        "{} keys require building with `--features pqc`",
+        key_type
+    ))
+}
+
+/// Wallet shared by the unit tests
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::Keypair;
+    use crate::mnemonic::Mnemonic;
+
+    /// The 24-word BIP39 test vector: 23 times "abandon", then "art"
+    pub(crate) const ABANDON_ART: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+
+    /// Address of [`abandon_keypair`]
+    pub(crate) const ABANDON_ADDRESS: &str =
+        "10e8a4f849828a2226294c24b05db8a151563f91ec3fafdc46aaf6df85c82b22";
+
+    /// Legacy Ed25519 keypair of [`ABANDON_ART`] without a passphrase
+    pub(crate) fn abandon_keypair() -> Keypair {
+        Keypair::from_seed(&Mnemonic::parse(ABANDON_ART).unwrap().to_seed(""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::derive_address;
+    use crate::mnemonic::Mnemonic;
+
+    #[test]
+    fn test_keypair_deterministic() {
+        let mnemonic = Mnemonic::generate().unwrap();
+        let a = Keypair::from_seed(&mnemonic.to_seed(""));
+        let b = Keypair::from_seed(&mnemonic.to_seed(""));
+
+        assert_eq!(a.public_key_bytes(), b.public_key_bytes());
+        assert_eq!(a.address().to_hex(), derive_address(&a.public_key_bytes()));
+    }
+
+    #[test]
+    fn test_accounts_yield_distinct_addresses() {
+        let seed = Mnemonic::generate().unwrap().to_seed("");
+        let derive = |d: &Derivation| Keypair::derive(&seed, KeyType::Ed25519, d).unwrap();
+        let legacy = derive(&Derivation::Legacy);
+        let account0 = derive(&Derivation::account(0).unwrap());
+        let account1 = derive(&Derivation::account(1).unwrap());
+
+        assert_eq!(legacy.address(), Keypair::from_seed(&seed).address());
+        assert_ne!(legacy.address(), account0.address());
+        assert_ne!(account0.address(), account1.address());
+    }
+
+    #[test]
+    fn test_key_type_names() {
+        assert_eq!("ml-dsa-44".parse::<KeyType>().unwrap(), KeyType::MlDsa44);
+        assert_eq!("Ed25519".parse::<KeyType>().unwrap(), KeyType::Ed25519);
+        assert_eq!("hybrid".parse::<KeyType>().unwrap(), KeyType::Hybrid);
+        assert!("rsa".parse::<KeyType>().is_err());
+        assert_eq!(
+            KeyType::from_public_key(&[0; 32]).unwrap(),
+            KeyType::Ed25519
+        );
+        assert_eq!(
+            KeyType::from_public_key(&[0; 1312]).unwrap(),
+            KeyType::MlDsa44
+        );
+        let mut hybrid = vec![HYBRID_ENCODING_V1];
+        hybrid.resize(1345, 0);
+        assert_eq!(KeyType::from_public_key(&hybrid).unwrap(), KeyType::Hybrid);
+        assert!(KeyType::from_public_key(&[0; 33]).is_err());
+        assert_eq!(
+            serde_json::to_string(&KeyType::Hybrid).unwrap(),
+            "\"Ed25519+ML-DSA-44\""
+        );
+    }
+
+    #[test]
+    fn test_ed25519_sign_verify() {
+        let keypair = Keypair::from_seed(&Mnemonic::generate().unwrap().to_seed(""));
+        let public_key = keypair.public_key_bytes();
+        let signature = keypair.sign(b"boundless").unwrap();
+
+        verify_signature(KeyType::Ed25519, &public_key, b"boundless", &signature).unwrap();
+        assert!(matches!(
+            verify_signature(KeyType::Ed25519, &public_key, b"tampered", &signature),
+            Err(Error::InvalidSignature(_))
+        ));
+    }
+
+    #[cfg(feature = "pqc")]
+    #[test]
+    fn test_ml_dsa_44_deterministic() {
+        let seed = Mnemonic::generate().unwrap().to_seed("");
+        let a = Keypair::derive(&seed, KeyType::MlDsa44, &Derivation::Legacy).unwrap();
+        let b = Keypair::derive(&seed, KeyType::MlDsa44, &Derivation::Legacy).unwrap();
+
+        assert_eq!(a.key_type(), KeyType::MlDsa44);
+        assert_eq!(a.public_key_bytes().len(), 1312);
+        assert_eq!(a.public_key_bytes(), b.public_key_bytes());
+        assert_eq!(a.address().to_hex(), derive_address(&a.public_key_bytes()));
+        assert_ne!(a.address(), Keypair::from_seed(&seed).address());
+    }
+
+    #[cfg(feature = "pqc")]
+    #[test]
+    fn test_hybrid_composite_key() {
+        let seed = Mnemonic::generate().unwrap().to_seed("");
+        let derivation = Derivation::account(0).unwrap();
+        let hybrid = Keypair::derive(&seed, KeyType::Hybrid, &derivation).unwrap();
+        let ed = Keypair::derive(&seed, KeyType::Ed25519, &derivation).unwrap();
+        let pq = Keypair::derive(&seed, KeyType::MlDsa44, &derivation).unwrap();
+
+        let encoded = hybrid.public_key_bytes();
+        assert_eq!(encoded.len(), 1 + 32 + 1312);
+        assert_eq!(
+            encoded,
+            hybrid::encode_public_key(&ed.public_key_bytes(), &pq.public_key_bytes())
+        );
+        assert_eq!(hybrid.address().to_hex(), derive_address(&encoded));
+
+        let components = hybrid.public_keys();
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0].public_key, hex::encode(ed.public_key_bytes()));
+        assert_eq!(components[1].key_type, KeyType::MlDsa44);
+    }
+
+    #[cfg(feature = "pqc")]
+    #[test]
+    fn test_hybrid_signature_requires_both_halves() {
+        let seed = Mnemonic::generate().unwrap().to_seed("");
+        let keypair = Keypair::derive(&seed, KeyType::Hybrid, &Derivation::Legacy).unwrap();
+        let public_key = keypair.public_key_bytes();
+        let signature = keypair.sign(b"hybrid").unwrap();
+        assert_eq!(signature.len(), 64 + 2420);
+
+        verify_signature(KeyType::Hybrid, &public_key, b"hybrid", &signature).unwrap();
+
+        // Corrupt only the Ed25519 half, then only the ML-DSA half
+        for index in [0, 64 + 10] {
+            let mut bad = signature.clone();
+            bad[index] ^= 0xff;
+            assert!(verify_signature(KeyType::Hybrid, &public_key, b"hybrid", &bad).is_err());
+        }
+    }
+
+    #[cfg(not(feature = "pqc"))]
+    #[test]
+    fn test_ml_dsa_44_requires_feature() {
+        let seed = Mnemonic::generate().unwrap().to_seed("");
+        assert!(matches!(
+            Keypair::derive(&seed, KeyType::MlDsa44, &Derivation::Legacy),
+            Err(Error::Unsupported(_))
+        ));
+    }
+}
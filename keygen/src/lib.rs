@@ -0,0 +1,79 @@
+//! Boundless Wallet Library
+//!
+//! Shared wallet primitives used by the `boundless-wallet-gen`,
+//! `boundless-keepbox` and `boundless-wallet` binaries, and embeddable by
+//! other Rust services:
+//!
+//! - [`Mnemonic`] / [`Seed`]: BIP39 mnemonic generation, parsing and seed derivation
+//!   in any BIP39 [`Language`]
+//! - [`Keypair`] / [`KeyType`]: Ed25519 (or ML-DSA-44 / hybrid with the `pqc`
+//!   feature) keypair derived from a seed
+//! - [`Derivation`]: legacy or SLIP-0010 HD derivation paths
+//! - [`Address`]: Boundless address (SHA3-256 of the public key)
+//! - [`KeepBox`]: AES-256-GCM + Argon2id encrypted wallet storage
+//! - [`Vault`]: many labelled wallets in one KeepBox under one password
+//! - [`tx`]: transfer transactions compatible with `send_transaction.py`
+//! - [`envelope`]: checksummed transaction files for air-gapped signing
+//! - [`message`]: arbitrary message signing with optional domain separation
+//! - [`slip39`]: SLIP-0039 Shamir share backups of a mnemonic
+//! - [`entropy`]: mnemonics from dice rolls or hex, with an audit transcript
+//! - [`repair`]: recovery of a mnemonic copied down with one mistake
+//! - [`agent`]: key agent serving unlocked KeepBox keys over a Unix socket
+//!   (Unix only)
+//! - [`signer`]: HTTP remote signer enforcing per-key [`policy`] limits and
+//!   double-sign protection
+//! - [`rpc`]: JSON-RPC client for Boundless nodes
+//! - `mock_node`: in-memory node for end-to-end tests of the CLIs
+//!   (`mock-node` feature)
+//!
+//! ```no_run
+//! use boundless_wallet::{Derivation, KeepBox, KeyType, Keypair, Mnemonic, WalletData};
+//!
+//! let mnemonic = Mnemonic::generate()?;
+//! let keypair = Keypair::from_seed(&mnemonic.to_seed(""));
+//! println!("Address: {}", keypair.address());
+//!
+//! let wallet =
+//!     WalletData::from_mnemonic(&mnemonic, "", KeyType::Ed25519, &Derivation::account(0)?)?;
+//! let keepbox = KeepBox::seal(&wallet, "Correct-Horse-9", None)?;
+//! keepbox.save("wallet.keepbox")?;
+//! # Ok::<(), boundless_wallet::Error>(())
+//! ```
+
+pub mod address;
+#[cfg(unix)]
+pub mod agent;
+pub mod derivation;
+pub mod entropy;
+pub mod envelope;
+pub mod error;
+pub mod files;
+pub mod http;
+pub mod keepbox;
+pub mod keys;
+pub mod message;
+pub mod mnemonic;
+#[cfg(feature = "mock-node")]
+pub mod mock_node;
+pub mod output;
+pub mod policy;
+#[cfg(feature = "pqc")]
+mod pqc;
+pub mod prompt;
+pub mod repair;
+pub mod rpc;
+pub mod signer;
+pub mod slip39;
+pub mod tx;
+pub mod vault;
+pub mod wallet;
+
+pub use address::{derive_address, Address};
+pub use derivation::{Derivation, DerivationPath};
+pub use error::{Error, Result};
+pub use keepbox::{KeepBox, WalletData};
+pub use keys::{verify_signature, KeyType, Keypair, PublicKeyInfo, Signer};
+pub use mnemonic::{Language, Mnemonic, Seed};
+pub use tx::{SignedTransaction, Transaction};
+pub use vault::{KeepBoxFile, Vault, VaultData};
+pub use wallet::WalletOutput;
@@ -0,0 +1,123 @@
+//! Signing of arbitrary messages
+//!
+//! With `prefixed` set, the signed bytes are domain-separated so a message
+//! signature can never be replayed as a transaction signature:
+//!
+//! ```text
+//! "Boundless Signed Message:\n" || decimal byte length || "\n" || message
+//! ```
+
+use crate::address::derive_address;
+use crate::error::{Error, Result};
+use crate::keys::{verify_signature, KeyType, Signer};
+
+/// Domain separation prefix for off-chain messages
+pub const MESSAGE_PREFIX: &str = "Boundless Signed Message:\n";
+
+/// Bytes actually signed for `message`
+pub fn signing_payload(message: &[u8], prefixed: bool) -> Vec<u8> {
+    if !prefixed {
+        return message.to_vec();
+    }
+    let mut payload = format!("{}{}\n", MESSAGE_PREFIX, message.len()).into_bytes();
+    payload.extend_from_slice(message);
+    payload
+}
+
+/// Sign `message`, optionally with the domain separation prefix
+pub fn sign_message(
+    keypair: &(impl Signer + ?Sized),
+    message: &[u8],
+    prefixed: bool,
+) -> Result<Vec<u8>> {
+    keypair.sign(&signing_payload(message, prefixed))
+}
+
+/// Check `public_key` hashes to `address` and `signature` is valid over
+/// `message`; returns the key type inferred from the public key
+pub fn verify_message(
+    public_key: &[u8],
+    address: &str,
+    message: &[u8],
+    signature: &[u8],
+    prefixed: bool,
+) -> Result<KeyType> {
+    let derived = derive_address(public_key);
+    if !derived.eq_ignore_ascii_case(address.trim()) {
+        return Err(Error::InvalidAddress(format!(
+            "public key hashes to {}, not {}",
+            derived, address
+        )));
+    }
+
+    let key_type = KeyType::from_public_key(public_key)?;
+    verify_signature(
+        key_type,
+        public_key,
+        &signing_payload(message, prefixed),
+        signature,
+    )?;
+    Ok(key_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::Keypair;
+    use crate::mnemonic::Mnemonic;
+
+    fn keypair() -> Keypair {
+        Keypair::from_seed(&Mnemonic::generate().unwrap().to_seed(""))
+    }
+
+    #[test]
+    fn test_payload_format() {
+        assert_eq!(signing_payload(b"hi", false), b"hi");
+        assert_eq!(
+            signing_payload(b"hi", true),
+            b"Boundless Signed Message:\n2\nhi"
+        );
+    }
+
+    #[test]
+    fn test_sign_verify_message() {
+        let keypair = keypair();
+        let public_key = keypair.public_key_bytes();
+        let address = keypair.address().to_hex();
+
+        for prefixed in [false, true] {
+            let signature = sign_message(&keypair, b"validator hello", prefixed).unwrap();
+            let key_type = verify_message(
+                &public_key,
+                &address,
+                b"validator hello",
+                &signature,
+                prefixed,
+            )
+            .unwrap();
+            assert_eq!(key_type, KeyType::Ed25519);
+
+            // The prefix is part of what is signed
+            assert!(verify_message(
+                &public_key,
+                &address,
+                b"validator hello",
+                &signature,
+                !prefixed
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_verify_message_checks_address() {
+        let keypair = keypair();
+        let signature = sign_message(&keypair, b"m", false).unwrap();
+        let other = self::keypair().address().to_hex();
+
+        assert!(matches!(
+            verify_message(&keypair.public_key_bytes(), &other, b"m", &signature, false),
+            Err(Error::InvalidAddress(_))
+        ));
+    }
+}
@@ -0,0 +1,345 @@
+//! BIP39 mnemonic generation and seed derivation
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::error::{Error, Result};
+
+/// Word counts BIP39 defines, from 128 to 256 bits of entropy
+pub const WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// BIP39 wordlist a mnemonic is written in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Language {
+    #[default]
+    English,
+    ChineseSimplified,
+    ChineseTraditional,
+    Czech,
+    French,
+    Italian,
+    Japanese,
+    Korean,
+    Portuguese,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 10] = [
+        Language::English,
+        Language::ChineseSimplified,
+        Language::ChineseTraditional,
+        Language::Czech,
+        Language::French,
+        Language::Italian,
+        Language::Japanese,
+        Language::Korean,
+        Language::Portuguese,
+        Language::Spanish,
+    ];
+
+    /// Name as stored in wallet files and accepted by `--language`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::English => "english",
+            Language::ChineseSimplified => "chinese-simplified",
+            Language::ChineseTraditional => "chinese-traditional",
+            Language::Czech => "czech",
+            Language::French => "french",
+            Language::Italian => "italian",
+            Language::Japanese => "japanese",
+            Language::Korean => "korean",
+            Language::Portuguese => "portuguese",
+            Language::Spanish => "spanish",
+        }
+    }
+
+    /// The 2048 words of this wordlist, in index order
+    pub fn word_list(self) -> &'static [&'static str; 2048] {
+        self.to_bip39().word_list()
+    }
+
+    fn to_bip39(self) -> bip39::Language {
+        match self {
+            Language::English => bip39::Language::English,
+            Language::ChineseSimplified => bip39::Language::SimplifiedChinese,
+            Language::ChineseTraditional => bip39::Language::TraditionalChinese,
+            Language::Czech => bip39::Language::Czech,
+            Language::French => bip39::Language::French,
+            Language::Italian => bip39::Language::Italian,
+            Language::Japanese => bip39::Language::Japanese,
+            Language::Korean => bip39::Language::Korean,
+            Language::Portuguese => bip39::Language::Portuguese,
+            Language::Spanish => bip39::Language::Spanish,
+        }
+    }
+
+    fn from_bip39(language: bip39::Language) -> Self {
+        Language::ALL
+            .into_iter()
+            .find(|l| l.to_bip39() == language)
+            .expect("every enabled BIP39 language is listed")
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl FromStr for Language {
+    type Err = Error;
+
+    /// Accepts the names above and ISO 639-1 codes (`zh-hans` / `zh-hant`
+    /// for Chinese)
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().to_ascii_lowercase().replace('_', "-");
+        let language = match s.as_str() {
+            "en" => Language::English,
+            "zh-hans" | "zh-cn" => Language::ChineseSimplified,
+            "zh-hant" | "zh-tw" => Language::ChineseTraditional,
+            "cs" => Language::Czech,
+            "fr" => Language::French,
+            "it" => Language::Italian,
+            "ja" => Language::Japanese,
+            "ko" => Language::Korean,
+            "pt" => Language::Portuguese,
+            "es" => Language::Spanish,
+            name => Language::ALL
+                .into_iter()
+                .find(|l| l.as_str() == name)
+                .ok_or_else(|| {
+                    let names: Vec<_> = Language::ALL.iter().map(|l| l.as_str()).collect();
+                    Error::InvalidArgument(format!(
+                        "unknown mnemonic language '{}' (expected one of {})",
+                        name,
+                        names.join(", ")
+                    ))
+                })?,
+        };
+        Ok(language)
+    }
+}
+
+/// BIP39 mnemonic phrase (12-24 words in any BIP39 language)
+#[derive(Clone, PartialEq, Eq)]
+pub struct Mnemonic(bip39::Mnemonic);
+
+impl Mnemonic {
+    /// Generate a new 24-word English mnemonic from OS randomness
+    pub fn generate() -> Result<Self> {
+        Self::generate_in(Language::English, 24)
+    }
+
+    /// Generate a new mnemonic of `word_count` words (12, 15, 18, 21 or
+    /// 24) in `language` from OS randomness
+    pub fn generate_in(language: Language, word_count: usize) -> Result<Self> {
+        let mut entropy = Zeroizing::new(vec![0u8; crate::entropy::entropy_bytes(word_count)?]);
+        getrandom::getrandom(&mut entropy).map_err(|e| Error::Entropy(e.to_string()))?;
+        Self::from_entropy_in(language, &entropy)
+    }
+
+    /// Build an English mnemonic from raw entropy (16-32 bytes, multiple of 4)
+    pub fn from_entropy(entropy: &[u8]) -> Result<Self> {
+        Self::from_entropy_in(Language::English, entropy)
+    }
+
+    /// Build a mnemonic in `language` from raw entropy
+    pub fn from_entropy_in(language: Language, entropy: &[u8]) -> Result<Self> {
+        bip39::Mnemonic::from_entropy_in(language.to_bip39(), entropy)
+            .map(Mnemonic)
+            .map_err(|e| Error::InvalidMnemonic(e.to_string()))
+    }
+
+    /// Parse and validate a mnemonic phrase, detecting its language. A
+    /// phrase whose words all appear in several wordlists (English and
+    /// French share some) is resolved by its checksum.
+    pub fn parse(phrase: &str) -> Result<Self> {
+        match bip39::Mnemonic::parse(phrase) {
+            Err(bip39::Error::AmbiguousLanguages(candidates)) => {
+                let mut valid = Vec::new();
+                let mut error = None;
+                for language in candidates.iter() {
+                    match bip39::Mnemonic::parse_in(language, phrase) {
+                        Ok(mnemonic) => valid.push(mnemonic),
+                        Err(e) => error = Some(e),
+                    }
+                }
+                match valid.len() {
+                    1 => Ok(Mnemonic(valid.remove(0))),
+                    0 => Err(Error::InvalidMnemonic(
+                        error.map_or_else(String::new, |e| e.to_string()),
+                    )),
+                    _ => {
+                        let names: Vec<_> = valid
+                            .iter()
+                            .map(|m| Language::from_bip39(m.language()).as_str())
+                            .collect();
+                        Err(Error::InvalidMnemonic(format!(
+                            "the phrase is valid in several wordlists ({}); pass --language",
+                            names.join(", ")
+                        )))
+                    }
+                }
+            }
+            result => result
+                .map(Mnemonic)
+                .map_err(|e| Error::InvalidMnemonic(e.to_string())),
+        }
+    }
+
+    /// Parse and validate a mnemonic phrase in a known language
+    pub fn parse_in(language: Language, phrase: &str) -> Result<Self> {
+        bip39::Mnemonic::parse_in(language.to_bip39(), phrase)
+            .map(Mnemonic)
+            .map_err(|e| Error::InvalidMnemonic(e.to_string()))
+    }
+
+    /// Parse in `language` if given, otherwise detect it
+    pub fn parse_with(language: Option<Language>, phrase: &str) -> Result<Self> {
+        match language {
+            Some(language) => Self::parse_in(language, phrase),
+            None => Self::parse(phrase),
+        }
+    }
+
+    /// Derive the 64-byte BIP39 seed (empty passphrase for none)
+    pub fn to_seed(&self, passphrase: &str) -> Seed {
+        Seed(self.0.to_seed(passphrase))
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.0.word_count()
+    }
+
+    pub fn language(&self) -> Language {
+        Language::from_bip39(self.0.language())
+    }
+
+    /// The entropy the phrase encodes (16-32 bytes)
+    pub fn entropy(&self) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(self.0.to_entropy())
+    }
+
+    /// Space separated phrase
+    pub fn phrase(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+impl fmt::Debug for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Mnemonic(<redacted>)")
+    }
+}
+
+/// 64-byte BIP39 seed, zeroized on drop
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct Seed([u8; 64]);
+
+impl Seed {
+    pub fn from_bytes(bytes: [u8; 64]) -> Self {
+        Seed(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 64] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Seed(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::test_support::ABANDON_ART;
+
+    #[test]
+    fn test_generate_24_words() {
+        let mnemonic = Mnemonic::generate().unwrap();
+        assert_eq!(mnemonic.word_count(), 24);
+        assert_eq!(Mnemonic::parse(&mnemonic.phrase()).unwrap(), mnemonic);
+    }
+
+    #[test]
+    fn test_word_counts_and_languages() {
+        for (language, words) in Language::ALL
+            .into_iter()
+            .zip(WORD_COUNTS.into_iter().cycle())
+        {
+            let mnemonic = Mnemonic::generate_in(language, words).unwrap();
+            assert_eq!(mnemonic.word_count(), words);
+            assert_eq!(mnemonic.entropy().len(), words / 3 * 4);
+
+            // Detected on parse, and recorded in wallet files by name
+            let parsed = Mnemonic::parse(&mnemonic.phrase()).unwrap();
+            assert_eq!(parsed.language(), language);
+            assert_eq!(parsed, mnemonic);
+            assert_eq!(language.as_str().parse::<Language>().unwrap(), language);
+            assert_eq!(
+                serde_json::to_string(&language).unwrap(),
+                format!("\"{}\"", language)
+            );
+        }
+
+        assert!(Mnemonic::generate_in(Language::English, 13).is_err());
+        assert_eq!("ja".parse::<Language>().unwrap(), Language::Japanese);
+        assert!("klingon".parse::<Language>().is_err());
+    }
+
+    #[test]
+    fn test_bip39_vectors() {
+        let english = Mnemonic::from_entropy(&[0x7f; 16]).unwrap();
+        assert_eq!(
+            english.phrase(),
+            "legal winner thank year wave sausage worth useful legal winner thank yellow"
+        );
+        assert_eq!(
+            hex::encode(english.to_seed("TREZOR").as_bytes()),
+            "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607"
+        );
+
+        let japanese = Mnemonic::from_entropy_in(Language::Japanese, &[0; 16]).unwrap();
+        assert_eq!(japanese.word_count(), 12);
+        assert_eq!(
+            Mnemonic::parse(&japanese.phrase()).unwrap().language(),
+            Language::Japanese
+        );
+        // Same entropy, different words: the seed differs per language
+        assert_ne!(
+            japanese.to_seed("").as_bytes(),
+            Mnemonic::from_entropy(&[0; 16])
+                .unwrap()
+                .to_seed("")
+                .as_bytes()
+        );
+        assert!(Mnemonic::parse_in(Language::English, &japanese.phrase()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_checksum_rejected() {
+        let bad = ABANDON_ART.replace(" art", " abandon");
+        assert!(matches!(
+            Mnemonic::parse(&bad),
+            Err(Error::InvalidMnemonic(_))
+        ));
+    }
+
+    #[test]
+    fn test_passphrase_changes_seed() {
+        let mnemonic = Mnemonic::parse(ABANDON_ART).unwrap();
+        assert_ne!(
+            mnemonic.to_seed("").as_bytes(),
+            mnemonic.to_seed("TREZOR").as_bytes()
+        );
+    }
+}
@@ -0,0 +1,683 @@
+//! In-memory Boundless node for integration tests
+//!
+//! Serves the JSON-RPC methods `send_transaction.py` uses (see [`rpc`]) on a
+//! loopback port, with balances, nonces and transactions held in memory, so
+//! the CLIs can be tested end to end without running node containers.
+//! Submitted transactions are checked as a node checks them: the signature
+//! over the `_serialize_tx` bytes against the sender's public key, the
+//! nonce, and the balance. A node only learns public keys from signed
+//! transactions, which do not carry one, so senders are registered with
+//! [`MockNode::add_account`].
+//!
+//! [`Fault`]s make chosen calls fail, to test timeouts, retries and error
+//! handling. Every call is recorded, including failed ones.
+//!
+//! ```no_run
+//! use assert_cmd::Command;
+//! use boundless_wallet::mock_node::{Fault, MockNode};
+//!
+//! let node = MockNode::start()?;
+//! node.fail("block_number", Fault::Http(503), 1);
+//! Command::cargo_bin("boundless-wallet")
+//!     .unwrap()
+//!     .args(["block-number"])
+//!     .args(node.rpc_args())
+//!     .assert()
+//!     .success();
+//! assert_eq!(node.calls().len(), 2);
+//! # Ok::<(), boundless_wallet::Error>(())
+//! ```
+//!
+//! [`rpc`]: crate::rpc
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::BufReader;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha3::{Digest, Sha3_256};
+
+use crate::address::Address;
+use crate::error::{Error, Result};
+use crate::http::{Request, Response};
+use crate::keys::KeyType;
+use crate::rpc::TransactionInfo;
+use crate::tx::{SignedTransaction, Transaction};
+
+/// JSON-RPC error code for transactions the node rejects
+pub const REJECTED: i64 = -32000;
+
+/// How an injected fault answers a call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fault {
+    /// Close the connection without acting on the call
+    Drop,
+    /// Act on the call, then close the connection without answering
+    LoseResponse,
+    /// Answer with this HTTP status without acting on the call
+    Http(u16),
+    /// Answer with this JSON-RPC error without acting on the call
+    Rpc { code: i64, message: String },
+    /// Act on the call and answer after this delay
+    Delay(Duration),
+    /// Answer 200 with a body that is not JSON
+    Garbage,
+}
+
+/// A fault for the next `times` calls of `method` (`*` for any method)
+///
+/// Parses from `METHOD=FAULT[*TIMES]`, where FAULT is `drop`,
+/// `lose-response`, `garbage`, `http:<status>`, `rpc:<code>` or
+/// `delay:<ms>`, e.g. `submit_transaction=lose-response` or
+/// `*=http:503*2`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaultRule {
+    pub method: String,
+    pub fault: Fault,
+    pub times: usize,
+}
+
+impl FromStr for FaultRule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || {
+            Error::InvalidArgument(format!(
+                "invalid fault {:?}: expected METHOD=FAULT[*TIMES] with FAULT one of drop, \
+                 lose-response, garbage, http:<status>, rpc:<code>, delay:<ms>",
+                s
+            ))
+        };
+
+        let (method, rest) = s.split_once('=').ok_or_else(invalid)?;
+        let (fault, times) = match rest.rsplit_once('*') {
+            Some((fault, times)) => (fault, times.parse().map_err(|_| invalid())?),
+            None => (rest, 1),
+        };
+        let fault = match fault.split_once(':') {
+            None if fault == "drop" => Fault::Drop,
+            None if fault == "lose-response" => Fault::LoseResponse,
+            None if fault == "garbage" => Fault::Garbage,
+            Some(("http", status)) => Fault::Http(status.parse().map_err(|_| invalid())?),
+            Some(("rpc", code)) => Fault::Rpc {
+                code: code.parse().map_err(|_| invalid())?,
+                message: "Injected fault".to_string(),
+            },
+            Some(("delay", ms)) => {
+                Fault::Delay(Duration::from_millis(ms.parse().map_err(|_| invalid())?))
+            }
+            _ => return Err(invalid()),
+        };
+        if method.is_empty() || times == 0 {
+            return Err(invalid());
+        }
+
+        Ok(FaultRule {
+            method: method.to_string(),
+            fault,
+            times,
+        })
+    }
+}
+
+impl fmt::Display for FaultRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}=", self.method)?;
+        match &self.fault {
+            Fault::Drop => write!(f, "drop")?,
+            Fault::LoseResponse => write!(f, "lose-response")?,
+            Fault::Garbage => write!(f, "garbage")?,
+            Fault::Http(status) => write!(f, "http:{}", status)?,
+            Fault::Rpc { code, .. } => write!(f, "rpc:{}", code)?,
+            Fault::Delay(delay) => write!(f, "delay:{}", delay.as_millis())?,
+        }
+        write!(f, "*{}", self.times)
+    }
+}
+
+/// A JSON-RPC call the node received
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    pub method: String,
+    pub params: Value,
+}
+
+#[derive(Debug, Default)]
+struct Account {
+    public_key: Option<Vec<u8>>,
+    balance: u128,
+    nonce: u64,
+}
+
+#[derive(Debug)]
+struct State {
+    accounts: BTreeMap<String, Account>,
+    transactions: Vec<TransactionInfo>,
+    block_number: u64,
+    auto_mine: bool,
+    faults: Vec<FaultRule>,
+    calls: Vec<Call>,
+}
+
+/// Outcome of a call, as the JSON text of a JSON-RPC result (a [`Value`]
+/// cannot hold balances above 64 bits) or an error
+type Outcome = std::result::Result<String, (i64, String)>;
+
+fn json_text(result: &impl Serialize) -> Outcome {
+    serde_json::to_string(result).map_err(|e| (-32603, format!("Internal error: {}", e)))
+}
+
+fn invalid_params(e: impl fmt::Display) -> (i64, String) {
+    (-32602, format!("Invalid params: {}", e))
+}
+
+fn rejected(e: impl fmt::Display) -> (i64, String) {
+    (REJECTED, e.to_string())
+}
+
+/// Lowercase hex of a well-formed address
+fn address_param(params: &Value, index: usize) -> std::result::Result<String, (i64, String)> {
+    let address = params[index]
+        .as_str()
+        .ok_or_else(|| invalid_params(format!("expected an address at {}", index)))?;
+    Ok(address.parse::<Address>().map_err(invalid_params)?.to_hex())
+}
+
+impl State {
+    fn call(&mut self, method: &str, params: &Value) -> Outcome {
+        match method {
+            "account_balance" => {
+                let address = address_param(params, 0)?;
+                json_text(&self.accounts.get(&address).map_or(0, |a| a.balance))
+            }
+            "account_nonce" => {
+                let address = address_param(params, 0)?;
+                json_text(&self.accounts.get(&address).map_or(0, |a| a.nonce))
+            }
+            "block_number" => json_text(&self.block_number),
+            "get_transaction" => {
+                let hash = params[0]
+                    .as_str()
+                    .ok_or_else(|| invalid_params("expected a transaction hash"))?
+                    .to_ascii_lowercase();
+                json_text(
+                    &self
+                        .transactions
+                        .iter()
+                        .find(|tx| tx.hash.as_deref() == Some(hash.as_str())),
+                )
+            }
+            "submit_transaction" => {
+                let wire = params[0]
+                    .as_str()
+                    .ok_or_else(|| invalid_params("expected the signed transaction JSON string"))?;
+                self.submit(wire).and_then(|hash| json_text(&hash))
+            }
+            _ => Err((-32601, "Method not found".to_string())),
+        }
+    }
+
+    fn submit(&mut self, wire: &str) -> std::result::Result<String, (i64, String)> {
+        let signed: SignedTransaction = serde_json::from_str(wire).map_err(invalid_params)?;
+        let tx = Transaction::new(
+            &signed.from,
+            &signed.to,
+            signed.amount,
+            signed.nonce,
+            signed.fee,
+        )
+        .map_err(invalid_params)?;
+        let (from, to) = (tx.from.to_ascii_lowercase(), tx.to.to_ascii_lowercase());
+
+        let sender = self.accounts.get(&from);
+        let public_key = sender
+            .and_then(|a| a.public_key.as_deref())
+            .ok_or_else(|| rejected(format!("unknown sender {}", from)))?;
+        let key_type = KeyType::from_public_key(public_key).map_err(rejected)?;
+        signed
+            .verify(key_type, public_key)
+            .map_err(|e| rejected(format!("invalid signature: {}", e)))?;
+
+        let sender = self.accounts.get_mut(&from).expect("sender checked above");
+        if tx.nonce != sender.nonce {
+            return Err(rejected(format!(
+                "invalid nonce: expected {}, got {}",
+                sender.nonce, tx.nonce
+            )));
+        }
+        let total = tx
+            .amount
+            .checked_add(tx.fee)
+            .filter(|total| *total <= sender.balance)
+            .ok_or_else(|| rejected("insufficient balance"))?;
+        sender.balance -= total;
+        sender.nonce += 1;
+        let recipient = self.accounts.entry(to.clone()).or_default();
+        recipient.balance = recipient.balance.saturating_add(tx.amount);
+
+        let hash = hex::encode(Sha3_256::digest(wire.as_bytes()));
+        let mut info = TransactionInfo {
+            hash: Some(hash.clone()),
+            status: Some("pending".to_string()),
+            block_number: None,
+            from: Some(from),
+            to: Some(to),
+            amount: Some(tx.amount),
+            fee: Some(tx.fee),
+            nonce: Some(tx.nonce),
+        };
+        if self.auto_mine {
+            self.block_number += 1;
+            info.status = Some("confirmed".to_string());
+            info.block_number = Some(self.block_number);
+        }
+        self.transactions.push(info);
+        Ok(hash)
+    }
+
+    /// Take one use of the first fault matching `method`
+    fn fault(&mut self, method: &str) -> Option<Fault> {
+        let index = self
+            .faults
+            .iter()
+            .position(|rule| rule.method == "*" || rule.method == method)?;
+        let rule = &mut self.faults[index];
+        rule.times -= 1;
+        let fault = rule.fault.clone();
+        if rule.times == 0 {
+            self.faults.remove(index);
+        }
+        Some(fault)
+    }
+}
+
+/// Mock node serving on a background thread until dropped
+pub struct MockNode {
+    state: Arc<Mutex<State>>,
+    addr: SocketAddr,
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockNode {
+    /// Serve on a free loopback port
+    pub fn start() -> Result<Self> {
+        MockNode::bind("127.0.0.1:0")
+    }
+
+    /// Serve on `addr`
+    pub fn bind(addr: &str) -> Result<Self> {
+        let listener = TcpListener::bind(addr)
+            .map_err(|e| Error::io(format!("Failed to listen on {}", addr), e))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| Error::io("Failed to read listen address", e))?;
+
+        let state = Arc::new(Mutex::new(State {
+            accounts: BTreeMap::new(),
+            transactions: Vec::new(),
+            block_number: 0,
+            auto_mine: true,
+            faults: Vec::new(),
+            calls: Vec::new(),
+        }));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let (state, shutdown) = (state.clone(), shutdown.clone());
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        let state = state.clone();
+                        thread::spawn(move || connection(&state, stream));
+                    }
+                }
+            })
+        };
+
+        Ok(MockNode {
+            state,
+            addr,
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// `http://` URL of the node
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// `--rpc-url <url>` for the CLIs
+    pub fn rpc_args(&self) -> [String; 2] {
+        ["--rpc-url".to_string(), self.url()]
+    }
+
+    /// Register the holder of `public_key` with `balance`, so it can send;
+    /// returns its address
+    pub fn add_account(&self, public_key: &[u8], balance: u128) -> Result<String> {
+        KeyType::from_public_key(public_key)?;
+        let address = Address::from_public_key(public_key).to_hex();
+        let mut state = self.state();
+        let account = state.accounts.entry(address.clone()).or_default();
+        account.public_key = Some(public_key.to_vec());
+        account.balance = balance;
+        Ok(address)
+    }
+
+    /// Set the balance of `address`, whose public key stays unknown
+    pub fn set_balance(&self, address: &str, balance: u128) -> Result<()> {
+        let address = address.parse::<Address>()?.to_hex();
+        self.state().accounts.entry(address).or_default().balance = balance;
+        Ok(())
+    }
+
+    pub fn balance(&self, address: &str) -> u128 {
+        self.account(address, |a| a.balance)
+    }
+
+    pub fn nonce(&self, address: &str) -> u64 {
+        self.account(address, |a| a.nonce)
+    }
+
+    pub fn block_number(&self) -> u64 {
+        self.state().block_number
+    }
+
+    pub fn set_block_number(&self, block_number: u64) {
+        self.state().block_number = block_number;
+    }
+
+    /// Include each accepted transaction in a block of its own right away
+    /// (the default), or leave them pending until [`MockNode::mine`]
+    pub fn set_auto_mine(&self, auto_mine: bool) {
+        self.state().auto_mine = auto_mine;
+    }
+
+    /// Include the pending transactions in a new block; returns its number
+    pub fn mine(&self) -> u64 {
+        let mut state = self.state();
+        state.block_number += 1;
+        let block_number = state.block_number;
+        for tx in &mut state.transactions {
+            if tx.block_number.is_none() {
+                tx.status = Some("confirmed".to_string());
+                tx.block_number = Some(block_number);
+            }
+        }
+        block_number
+    }
+
+    /// Accepted transactions, oldest first
+    pub fn transactions(&self) -> Vec<TransactionInfo> {
+        self.state().transactions.clone()
+    }
+
+    /// Calls received so far, oldest first
+    pub fn calls(&self) -> Vec<Call> {
+        self.state().calls.clone()
+    }
+
+    /// Answer the next `times` calls of `method` (`*` for any) with `fault`
+    pub fn fail(&self, method: &str, fault: Fault, times: usize) {
+        self.inject(FaultRule {
+            method: method.to_string(),
+            fault,
+            times,
+        });
+    }
+
+    pub fn inject(&self, rule: FaultRule) {
+        if rule.times > 0 {
+            self.state().faults.push(rule);
+        }
+    }
+
+    /// Serve until the process is killed
+    pub fn wait(mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    fn account<T: Default>(&self, address: &str, field: impl Fn(&Account) -> T) -> T {
+        let address = address.to_ascii_lowercase();
+        self.state()
+            .accounts
+            .get(&address)
+            .map(field)
+            .unwrap_or_default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        lock(&self.state)
+    }
+}
+
+impl Drop for MockNode {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the flag
+        let _ = TcpStream::connect(self.addr);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A panicking test thread must not take the other calls down with it
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn connection(state: &Mutex<State>, stream: TcpStream) {
+    let request = match Request::read_from(&mut BufReader::new(&stream)) {
+        Ok(request) => request,
+        Err(e) => {
+            let _ = Response::error(&e).write_to(&mut &stream);
+            return;
+        }
+    };
+    let reply = |id: &Value, outcome: Outcome| {
+        let body = match outcome {
+            Ok(result) => format!(r#"{{"jsonrpc":"2.0","id":{},"result":{}}}"#, id, result),
+            Err((code, message)) => {
+                json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+                    .to_string()
+            }
+        };
+        let response = Response {
+            status: 200,
+            body: body.into_bytes(),
+        };
+        let _ = response.write_to(&mut &stream);
+    };
+
+    let call: Value = match serde_json::from_slice(&request.body) {
+        Ok(call) => call,
+        Err(e) => return reply(&Value::Null, Err((-32700, format!("Parse error: {}", e)))),
+    };
+    let id = call["id"].clone();
+    let Some(method) = call["method"].as_str() else {
+        return reply(&id, Err((-32600, "Invalid request: no method".to_string())));
+    };
+    let params = match &call["params"] {
+        Value::Null => json!([]),
+        params => params.clone(),
+    };
+
+    let fault = {
+        let mut state = lock(state);
+        state.calls.push(Call {
+            method: method.to_string(),
+            params: params.clone(),
+        });
+        state.fault(method)
+    };
+    match fault {
+        Some(Fault::Drop) => {}
+        Some(Fault::Http(status)) => {
+            let _ = Response::json(status, &json!({})).write_to(&mut &stream);
+        }
+        Some(Fault::Rpc { code, message }) => reply(&id, Err((code, message))),
+        Some(Fault::Garbage) => {
+            let response = Response {
+                status: 200,
+                body: b"<html>502 Bad Gateway</html>".to_vec(),
+            };
+            let _ = response.write_to(&mut &stream);
+        }
+        Some(Fault::LoseResponse) => {
+            let _ = lock(state).call(method, &params);
+        }
+        Some(Fault::Delay(delay)) => {
+            thread::sleep(delay);
+            let outcome = lock(state).call(method, &params);
+            reply(&id, outcome);
+        }
+        None => {
+            let outcome = lock(state).call(method, &params);
+            reply(&id, outcome);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::test_support::abandon_keypair as keypair;
+    use crate::rpc::RpcClient;
+
+    #[test]
+    fn test_transfers() {
+        let node = MockNode::start().unwrap();
+        let keypair = keypair();
+        let from = node.add_account(&keypair.public_key_bytes(), 100).unwrap();
+        let to = "bb".repeat(32);
+        let client = RpcClient::new(&node.url()).unwrap();
+
+        let tx = Transaction::new(&from, &to, 60, 0, 1).unwrap();
+        let hash = client
+            .submit_transaction(&tx.sign(&keypair).unwrap())
+            .unwrap();
+        assert_eq!(
+            (client.balance(&from).unwrap(), client.nonce(&from).unwrap()),
+            (39, 1)
+        );
+        assert_eq!(client.balance(&to).unwrap(), 60);
+        let info = client.transaction(&hash).unwrap().unwrap();
+        assert_eq!(
+            (info.status.as_deref(), info.block_number),
+            (Some("confirmed"), Some(1))
+        );
+
+        // Replayed, overspent, forged and unknown-sender transactions
+        let rejected = |tx: SignedTransaction| match client.submit_transaction(&tx) {
+            Err(Error::Rpc { code, message }) => {
+                assert_eq!(code, REJECTED);
+                message
+            }
+            other => panic!("{:?}", other),
+        };
+        assert!(rejected(tx.sign(&keypair).unwrap()).contains("nonce"));
+        let overspend = Transaction::new(&from, &to, 40, 1, 0).unwrap();
+        assert!(rejected(overspend.sign(&keypair).unwrap()).contains("balance"));
+        let mut forged = Transaction::new(&from, &to, 1, 1, 0)
+            .unwrap()
+            .sign(&keypair)
+            .unwrap();
+        forged.amount = 2;
+        assert!(rejected(forged).contains("signature"));
+        let mut stranger = Transaction::new(&from, &to, 1, 1, 0)
+            .unwrap()
+            .sign(&keypair)
+            .unwrap();
+        stranger.from = to.clone();
+        assert!(rejected(stranger).contains("unknown sender"));
+
+        node.set_auto_mine(false);
+        let tx = Transaction::new(&from, &to, 1, 1, 0).unwrap();
+        let hash = client
+            .submit_transaction(&tx.sign(&keypair).unwrap())
+            .unwrap();
+        assert_eq!(
+            client
+                .transaction(&hash)
+                .unwrap()
+                .unwrap()
+                .status
+                .as_deref(),
+            Some("pending")
+        );
+        assert_eq!(node.mine(), 2);
+        assert_eq!(node.transactions()[1].block_number, Some(2));
+        assert_eq!(client.transaction(&"00".repeat(32)).unwrap(), None);
+        assert_eq!(node.calls()[0].method, "submit_transaction");
+
+        // Balances above 64 bits are answered exactly
+        node.set_balance(&to, u128::MAX).unwrap();
+        assert_eq!(client.balance(&to).unwrap(), u128::MAX);
+    }
+
+    #[test]
+    fn test_faults() {
+        let node = MockNode::start().unwrap();
+        let client = RpcClient::new(&node.url()).unwrap().with_retries(0);
+
+        node.inject("*=http:503*2".parse().unwrap());
+        node.fail("block_number", Fault::Garbage, 1);
+        node.fail(
+            "block_number",
+            Fault::Rpc {
+                code: -32001,
+                message: "syncing".to_string(),
+            },
+            1,
+        );
+        assert!(matches!(
+            client.nonce(&"aa".repeat(32)),
+            Err(Error::NodeUnreachable(_))
+        ));
+        assert!(matches!(
+            client.block_number(),
+            Err(Error::NodeUnreachable(_))
+        ));
+        assert!(matches!(
+            client.block_number(),
+            Err(Error::NodeUnreachable(_))
+        ));
+        assert!(matches!(
+            client.block_number(),
+            Err(Error::Rpc { code: -32001, .. })
+        ));
+        assert_eq!(client.block_number().unwrap(), 0);
+        assert_eq!(node.calls().len(), 5);
+
+        let slow = client.with_timeout(Duration::from_millis(100));
+        node.fail("block_number", Fault::Delay(Duration::from_millis(500)), 1);
+        assert!(matches!(
+            slow.block_number(),
+            Err(Error::NodeUnreachable(_))
+        ));
+
+        let rule: FaultRule = "submit_transaction=lose-response".parse().unwrap();
+        assert_eq!((rule.fault.clone(), rule.times), (Fault::LoseResponse, 1));
+        assert_eq!(rule.to_string(), "submit_transaction=lose-response*1");
+        for bad in ["drop", "=drop", "x=fire", "x=http:abc", "x=drop*0"] {
+            assert!(bad.parse::<FaultRule>().is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Text or JSON output for the CLI binaries
+//!
+//! With `--output-format json` every command prints exactly one JSON
+//! document on stdout, described by `schemas/cli-output.schema.json`:
+//! human text written with [`say!`](crate::say) is suppressed, prompts go to
+//! stderr, and [`warn`]ings are collected into the document instead of being
+//! printed.
+
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde_json::{json, Value};
+
+use crate::error::{exit_code, Error, Result};
+
+/// Version of the JSON document layout; bumped on incompatible changes
+pub const OUTPUT_SCHEMA_VERSION: u32 = 1;
+
+/// How a command reports its result
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text (default)
+    #[default]
+    Text,
+    /// One JSON document on stdout
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            other => Err(Error::InvalidArgument(format!(
+                "unknown output format '{}' (expected text or json)",
+                other
+            ))),
+        }
+    }
+}
+
+static JSON: AtomicBool = AtomicBool::new(false);
+static WARNINGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Select the output format for the rest of the process
+pub fn set_output_format(format: OutputFormat) {
+    JSON.store(format == OutputFormat::Json, Ordering::Relaxed);
+}
+
+/// Whether human text is suppressed in favour of a JSON document
+pub fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// The output format requested on a command line that failed to parse, so
+/// usage errors can still be reported as a JSON document
+pub fn format_from_args<I: IntoIterator<Item = String>>(args: I) -> OutputFormat {
+    let mut format = OutputFormat::Text;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "--output-format" => args.next(),
+            _ => arg.strip_prefix("--output-format=").map(str::to_string),
+        };
+        if let Some(parsed) = value.and_then(|v| v.parse().ok()) {
+            format = parsed;
+        }
+    }
+    format
+}
+
+/// Parse the command line of binary `command`. Usage errors exit with code 1
+/// as documented in CLI_SPECIFICATION.md rather than clap's default of 2,
+/// and are reported as a JSON document when one was asked for.
+pub fn parse_or_exit<C: clap::Parser>(command: &str) -> C {
+    C::try_parse().unwrap_or_else(|e| {
+        if e.use_stderr() && format_from_args(std::env::args()) == OutputFormat::Json {
+            set_output_format(OutputFormat::Json);
+            finish(
+                command,
+                Err(Error::InvalidArgument(e.to_string().trim_end().to_string())),
+            );
+        }
+        let _ = e.print();
+        std::process::exit(if e.use_stderr() {
+            exit_code::INVALID_ARGUMENTS
+        } else {
+            exit_code::SUCCESS
+        })
+    })
+}
+
+/// Print a line of human-readable output; nothing in JSON mode
+#[macro_export]
+macro_rules! say {
+    () => {
+        if !$crate::output::is_json() {
+            println!();
+        }
+    };
+    ($($arg:tt)*) => {
+        if !$crate::output::is_json() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Report a warning: printed to stderr as text, or added to the JSON
+/// document's `warnings`
+pub fn warn(message: impl Into<String>) {
+    let message = message.into();
+    if is_json() {
+        WARNINGS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(message);
+    } else {
+        eprintln!("⚠️  {}", message);
+    }
+}
+
+/// Show an input prompt without a newline: on stdout as text, on stderr in
+/// JSON mode so the document stays parseable
+pub fn prompt(text: &str) -> Result<()> {
+    if is_json() {
+        eprint!("{}", text);
+        io::stderr().flush()
+    } else {
+        print!("{}", text);
+        io::stdout().flush()
+    }
+    .map_err(|e| Error::io("Failed to flush prompt", e))
+}
+
+/// The JSON document for a finished command
+pub fn document(command: &str, result: &Result<Value>) -> Value {
+    let warnings = std::mem::take(&mut *WARNINGS.lock().unwrap_or_else(|e| e.into_inner()));
+    match result {
+        Ok(data) => json!({
+            "schema_version": OUTPUT_SCHEMA_VERSION,
+            "command": command,
+            "ok": true,
+            "exit_code": exit_code::SUCCESS,
+            "data": data,
+            "warnings": warnings,
+            "error": null,
+        }),
+        Err(e) => json!({
+            "schema_version": OUTPUT_SCHEMA_VERSION,
+            "command": command,
+            "ok": false,
+            "exit_code": e.exit_code(),
+            "data": null,
+            "warnings": warnings,
+            "error": {
+                "id": e.id(),
+                "code": e.exit_code(),
+                "message": e.to_string(),
+            },
+        }),
+    }
+}
+
+/// Report the command's result and exit with its code: the JSON document on
+/// stdout, or for text output the error (with its identifier) on stderr
+pub fn finish(command: &str, result: Result<Value>) -> ! {
+    let code = match &result {
+        Ok(_) => exit_code::SUCCESS,
+        Err(e) => e.exit_code(),
+    };
+
+    if is_json() {
+        let document = document(command, &result);
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&document).unwrap_or_default()
+        );
+    } else if let Err(e) = &result {
+        eprintln!("❌ Error [{}]: {}", e.id(), e);
+    }
+    std::process::exit(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_layout() {
+        let ok = document("open", &Ok(json!({ "address": "ab" })));
+        assert_eq!(ok["ok"], true);
+        assert_eq!(ok["exit_code"], 0);
+        assert_eq!(ok["data"]["address"], "ab");
+        assert!(ok["error"].is_null());
+
+        let failed = document("verify", &Err(Error::DecryptionFailed));
+        assert_eq!(failed["ok"], false);
+        assert_eq!(failed["exit_code"], 3);
+        assert_eq!(failed["error"]["id"], "decryption_failed");
+        assert!(failed["data"].is_null());
+        assert!(failed["warnings"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_format_from_args() {
+        let args = |s: &str| s.split(' ').map(str::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            format_from_args(args("open --output-format JSON")),
+            OutputFormat::Json
+        );
+        assert_eq!(
+            format_from_args(args("open --output-format json -k")),
+            OutputFormat::Json
+        );
+        assert_eq!(
+            format_from_args(args("--output-format=json open")),
+            OutputFormat::Json
+        );
+        assert_eq!(format_from_args(args("open -k x")), OutputFormat::Text);
+    }
+
+    #[test]
+    fn test_output_format_parse() {
+        assert_eq!("JSON".parse::<OutputFormat>().unwrap(), OutputFormat::Json);
+        assert_eq!("text".parse::<OutputFormat>().unwrap(), OutputFormat::Text);
+        assert!(matches!(
+            "yaml".parse::<OutputFormat>(),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+}
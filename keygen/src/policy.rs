@@ -0,0 +1,401 @@
+//! Signing policies and double-sign protection for the remote signer
+//!
+//! Every key served by `boundless-signer` has a [`Policy`] limiting what it
+//! signs: recipients, amount per transaction and per UTC day, fee, and how
+//! far ahead of the last signed nonce a transaction may be. A key without a
+//! policy signs nothing.
+//!
+//! The [`ProtectionDb`] records a hash of every transaction signed for each
+//! nonce, so a second, different transaction with an already used nonce is
+//! refused - the signer can never produce two competing transactions for
+//! the same account slot. Asking again for the identical transaction is
+//! allowed and does not count towards the daily limit twice. The database
+//! must be saved before a signature is released; see
+//! [`RemoteSigner`](crate::signer::RemoteSigner).
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha3::{Digest, Sha3_256};
+
+use crate::address::Address;
+use crate::error::{Error, Result};
+use crate::files;
+use crate::tx::{format_amount, parse_amount, Transaction};
+
+/// Signed nonces remembered per key; older ones are forgotten and every
+/// nonce below the oldest remembered one is refused
+pub const NONCE_HISTORY: usize = 1000;
+
+/// Version of the protection database layout
+const PROTECTION_DB_VERSION: u32 = 1;
+
+/// What a key may sign. Amounts are in BLS, e.g. `"2.5"`; an absent limit
+/// is no limit.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    /// Recipients transfers may go to; absent allows any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_recipients: Option<Vec<String>>,
+
+    /// Largest amount of one transfer
+    #[serde(default, with = "bls", skip_serializing_if = "Option::is_none")]
+    pub max_amount: Option<u128>,
+
+    /// Largest total amount signed per UTC day
+    #[serde(default, with = "bls", skip_serializing_if = "Option::is_none")]
+    pub max_daily_amount: Option<u128>,
+
+    /// Largest fee of one transfer
+    #[serde(default, with = "bls", skip_serializing_if = "Option::is_none")]
+    pub max_fee: Option<u128>,
+
+    /// Nonces that may be skipped past the highest signed one; 0 requires
+    /// nonces in sequence. The first transaction a key signs may use any
+    /// nonce.
+    #[serde(default)]
+    pub max_nonce_gap: u64,
+
+    /// Whether `/sign/message` may use this key. Messages are always signed
+    /// with the "Boundless Signed Message" prefix, so they can never be
+    /// replayed as transactions.
+    #[serde(default)]
+    pub allow_messages: bool,
+}
+
+impl Policy {
+    /// Check the recipient addresses are well-formed and normalize them to
+    /// lowercase
+    pub fn validate(&mut self) -> Result<()> {
+        if let Some(recipients) = &mut self.allowed_recipients {
+            for recipient in recipients.iter_mut() {
+                *recipient = recipient.parse::<Address>()?.to_hex();
+            }
+        }
+        Ok(())
+    }
+
+    /// Limits that do not depend on signing history
+    fn check_transfer(&self, tx: &Transaction) -> Result<()> {
+        if let Some(recipients) = &self.allowed_recipients {
+            if !recipients.iter().any(|r| r.eq_ignore_ascii_case(&tx.to)) {
+                return Err(Error::PolicyViolation(format!(
+                    "recipient {} is not allowed",
+                    tx.to
+                )));
+            }
+        }
+        if let Some(max) = self.max_amount.filter(|max| tx.amount > *max) {
+            return Err(Error::PolicyViolation(format!(
+                "amount {} BLS exceeds the {} BLS limit per transaction",
+                format_amount(tx.amount),
+                format_amount(max)
+            )));
+        }
+        if let Some(max) = self.max_fee.filter(|max| tx.fee > *max) {
+            return Err(Error::PolicyViolation(format!(
+                "fee {} BLS exceeds the {} BLS limit",
+                format_amount(tx.fee),
+                format_amount(max)
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Optional BLS amounts as decimal strings
+mod bls {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        amount: &Option<u128>,
+        s: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        amount.map(format_amount).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> std::result::Result<Option<u128>, D::Error> {
+        Option::<String>::deserialize(d)?
+            .map(|amount| parse_amount(&amount).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+/// Whether a transaction is new or one the key signed before
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Approval {
+    /// Not signed before; record it before releasing the signature
+    New,
+    /// Identical to the transaction already signed for this nonce
+    Repeat,
+}
+
+/// Signing history of one key
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRecord {
+    /// Nonces below this are refused; raised as old entries are forgotten
+    pub min_nonce: u64,
+
+    /// SHA3-256 of the signing bytes of the transaction signed per nonce
+    pub signed: BTreeMap<u64, String>,
+
+    /// UTC day (`YYYY-MM-DD`) that `spent` counts
+    pub day: Option<String>,
+
+    /// Amount signed on `day`, in base units
+    pub spent: u128,
+}
+
+impl KeyRecord {
+    fn spent_on(&self, day: &str) -> u128 {
+        if self.day.as_deref() == Some(day) {
+            self.spent
+        } else {
+            0
+        }
+    }
+}
+
+/// Per-key history of signed transactions, kept on disk between runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtectionDb {
+    pub version: u32,
+    /// Records by lowercase address
+    pub keys: BTreeMap<String, KeyRecord>,
+}
+
+impl Default for ProtectionDb {
+    fn default() -> Self {
+        ProtectionDb {
+            version: PROTECTION_DB_VERSION,
+            keys: BTreeMap::new(),
+        }
+    }
+}
+
+impl ProtectionDb {
+    /// Load the database at `path`; an empty one if the file does not exist
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = match fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(ProtectionDb::default()),
+            Err(e) => return Err(Error::io("Failed to read protection database", e)),
+        };
+        let db: ProtectionDb = serde_json::from_str(&json)
+            .map_err(|e| Error::Encoding(format!("Failed to parse protection database: {}", e)))?;
+        if db.version != PROTECTION_DB_VERSION {
+            return Err(Error::Unsupported(format!(
+                "protection database version {} (this build reads {})",
+                db.version, PROTECTION_DB_VERSION
+            )));
+        }
+        Ok(db)
+    }
+
+    /// Crash-safely write the database to `path` (0600)
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_vec_pretty(self).map_err(|e| {
+            Error::Encoding(format!("Failed to serialize protection database: {}", e))
+        })?;
+        if fs::symlink_metadata(path).is_err() {
+            return files::create_private_file(path, &json);
+        }
+        files::replace_private_file(path, &json, |written| {
+            serde_json::from_slice::<ProtectionDb>(written)
+                .map(|_| ())
+                .map_err(|e| Error::Encoding(e.to_string()))
+        })
+    }
+
+    /// Whether `policy` and the history of `tx.from` allow signing `tx` on
+    /// UTC `day`
+    pub fn check(&self, policy: &Policy, tx: &Transaction, day: &str) -> Result<Approval> {
+        policy.check_transfer(tx)?;
+
+        let empty = KeyRecord::default();
+        let record = self
+            .keys
+            .get(&tx.from.to_ascii_lowercase())
+            .unwrap_or(&empty);
+        match record.signed.get(&tx.nonce) {
+            Some(hash) if *hash == tx_hash(tx) => return Ok(Approval::Repeat),
+            Some(_) => {
+                return Err(Error::PolicyViolation(format!(
+                    "nonce {} was already signed for a different transaction",
+                    tx.nonce
+                )))
+            }
+            None => {}
+        }
+        if tx.nonce < record.min_nonce {
+            return Err(Error::PolicyViolation(format!(
+                "nonce {} is below {}, the oldest nonce still protected",
+                tx.nonce, record.min_nonce
+            )));
+        }
+        if let Some(&highest) = record.signed.keys().next_back() {
+            let max_nonce = highest
+                .saturating_add(1)
+                .saturating_add(policy.max_nonce_gap);
+            if tx.nonce > max_nonce {
+                return Err(Error::PolicyViolation(format!(
+                    "nonce {} skips past {}, the highest allowed after signed nonce {}",
+                    tx.nonce, max_nonce, highest
+                )));
+            }
+        }
+        if let Some(max) = policy.max_daily_amount {
+            let total = record.spent_on(day).saturating_add(tx.amount);
+            if total > max {
+                return Err(Error::PolicyViolation(format!(
+                    "{} BLS signed today with this one exceeds the {} BLS daily limit",
+                    format_amount(total),
+                    format_amount(max)
+                )));
+            }
+        }
+        Ok(Approval::New)
+    }
+
+    /// Record `tx` as signed on UTC `day`
+    pub fn record(&mut self, tx: &Transaction, day: &str) {
+        let record = self.keys.entry(tx.from.to_ascii_lowercase()).or_default();
+        if record.signed.insert(tx.nonce, tx_hash(tx)).is_some() {
+            return;
+        }
+        record.spent = record.spent_on(day).saturating_add(tx.amount);
+        record.day = Some(day.to_string());
+        while record.signed.len() > NONCE_HISTORY {
+            if let Some((nonce, _)) = record.signed.pop_first() {
+                record.min_nonce = nonce + 1;
+            }
+        }
+    }
+}
+
+/// SHA3-256 of the signing bytes (hex)
+fn tx_hash(tx: &Transaction) -> String {
+    hex::encode(Sha3_256::digest(tx.signing_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tx::parse_amount;
+
+    const DAY: &str = "2026-10-17";
+
+    fn tx(to: &str, amount: &str, nonce: u64, fee: &str) -> Transaction {
+        Transaction::new(
+            &"aa".repeat(32),
+            &to.repeat(32),
+            parse_amount(amount).unwrap(),
+            nonce,
+            parse_amount(fee).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn policy(json: &str) -> Policy {
+        let mut policy: Policy = serde_json::from_str(json).unwrap();
+        policy.validate().unwrap();
+        policy
+    }
+
+    fn refused(result: Result<Approval>) -> bool {
+        matches!(result, Err(Error::PolicyViolation(_)))
+    }
+
+    #[test]
+    fn test_policy_limits() {
+        let policy = policy(&format!(
+            r#"{{"allowed_recipients": ["{}"], "max_amount": "10", "max_fee": "0.01"}}"#,
+            "BB".repeat(32)
+        ));
+        assert_eq!(policy.allowed_recipients, Some(vec!["bb".repeat(32)]));
+        let db = ProtectionDb::default();
+
+        assert_eq!(
+            db.check(&policy, &tx("bb", "10", 0, "0.01"), DAY).unwrap(),
+            Approval::New
+        );
+        assert!(refused(db.check(&policy, &tx("cc", "1", 0, "0"), DAY)));
+        assert!(refused(db.check(
+            &policy,
+            &tx("bb", "10.000000000000000001", 0, "0"),
+            DAY
+        )));
+        assert!(refused(db.check(&policy, &tx("bb", "1", 0, "0.02"), DAY)));
+
+        // Typos in a policy are errors, not silently ignored limits
+        assert!(serde_json::from_str::<Policy>(r#"{"max_ammount": "1"}"#).is_err());
+        assert!(serde_json::from_str::<Policy>(r#"{"max_amount": "1.2.3"}"#).is_err());
+        let mut bad: Policy = serde_json::from_str(r#"{"allowed_recipients": ["xyz"]}"#).unwrap();
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_double_sign_and_nonce_gap() {
+        let policy = policy(r#"{"max_nonce_gap": 1}"#);
+        let mut db = ProtectionDb::default();
+
+        let first = tx("bb", "1", 5, "0");
+        db.record(&first, DAY);
+        assert_eq!(db.check(&policy, &first, DAY).unwrap(), Approval::Repeat);
+        assert!(refused(db.check(&policy, &tx("cc", "1", 5, "0"), DAY)));
+
+        // Up to one nonce may be skipped, and the skipped one filled later
+        assert!(refused(db.check(&policy, &tx("bb", "1", 8, "0"), DAY)));
+        let skipping = tx("bb", "1", 7, "0");
+        assert_eq!(db.check(&policy, &skipping, DAY).unwrap(), Approval::New);
+        db.record(&skipping, DAY);
+        assert_eq!(
+            db.check(&policy, &tx("bb", "1", 6, "0"), DAY).unwrap(),
+            Approval::New
+        );
+
+        // Forgotten nonces stay refused
+        for nonce in 8..8 + NONCE_HISTORY as u64 {
+            db.record(&tx("bb", "1", nonce, "0"), DAY);
+        }
+        let record = &db.keys[&"aa".repeat(32)];
+        assert_eq!(record.signed.len(), NONCE_HISTORY);
+        assert_eq!(record.min_nonce, 8);
+        assert!(refused(db.check(&policy, &tx("bb", "1", 6, "0"), DAY)));
+    }
+
+    #[test]
+    fn test_daily_limit() {
+        let policy = policy(r#"{"max_daily_amount": "5", "max_nonce_gap": 10}"#);
+        let mut db = ProtectionDb::default();
+
+        db.record(&tx("bb", "3", 0, "0"), DAY);
+        db.record(&tx("bb", "3", 0, "0"), DAY); // a repeat is not counted twice
+        assert_eq!(db.keys[&"aa".repeat(32)].spent, parse_amount("3").unwrap());
+        assert!(db.check(&policy, &tx("bb", "2", 1, "0"), DAY).is_ok());
+        assert!(refused(db.check(&policy, &tx("bb", "2.1", 1, "0"), DAY)));
+        assert!(db
+            .check(&policy, &tx("bb", "5", 1, "0"), "2026-10-18")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("protection.json");
+        assert_eq!(ProtectionDb::load(&path).unwrap(), ProtectionDb::default());
+
+        let mut db = ProtectionDb::default();
+        db.record(&tx("bb", "1", 3, "0"), DAY);
+        db.save(&path).unwrap();
+        db.record(&tx("bb", "1", 4, "0"), DAY);
+        db.save(&path).unwrap();
+        assert_eq!(ProtectionDb::load(&path).unwrap(), db);
+    }
+}
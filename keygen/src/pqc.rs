@@ -0,0 +1,73 @@
+//! ML-DSA-44 (FIPS 204) via the pure-Rust `fips204` crate
+
+use fips204::ml_dsa_44;
+use fips204::traits::{KeyGen, SerDes, Signer, Verifier};
+use zeroize::Zeroizing;
+
+use crate::error::{Error, Result};
+
+pub(crate) const PUBLIC_KEY_LEN: usize = ml_dsa_44::PK_LEN;
+pub(crate) const SIGNATURE_LEN: usize = ml_dsa_44::SIG_LEN;
+
+/// Empty FIPS 204 context string; domain separation is done by callers
+const CONTEXT: &[u8] = b"";
+
+#[derive(Clone)]
+pub(crate) struct MlDsa44Keypair {
+    public_key: ml_dsa_44::PublicKey,
+    private_key: ml_dsa_44::PrivateKey,
+}
+
+impl MlDsa44Keypair {
+    /// Deterministic `ML-DSA.KeyGen_internal(xi)`
+    pub(crate) fn from_seed(xi: &[u8; 32]) -> Self {
+        let (public_key, private_key) = ml_dsa_44::KG::keygen_from_seed(xi);
+        MlDsa44Keypair {
+            public_key,
+            private_key,
+        }
+    }
+
+    pub(crate) fn public_key_bytes(&self) -> Vec<u8> {
+        self.public_key.clone().into_bytes().to_vec()
+    }
+
+    pub(crate) fn secret_bytes(&self) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(self.private_key.clone().into_bytes().to_vec())
+    }
+
+    /// Hedged (randomized) ML-DSA signature
+    pub(crate) fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        self.private_key
+            .try_sign(message, CONTEXT)
+            .map(|sig| sig.to_vec())
+            .map_err(|e| Error::Crypto(format!("ML-DSA-44 signing failed: {}", e)))
+    }
+}
+
+pub(crate) fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<()> {
+    let public_key: [u8; PUBLIC_KEY_LEN] = public_key.try_into().map_err(|_| {
+        Error::InvalidPublicKey(format!(
+            "ML-DSA-44 public key must be {} bytes, got {}",
+            PUBLIC_KEY_LEN,
+            public_key.len()
+        ))
+    })?;
+    let signature: [u8; SIGNATURE_LEN] = signature.try_into().map_err(|_| {
+        Error::InvalidSignature(format!(
+            "ML-DSA-44 signature must be {} bytes, got {}",
+            SIGNATURE_LEN,
+            signature.len()
+        ))
+    })?;
+
+    let public_key = ml_dsa_44::PublicKey::try_from_bytes(public_key)
+        .map_err(|e| Error::InvalidPublicKey(e.to_string()))?;
+    if public_key.verify(message, &signature, CONTEXT) {
+        Ok(())
+    } else {
+        Err(Error::InvalidSignature(
+            "ML-DSA-44 signature does not verify".to_string(),
+        ))
+    }
+}
@@ -0,0 +1,444 @@
+//! Interactive terminal prompts shared by the CLI binaries
+//!
+//! KeepBox passwords normally come from the terminal. For automation the
+//! binaries can instead select a [`PasswordSource`] once at startup with
+//! [`set_password_source`]; every later [`prompt_password`] and
+//! [`prompt_bip39_passphrase`] then takes the next line from it.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use rpassword::read_password;
+use zeroize::Zeroizing;
+
+use crate::error::{Error, Result};
+use crate::keepbox::{validate_password_strength, WalletData};
+use crate::keys::KeyRing;
+use crate::output;
+use crate::vault::KeepBoxFile;
+
+/// Environment variable used as the KeepBox password when no other source is
+/// given. Other processes of the same user can read a process environment,
+/// so this is meant for disposable CI runners only.
+pub const PASSWORD_ENV: &str = "BOUNDLESS_KEYSTORE_PASSWORD";
+
+/// Where KeepBox passwords are read from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PasswordSource {
+    /// Ask on the terminal, with confirmation for new passwords
+    #[default]
+    Prompt,
+    /// One password per line of a file
+    File(PathBuf),
+    /// One password per line read from an inherited file descriptor
+    Fd(u32),
+    /// One password per line of standard input
+    Stdin,
+    /// The [`PASSWORD_ENV`] variable (a single password)
+    Env,
+}
+
+impl PasswordSource {
+    /// Pick the source from the CLI options, falling back to [`PASSWORD_ENV`]
+    /// if it is set and then to the terminal
+    pub fn from_options(file: Option<PathBuf>, fd: Option<u32>, stdin: bool) -> Self {
+        match (file, fd, stdin) {
+            (Some(path), _, _) => PasswordSource::File(path),
+            (None, Some(fd), _) => PasswordSource::Fd(fd),
+            (None, None, true) => PasswordSource::Stdin,
+            (None, None, false) if std::env::var_os(PASSWORD_ENV).is_some() => PasswordSource::Env,
+            (None, None, false) => PasswordSource::Prompt,
+        }
+    }
+
+    /// Whether passwords are typed at the terminal
+    pub fn is_interactive(&self) -> bool {
+        *self == PasswordSource::Prompt
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            PasswordSource::Prompt => "the terminal".to_string(),
+            PasswordSource::File(path) => format!("password file {}", path.display()),
+            PasswordSource::Fd(fd) => format!("file descriptor {}", fd),
+            PasswordSource::Stdin => "stdin".to_string(),
+            PasswordSource::Env => PASSWORD_ENV.to_string(),
+        }
+    }
+}
+
+/// Password source options of the CLI binaries, flattened into each one.
+/// Without any of these, passwords are read from [`PASSWORD_ENV`] if set,
+/// otherwise prompted for.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct PasswordArgs {
+    /// Read passwords from a file, one per line in the order they are asked for
+    #[arg(long, global = true, value_name = "PATH", conflicts_with_all = ["password_fd", "password_stdin"])]
+    pub password_file: Option<PathBuf>,
+
+    /// Read passwords from an inherited file descriptor, one per line
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        conflicts_with = "password_stdin"
+    )]
+    pub password_fd: Option<u32>,
+
+    /// Read passwords from stdin, one per line
+    #[arg(long, global = true)]
+    pub password_stdin: bool,
+}
+
+impl PasswordArgs {
+    pub fn source(&self) -> PasswordSource {
+        PasswordSource::from_options(
+            self.password_file.clone(),
+            self.password_fd,
+            self.password_stdin,
+        )
+    }
+}
+
+/// Non-interactive source and the passwords it has not handed out yet
+struct PasswordReader {
+    source: PasswordSource,
+    pending: Option<VecDeque<Zeroizing<String>>>,
+}
+
+impl PasswordReader {
+    /// Next password from the source. Files, descriptors and the environment
+    /// are read once on first use; stdin is read a line at a time.
+    fn next(&mut self) -> Result<Zeroizing<String>> {
+        if self.source == PasswordSource::Stdin {
+            let mut line = Zeroizing::new(String::new());
+            io::stdin()
+                .lock()
+                .read_line(&mut line)
+                .map_err(|e| Error::io("Failed to read password from stdin", e))?;
+            return trim_line_ending(line).ok_or_else(|| self.exhausted());
+        }
+
+        if self.pending.is_none() {
+            self.pending = Some(self.read_all()?);
+        }
+        let next = self
+            .pending
+            .as_mut()
+            .and_then(|pending| pending.pop_front());
+        next.ok_or_else(|| self.exhausted())
+    }
+
+    fn read_all(&self) -> Result<VecDeque<Zeroizing<String>>> {
+        let contents = match &self.source {
+            PasswordSource::File(path) => {
+                warn_if_shared(path);
+                Zeroizing::new(
+                    fs::read_to_string(path)
+                        .map_err(|e| Error::io("Failed to read password file", e))?,
+                )
+            }
+            // /dev/fd avoids taking ownership of a raw descriptor (Linux, macOS, BSD)
+            PasswordSource::Fd(fd) => Zeroizing::new(
+                fs::read_to_string(format!("/dev/fd/{}", fd))
+                    .map_err(|e| Error::io(format!("Failed to read password from fd {}", fd), e))?,
+            ),
+            PasswordSource::Env => {
+                output::warn(format!(
+                    "WARNING: using the KeepBox password from {}. Environment variables leak \
+                     through /proc, `ps e`, crash dumps and child processes; prefer \
+                     --password-file or --password-fd.",
+                    PASSWORD_ENV
+                ));
+                let password = std::env::var(PASSWORD_ENV)
+                    .map_err(|_| Error::Encoding(format!("{} is not valid UTF-8", PASSWORD_ENV)))?;
+                return Ok(VecDeque::from([Zeroizing::new(password)]));
+            }
+            PasswordSource::Prompt | PasswordSource::Stdin => {
+                unreachable!("not read in one go")
+            }
+        };
+
+        Ok(contents
+            .lines()
+            .map(|line| Zeroizing::new(line.to_string()))
+            .collect())
+    }
+
+    fn exhausted(&self) -> Error {
+        if self.source == PasswordSource::Env {
+            return Error::Unsupported(format!(
+                "{} holds a single password; use --password-file with one per line",
+                PASSWORD_ENV
+            ));
+        }
+        Error::io(
+            format!("No more passwords in {}", self.source.describe()),
+            io::ErrorKind::UnexpectedEof.into(),
+        )
+    }
+}
+
+/// Strip one trailing `\n` or `\r\n`; `None` at end of input
+fn trim_line_ending(mut line: Zeroizing<String>) -> Option<Zeroizing<String>> {
+    if line.is_empty() {
+        return None;
+    }
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    Some(line)
+}
+
+/// Warn when a password file can be read by other users
+fn warn_if_shared(path: &PathBuf) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(metadata) = fs::metadata(path) {
+            if metadata.permissions().mode() & 0o077 != 0 {
+                output::warn(format!(
+                    "WARNING: password file {} is accessible by other users (chmod 600 it)",
+                    path.display()
+                ));
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+static PASSWORD_SOURCE: Mutex<Option<PasswordReader>> = Mutex::new(None);
+
+/// Read later [`prompt_password`] calls from `source` instead of the
+/// terminal. Called once by the CLI binaries; library users pass passwords
+/// to [`crate::KeepBox`] directly.
+pub fn set_password_source(source: PasswordSource) {
+    let reader = (!source.is_interactive()).then_some(PasswordReader {
+        source,
+        pending: None,
+    });
+    *PASSWORD_SOURCE.lock().unwrap_or_else(|e| e.into_inner()) = reader;
+}
+
+/// Get a KeepBox password. From the terminal (not echoed) it re-asks until
+/// the password passes the strength policy and, if `confirm`, is entered
+/// twice. From a non-interactive source the next password is taken as is:
+/// it must pass the policy, and is not confirmed.
+pub fn prompt_password(prompt: &str, confirm: bool) -> Result<Zeroizing<String>> {
+    if let Some(reader) = PASSWORD_SOURCE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+    {
+        let password = reader.next()?;
+        if password.is_empty() {
+            return Err(Error::WeakPassword(format!(
+                "Empty password from {}",
+                reader.source.describe()
+            )));
+        }
+        validate_password_strength(&password)?;
+        return Ok(password);
+    }
+
+    loop {
+        output::prompt(prompt)?;
+
+        let password =
+            Zeroizing::new(read_password().map_err(|e| Error::io("Failed to read password", e))?);
+
+        if password.is_empty() {
+            eprintln!("❌ Password cannot be empty");
+            continue;
+        }
+
+        if let Err(e) = validate_password_strength(&password) {
+            eprintln!("❌ {}", e);
+            continue;
+        }
+
+        if confirm {
+            output::prompt("Confirm password: ")?;
+
+            let password2 = Zeroizing::new(
+                read_password().map_err(|e| Error::io("Failed to read password", e))?,
+            );
+
+            if password != password2 {
+                eprintln!("❌ Passwords do not match");
+                continue;
+            }
+        }
+
+        return Ok(password);
+    }
+}
+
+/// Prompt for a BIP39 passphrase (not echoed). Unlike the KeepBox password
+/// no strength policy applies: the passphrase must match the one the wallet
+/// was generated with. A non-interactive source set with
+/// [`set_password_source`] supplies it as its next line, unconfirmed.
+pub fn prompt_bip39_passphrase(confirm: bool) -> Result<Zeroizing<String>> {
+    if let Some(reader) = PASSWORD_SOURCE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+    {
+        let passphrase = reader.next()?;
+        if passphrase.is_empty() {
+            return Err(Error::InvalidArgument(format!(
+                "Empty BIP39 passphrase from {}",
+                reader.source.describe()
+            )));
+        }
+        return Ok(passphrase);
+    }
+
+    loop {
+        output::prompt("Enter BIP39 passphrase: ")?;
+
+        let passphrase =
+            Zeroizing::new(read_password().map_err(|e| Error::io("Failed to read passphrase", e))?);
+
+        if passphrase.is_empty() {
+            eprintln!("❌ Passphrase cannot be empty");
+            continue;
+        }
+
+        if confirm {
+            output::prompt("Confirm BIP39 passphrase: ")?;
+
+            let passphrase2 = Zeroizing::new(
+                read_password().map_err(|e| Error::io("Failed to read passphrase", e))?,
+            );
+
+            if passphrase != passphrase2 {
+                eprintln!("❌ Passphrases do not match");
+                continue;
+            }
+        }
+
+        return Ok(passphrase);
+    }
+}
+
+/// Prompt for the passphrase only if the decrypted wallet needs one
+pub fn passphrase_for(wallet_data: &WalletData) -> Result<Zeroizing<String>> {
+    if wallet_data.has_passphrase {
+        crate::say!("🔑 This wallet uses a BIP39 passphrase.");
+        prompt_bip39_passphrase(false)
+    } else {
+        Ok(Zeroizing::new(String::new()))
+    }
+}
+
+/// Prompt for the password of each KeepBox in `paths` (and any BIP39
+/// passphrases) and add its wallet, or every wallet of a vault, to `keys`
+pub fn unlock_keepboxes(paths: &[impl AsRef<Path>], keys: &mut KeyRing) -> Result<()> {
+    for path in paths {
+        let path = path.as_ref();
+        let keepbox = KeepBoxFile::load(path)?;
+        let password = prompt_password(&format!("Enter password for {}: ", path.display()), false)?;
+        crate::say!();
+
+        let wallets: Vec<(WalletData, Option<String>)> = match &keepbox {
+            KeepBoxFile::Wallet(keepbox) => {
+                vec![(keepbox.unseal(&password)?, keepbox.metadata.label.clone())]
+            }
+            KeepBoxFile::Vault(vault) => vault
+                .unseal(&password)?
+                .entries
+                .into_iter()
+                .map(|entry| (entry.wallet, Some(entry.metadata.label)))
+                .collect(),
+        };
+        for (wallet_data, label) in wallets {
+            let passphrase = passphrase_for(&wallet_data)?;
+            let key = keys.add(wallet_data.keypair(&passphrase)?, label, path)?;
+            crate::say!(
+                "✓ Unlocked {} ({})",
+                key.address,
+                key.label.as_deref().unwrap_or("no label")
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reader(source: PasswordSource) -> PasswordReader {
+        PasswordReader {
+            source,
+            pending: None,
+        }
+    }
+
+    #[test]
+    fn test_password_file_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("passwords");
+        fs::write(&path, "Old-Password-2025\r\n New-Password-2025 \n").unwrap();
+
+        let mut passwords = reader(PasswordSource::File(path));
+        assert_eq!(passwords.next().unwrap().as_str(), "Old-Password-2025");
+        // Only the line ending is stripped
+        assert_eq!(passwords.next().unwrap().as_str(), " New-Password-2025 ");
+        assert!(matches!(passwords.next(), Err(Error::Io { .. })));
+    }
+
+    #[test]
+    fn test_passphrase_from_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("passwords");
+        fs::write(&path, "Str0ng!Passw0rd#1\nshort\n\n").unwrap();
+
+        // The only test that sets the process-wide source
+        set_password_source(PasswordSource::File(path));
+        let password = prompt_password("", false).map(|p| p.to_string());
+        let passphrase = prompt_bip39_passphrase(true).map(|p| p.to_string());
+        let empty = prompt_bip39_passphrase(false);
+        set_password_source(PasswordSource::Prompt);
+
+        assert_eq!(password.unwrap(), "Str0ng!Passw0rd#1");
+        // No strength policy for passphrases
+        assert_eq!(passphrase.unwrap(), "short");
+        assert!(matches!(empty, Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_password_source_options() {
+        let file = PathBuf::from("pw.txt");
+        assert_eq!(
+            PasswordSource::from_options(Some(file.clone()), Some(3), true),
+            PasswordSource::File(file)
+        );
+        assert_eq!(
+            PasswordSource::from_options(None, Some(3), true),
+            PasswordSource::Fd(3)
+        );
+        assert_eq!(
+            PasswordSource::from_options(None, None, true),
+            PasswordSource::Stdin
+        );
+    }
+
+    #[test]
+    fn test_trim_line_ending() {
+        let trim = |s: &str| trim_line_ending(Zeroizing::new(s.to_string())).map(|l| l.to_string());
+        assert_eq!(trim("pw\n").as_deref(), Some("pw"));
+        assert_eq!(trim("pw\r\n").as_deref(), Some("pw"));
+        assert_eq!(trim("pw").as_deref(), Some("pw"));
+        assert_eq!(trim("\n").as_deref(), Some(""));
+        assert_eq!(trim(""), None);
+    }
+}
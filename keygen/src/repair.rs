@@ -0,0 +1,400 @@
+//! Mnemonic typo recovery
+//!
+//! A phrase copied down with one mistake fails its BIP39 checksum and
+//! [`Mnemonic::parse`] can only reject it. [`repair`] lists the phrases one
+//! edit away that pass the checksum:
+//!
+//! - a misspelled word is completed from its first four letters, which are
+//!   unique within each wordlist
+//! - one word is replaced by every other word of the wordlist (only the
+//!   word outside the wordlist, if there is one)
+//! - two adjacent words are swapped
+//!
+//! The checksum passes by chance for 1 in 16 (12 words) to 1 in 256
+//! (24 words) wrong phrases, so a phrase usually has many candidates. A known
+//! address picks out the right one: [`derive_addresses`] derives every
+//! candidate's address across all cores.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use crate::address::Address;
+use crate::derivation::Derivation;
+use crate::error::{Error, Result};
+use crate::keys::{KeyType, Keypair};
+use crate::mnemonic::{Language, Mnemonic, WORD_COUNTS};
+
+/// One edit between the phrase as written and a candidate. Positions count
+/// words from 1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Fix {
+    /// Word outside the wordlist completed from its first four letters
+    Completed {
+        position: usize,
+        written: String,
+        word: &'static str,
+    },
+    /// Word replaced by another word of the wordlist
+    Replaced {
+        position: usize,
+        written: String,
+        word: &'static str,
+    },
+    /// Words at `position` and `position + 1` swapped
+    Swapped { position: usize },
+}
+
+/// A checksum-valid phrase one edit (plus any completions) away from the
+/// phrase as written
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub mnemonic: Mnemonic,
+    /// Empty if the phrase was valid as written
+    pub fixes: Vec<Fix>,
+}
+
+/// Every checksum-valid phrase one edit away from `phrase`, in the given
+/// wordlist or in each wordlist that recognises the most of its words.
+///
+/// Fails if the word count is wrong or more than one word is neither in the
+/// wordlist nor completable.
+pub fn repair(phrase: &str, language: Option<Language>) -> Result<Vec<Candidate>> {
+    let words: Vec<Zeroizing<String>> = phrase
+        .split_whitespace()
+        .map(|w| Zeroizing::new(w.to_lowercase()))
+        .collect();
+    if !WORD_COUNTS.contains(&words.len()) {
+        return Err(Error::InvalidMnemonic(format!(
+            "a mnemonic has 12, 15, 18, 21 or 24 words, not {}",
+            words.len()
+        )));
+    }
+
+    let languages = match language {
+        Some(language) => vec![language],
+        None => {
+            let scores: Vec<usize> = Language::ALL
+                .iter()
+                .map(|&l| words.iter().filter(|w| resolve(l, w).is_some()).count())
+                .collect();
+            let best = scores.iter().copied().max().unwrap_or(0);
+            Language::ALL
+                .iter()
+                .zip(&scores)
+                .filter(|&(_, &score)| score == best)
+                .map(|(&l, _)| l)
+                .collect()
+        }
+    };
+
+    let mut candidates = Vec::new();
+    let mut error = None;
+    for language in languages {
+        match repair_in(language, &words) {
+            Ok(found) => candidates.extend(found),
+            Err(e) => error = Some(e),
+        }
+    }
+    match error {
+        Some(e) if candidates.is_empty() => Err(e),
+        _ => Ok(candidates),
+    }
+}
+
+fn repair_in(language: Language, words: &[Zeroizing<String>]) -> Result<Vec<Candidate>> {
+    let list = language.word_list();
+
+    let mut indices = Zeroizing::new(Vec::with_capacity(words.len()));
+    let mut completions = Vec::new();
+    let mut unknown = Vec::new();
+    for (i, word) in words.iter().enumerate() {
+        match resolve(language, word) {
+            Some((index, exact)) => {
+                if !exact {
+                    completions.push(Fix::Completed {
+                        position: i + 1,
+                        written: word.to_string(),
+                        word: list[index as usize],
+                    });
+                }
+                indices.push(index);
+            }
+            None => {
+                unknown.push(i);
+                indices.push(0);
+            }
+        }
+    }
+
+    let mut candidates = Vec::new();
+    let mut push = |indices: &[u16], fix: Option<Fix>| -> Result<()> {
+        if let Some(entropy) = checked_entropy(indices) {
+            let mut fixes = completions.clone();
+            fixes.extend(fix);
+            candidates.push(Candidate {
+                mnemonic: Mnemonic::from_entropy_in(language, &entropy)?,
+                fixes,
+            });
+        }
+        Ok(())
+    };
+
+    match unknown[..] {
+        [] => {
+            push(&indices, None)?;
+            let mut edited = indices.clone();
+            for i in 0..indices.len() {
+                for word in 0..list.len() as u16 {
+                    if word == indices[i] {
+                        continue;
+                    }
+                    edited[i] = word;
+                    push(
+                        &edited,
+                        Some(Fix::Replaced {
+                            position: i + 1,
+                            written: list[indices[i] as usize].to_string(),
+                            word: list[word as usize],
+                        }),
+                    )?;
+                }
+                edited[i] = indices[i];
+            }
+            for i in 0..indices.len() - 1 {
+                if indices[i] == indices[i + 1] {
+                    continue;
+                }
+                edited.swap(i, i + 1);
+                push(&edited, Some(Fix::Swapped { position: i + 1 }))?;
+                edited.swap(i, i + 1);
+            }
+        }
+        [i] => {
+            let mut edited = indices.clone();
+            for word in 0..list.len() as u16 {
+                edited[i] = word;
+                push(
+                    &edited,
+                    Some(Fix::Replaced {
+                        position: i + 1,
+                        written: words[i].to_string(),
+                        word: list[word as usize],
+                    }),
+                )?;
+            }
+        }
+        _ => {
+            let positions: Vec<String> = unknown.iter().map(|i| (i + 1).to_string()).collect();
+            return Err(Error::InvalidMnemonic(format!(
+                "words {} are not in the {} wordlist; only one unknown word can be repaired",
+                positions.join(", "),
+                language
+            )));
+        }
+    }
+    Ok(candidates)
+}
+
+/// Index of `word` in the wordlist, and whether it matched exactly rather
+/// than by its first four letters
+fn resolve(language: Language, word: &str) -> Option<(u16, bool)> {
+    let list = language.word_list();
+    if let Some(index) = list.iter().position(|w| *w == word) {
+        return Some((index as u16, true));
+    }
+    let prefix: String = word.chars().take(4).collect();
+    if prefix.chars().count() < 4 {
+        return None;
+    }
+    let mut matches = list
+        .iter()
+        .enumerate()
+        .filter(|(_, w)| w.starts_with(&prefix));
+    match (matches.next(), matches.next()) {
+        (Some((index, _)), None) => Some((index as u16, false)),
+        _ => None,
+    }
+}
+
+/// The entropy behind 11-bit word indices, if their checksum is valid
+fn checked_entropy(indices: &[u16]) -> Option<Zeroizing<Vec<u8>>> {
+    let bits = indices.len() * 11;
+    let checksum_bits = bits / 33;
+    let mut bytes = Zeroizing::new(vec![0u8; bits.div_ceil(8)]);
+    for (i, &index) in indices.iter().enumerate() {
+        for b in 0..11 {
+            if index & (1 << (10 - b)) != 0 {
+                let bit = i * 11 + b;
+                bytes[bit / 8] |= 0x80 >> (bit % 8);
+            }
+        }
+    }
+    let entropy_len = (bits - checksum_bits) / 8;
+    let checksum = Sha256::digest(&bytes[..entropy_len])[0] >> (8 - checksum_bits);
+    let written = bytes[entropy_len] >> (8 - checksum_bits);
+    if checksum != written {
+        return None;
+    }
+    bytes.truncate(entropy_len);
+    Some(bytes)
+}
+
+/// Derive the address of every candidate on `threads` threads (all cores
+/// for 0). With a `target`, only candidates deriving it are returned and the
+/// search stops at the first match. Results are `(index into candidates,
+/// address)` in candidate order.
+pub fn derive_addresses(
+    candidates: &[Candidate],
+    passphrase: &str,
+    key_type: KeyType,
+    derivation: &Derivation,
+    target: Option<&Address>,
+    threads: usize,
+) -> Result<Vec<(usize, Address)>> {
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+    let threads = match threads {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
+    let chunk = candidates.len().div_ceil(threads);
+    let found = AtomicBool::new(false);
+
+    let results: Vec<Result<Vec<(usize, Address)>>> = thread::scope(|scope| {
+        let workers: Vec<_> = candidates
+            .chunks(chunk)
+            .enumerate()
+            .map(|(n, slice)| {
+                let found = &found;
+                scope.spawn(move || {
+                    let mut addresses = Vec::new();
+                    for (i, candidate) in slice.iter().enumerate() {
+                        if found.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let seed = candidate.mnemonic.to_seed(passphrase);
+                        let address = Keypair::derive(&seed, key_type, derivation)?.address();
+                        match target {
+                            Some(target) if *target != address => continue,
+                            Some(_) => found.store(true, Ordering::Relaxed),
+                            None => {}
+                        }
+                        addresses.push((n * chunk + i, address));
+                    }
+                    Ok(addresses)
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|w| w.join().expect("address derivation thread panicked"))
+            .collect()
+    });
+
+    let mut addresses = Vec::new();
+    for result in results {
+        addresses.extend(result?);
+    }
+    Ok(addresses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::test_support::{ABANDON_ADDRESS as ADDRESS, ABANDON_ART as PHRASE};
+
+    fn phrases(candidates: &[Candidate]) -> Vec<String> {
+        candidates.iter().map(|c| c.mnemonic.phrase()).collect()
+    }
+
+    #[test]
+    fn test_checked_entropy_matches_bip39() {
+        let mnemonic = Mnemonic::parse(PHRASE).unwrap();
+        let list = Language::English.word_list();
+        let indices: Vec<u16> = PHRASE
+            .split_whitespace()
+            .map(|w| list.iter().position(|l| *l == w).unwrap() as u16)
+            .collect();
+        assert_eq!(
+            checked_entropy(&indices).unwrap().as_slice(),
+            mnemonic.entropy().as_slice()
+        );
+        let mut wrong = indices.clone();
+        wrong[23] = 0;
+        assert!(checked_entropy(&wrong).is_none());
+    }
+
+    #[test]
+    fn test_misspelled_and_unknown_words() {
+        // Completed from "aban", nothing else to fix
+        let candidates = repair(&PHRASE.replacen("abandon", "abandn", 1), None).unwrap();
+        assert_eq!(candidates[0].mnemonic.phrase(), PHRASE);
+        assert_eq!(
+            candidates[0].fixes,
+            [Fix::Completed {
+                position: 1,
+                written: "abandn".to_string(),
+                word: "abandon"
+            }]
+        );
+
+        // Too short to complete: every word is tried in its place
+        let candidates = repair(&PHRASE.replace("art", "zz"), Some(Language::English)).unwrap();
+        assert!(phrases(&candidates).contains(&PHRASE.to_string()));
+        assert!(candidates
+            .iter()
+            .all(|c| matches!(c.fixes[..], [Fix::Replaced { position: 24, .. }])));
+
+        let two_unknown = PHRASE.replacen("abandon", "zz", 2);
+        assert!(repair(&two_unknown, Some(Language::English)).is_err());
+        assert!(repair("abandon art", None).is_err());
+    }
+
+    #[test]
+    fn test_substitution_and_swap_found_by_address() {
+        let target: Address = ADDRESS.parse().unwrap();
+        let derivation = Derivation::Legacy;
+
+        // "art" miscopied as another valid word: the checksum fails
+        let typo = PHRASE.replace("art", "arm");
+        assert!(Mnemonic::parse(&typo).is_err());
+        let candidates = repair(&typo, None).unwrap();
+        let found = derive_addresses(
+            &candidates,
+            "",
+            KeyType::Ed25519,
+            &derivation,
+            Some(&target),
+            4,
+        )
+        .unwrap();
+        assert_eq!(found.len(), 1);
+        let (index, address) = found[0];
+        assert_eq!(address, target);
+        assert_eq!(candidates[index].mnemonic.phrase(), PHRASE);
+
+        // The last two words swapped
+        let swapped = PHRASE.replace("abandon art", "art abandon");
+        let candidates = repair(&swapped, Some(Language::English)).unwrap();
+        let found = derive_addresses(
+            &candidates,
+            "",
+            KeyType::Ed25519,
+            &derivation,
+            Some(&target),
+            0,
+        )
+        .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            candidates[found[0].0].fixes,
+            [Fix::Swapped { position: 23 }]
+        );
+    }
+}
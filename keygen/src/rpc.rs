@@ -0,0 +1,489 @@
+//! JSON-RPC client for Boundless nodes
+//!
+//! The Rust counterpart of `BoundlessRPC` in `send_transaction.py`: the same
+//! methods against the same default endpoint, with typed results. Each call
+//! is a JSON-RPC 2.0 POST on its own connection:
+//!
+//! | Method | Params | Result |
+//! |--------|--------|--------|
+//! | `account_balance` | `[address]` | balance in base units |
+//! | `account_nonce` | `[address]` | next nonce |
+//! | `submit_transaction` | `[SignedTransaction::to_json()]` | transaction hash |
+//! | `get_transaction` | `[hash]` | [`TransactionInfo`] or `null` |
+//! | `block_number` | `[]` | current height |
+//!
+//! A call that fails before the request reached the node is retried; one
+//! that may have reached it (timeout, dropped connection, HTTP 5xx) is only
+//! retried for read-only methods, never for `submit_transaction`. Errors the
+//! node reports are [`Error::Rpc`] and never retried; every other failure is
+//! [`Error::NodeUnreachable`].
+
+use std::io::BufReader;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::value::RawValue;
+use serde_json::{json, Value};
+
+use crate::error::{Error, Result};
+use crate::http::{Endpoint, Response};
+use crate::tx::SignedTransaction;
+
+/// Node endpoint used by `send_transaction.py`
+pub const DEFAULT_URL: &str = "http://localhost:9933";
+
+/// Time allowed to connect, and to receive each response
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Extra attempts after a failed call
+pub const DEFAULT_RETRIES: u32 = 2;
+
+/// Wait before the first retry; doubled for each one after
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// JSON-RPC 2.0 request
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RpcRequest<'a> {
+    pub jsonrpc: &'static str,
+    pub id: u64,
+    pub method: &'a str,
+    pub params: &'a [Value],
+}
+
+/// JSON-RPC 2.0 response. The result is kept as its JSON text and decoded
+/// by type: a [`Value`] cannot hold integers above 64 bits.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RpcResponse {
+    #[serde(default)]
+    pub result: Option<Box<RawValue>>,
+    #[serde(default)]
+    pub error: Option<RpcError>,
+}
+
+/// Error object of a JSON-RPC response
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: Option<Value>,
+}
+
+/// Transaction as reported by `get_transaction`; fields the node leaves out
+/// are `None`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionInfo {
+    #[serde(default)]
+    pub hash: Option<String>,
+    /// e.g. `pending`, `confirmed` or `failed`
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "quantity")]
+    pub block_number: Option<u64>,
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default, deserialize_with = "quantity")]
+    pub amount: Option<u128>,
+    #[serde(default, deserialize_with = "quantity")]
+    pub fee: Option<u128>,
+    #[serde(default, deserialize_with = "quantity")]
+    pub nonce: Option<u64>,
+}
+
+impl TransactionInfo {
+    /// Form used in output documents, with amounts as decimal strings
+    pub fn to_output(&self) -> Value {
+        json!({
+            "hash": self.hash,
+            "status": self.status,
+            "block_number": self.block_number,
+            "from": self.from,
+            "to": self.to,
+            "amount": self.amount.map(|amount| amount.to_string()),
+            "fee": self.fee.map(|fee| fee.to_string()),
+            "nonce": self.nonce,
+        })
+    }
+}
+
+/// A whole number, or a decimal string as some nodes send large amounts.
+/// Parsed from the number's own text so u128 amounts stay exact.
+fn parse_quantity<T: FromStr>(raw: &RawValue) -> std::result::Result<T, String> {
+    let text = match raw.get() {
+        quoted if quoted.starts_with('"') => {
+            serde_json::from_str::<String>(quoted).map_err(|e| e.to_string())?
+        }
+        number if number.starts_with(|c: char| c.is_ascii_digit()) => number.to_string(),
+        other => return Err(format!("expected a quantity, got {}", other)),
+    };
+    text.parse()
+        .map_err(|_| format!("invalid quantity {:?}", text))
+}
+
+fn quantity<'de, D, T>(d: D) -> std::result::Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+{
+    Option::<Box<RawValue>>::deserialize(d)?
+        .map(|raw| parse_quantity(&raw).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// A missing or `null` result
+fn null() -> Box<RawValue> {
+    RawValue::from_string("null".to_string()).expect("null is valid JSON")
+}
+
+fn unexpected_result(method: &str, e: impl std::fmt::Display) -> Error {
+    Error::Encoding(format!("Unexpected {} result from node: {}", method, e))
+}
+
+/// Why an attempt failed
+enum Failure {
+    /// The node never received the request; any call can be retried
+    NotSent(Error),
+    /// The node may have acted on the request
+    Sent(Error),
+    /// The node answered; retrying would not help
+    Final(Error),
+}
+
+/// Client for one node endpoint
+#[derive(Debug, Clone)]
+pub struct RpcClient {
+    endpoint: Endpoint,
+    timeout: Duration,
+    retries: u32,
+    retry_delay: Duration,
+}
+
+impl RpcClient {
+    /// Client for `url` (`http://host[:port][/path]`) with the default
+    /// timeout and retries
+    pub fn new(url: &str) -> Result<Self> {
+        Ok(RpcClient {
+            endpoint: url.parse()?,
+            timeout: DEFAULT_TIMEOUT,
+            retries: DEFAULT_RETRIES,
+            retry_delay: RETRY_DELAY,
+        })
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+
+    /// Balance of `address` in base units
+    pub fn balance(&self, address: &str) -> Result<u128> {
+        self.call_quantity("account_balance", &[json!(address)])
+    }
+
+    /// Nonce the next transaction from `address` must use
+    pub fn nonce(&self, address: &str) -> Result<u64> {
+        self.call_quantity("account_nonce", &[json!(address)])
+    }
+
+    /// Broadcast `tx`, returning its hash
+    pub fn submit_transaction(&self, tx: &SignedTransaction) -> Result<String> {
+        self.call("submit_transaction", &[json!(tx.to_json()?)])
+    }
+
+    /// The transaction with `hash`, or `None` if the node does not know it
+    pub fn transaction(&self, hash: &str) -> Result<Option<TransactionInfo>> {
+        self.call("get_transaction", &[json!(hash)])
+    }
+
+    /// Current block height
+    pub fn block_number(&self) -> Result<u64> {
+        self.call_quantity("block_number", &[])
+    }
+
+    /// Call `method` and decode its result. A result of the wrong shape
+    /// from a node that did answer is an [`Error::Encoding`].
+    pub fn call<T: DeserializeOwned>(&self, method: &str, params: &[Value]) -> Result<T> {
+        let result = self.call_raw(method, params)?;
+        serde_json::from_str(result.get()).map_err(|e| unexpected_result(method, e))
+    }
+
+    fn call_quantity<T: FromStr>(&self, method: &str, params: &[Value]) -> Result<T> {
+        parse_quantity(&self.call_raw(method, params)?).map_err(|e| unexpected_result(method, e))
+    }
+
+    fn call_raw(&self, method: &str, params: &[Value]) -> Result<Box<RawValue>> {
+        let request = RpcRequest {
+            jsonrpc: "2.0",
+            id: 1,
+            method,
+            params,
+        };
+        let body = serde_json::to_vec(&request)
+            .map_err(|e| Error::Encoding(format!("Failed to encode RPC request: {}", e)))?;
+        let read_only = method != "submit_transaction";
+
+        let mut delay = self.retry_delay;
+        let mut attempt = 0;
+        loop {
+            let error = match self.attempt(&body) {
+                Ok(value) => return Ok(value),
+                Err(Failure::Final(e)) => return Err(e),
+                Err(Failure::Sent(e)) if !read_only => return Err(e),
+                Err(Failure::NotSent(e) | Failure::Sent(e)) => e,
+            };
+            if attempt == self.retries {
+                return Err(error);
+            }
+            attempt += 1;
+            thread::sleep(delay);
+            delay *= 2;
+        }
+    }
+
+    fn attempt(&self, body: &[u8]) -> std::result::Result<Box<RawValue>, Failure> {
+        let unreachable = |e: String| Error::NodeUnreachable(format!("{}: {}", self.endpoint, e));
+
+        let addrs = (self.endpoint.host.as_str(), self.endpoint.port)
+            .to_socket_addrs()
+            .map_err(|e| Failure::NotSent(unreachable(e.to_string())))?;
+        let mut last = None;
+        let mut stream = None;
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, self.timeout) {
+                Ok(s) => {
+                    stream = Some(s);
+                    break;
+                }
+                Err(e) => last = Some(e),
+            }
+        }
+        let stream = stream.ok_or_else(|| {
+            Failure::NotSent(unreachable(
+                last.map_or("no address".to_string(), |e| e.to_string()),
+            ))
+        })?;
+        stream
+            .set_read_timeout(Some(self.timeout))
+            .and_then(|_| stream.set_write_timeout(Some(self.timeout)))
+            .map_err(|e| Failure::NotSent(unreachable(e.to_string())))?;
+
+        self.endpoint
+            .write_post(&mut &stream, body)
+            .map_err(|e| Failure::Sent(unreachable(e.to_string())))?;
+        let response = Response::read_from(&mut BufReader::new(&stream))
+            .map_err(|e| Failure::Sent(unreachable(e.to_string())))?;
+
+        // Nodes may report JSON-RPC errors with any HTTP status
+        match serde_json::from_slice::<RpcResponse>(&response.body) {
+            Ok(RpcResponse {
+                error: Some(error), ..
+            }) => Err(Failure::Final(Error::Rpc {
+                code: error.code,
+                message: error.message,
+            })),
+            Ok(RpcResponse { result, .. }) if response.status == 200 => {
+                Ok(result.unwrap_or_else(null))
+            }
+            Err(e) if response.status == 200 => Err(Failure::Final(unreachable(format!(
+                "not a JSON-RPC response: {}",
+                e
+            )))),
+            _ if response.status >= 500 => Err(Failure::Sent(unreachable(format!(
+                "HTTP {}",
+                response.status
+            )))),
+            _ => Err(Failure::Final(unreachable(format!(
+                "HTTP {}",
+                response.status
+            )))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::Request;
+    use crate::keys::test_support::ABANDON_ADDRESS;
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    /// In-process node answering successive connections with `replies`
+    /// (status and body; status 0 closes without answering), returning the
+    /// JSON-RPC requests it received
+    fn mock_node(replies: Vec<(u16, String)>) -> (RpcClient, JoinHandle<Vec<Value>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/rpc", listener.local_addr().unwrap());
+        let node = thread::spawn(move || {
+            let mut received = Vec::new();
+            for (status, body) in replies {
+                let (stream, _) = listener.accept().unwrap();
+                let request = Request::read_from(&mut BufReader::new(&stream)).unwrap();
+                assert_eq!(
+                    (request.method.as_str(), request.path.as_str()),
+                    ("POST", "/rpc")
+                );
+                received.push(serde_json::from_slice(&request.body).unwrap());
+                if status != 0 {
+                    let body = body.into_bytes();
+                    Response { status, body }.write_to(&mut &stream).unwrap();
+                }
+            }
+            received
+        });
+        let client = RpcClient::new(&url)
+            .unwrap()
+            .with_timeout(Duration::from_secs(2));
+        (
+            RpcClient {
+                retry_delay: Duration::from_millis(10),
+                ..client
+            },
+            node,
+        )
+    }
+
+    fn transfer(from: &str) -> SignedTransaction {
+        SignedTransaction {
+            from: from.to_string(),
+            to: "bb".repeat(32),
+            amount: 5,
+            nonce: 7,
+            fee: 1,
+            signature: "00".repeat(64),
+        }
+    }
+
+    fn result(value: Value) -> (u16, String) {
+        raw_result(&value.to_string())
+    }
+
+    fn raw_result(json: &str) -> (u16, String) {
+        let body = format!(r#"{{"jsonrpc": "2.0", "id": 1, "result": {}}}"#, json);
+        (200, body)
+    }
+
+    #[test]
+    fn test_methods() {
+        let address = ABANDON_ADDRESS;
+        let (client, node) = mock_node(vec![
+            result(json!(1_500_000_000_000_000_000u128)),
+            result(json!("340282366920938463463374607431768211455")),
+            raw_result("340282366920938463463374607431768211455"),
+            result(json!(7)),
+            result(json!(1234)),
+            result(json!("ab".repeat(32))),
+            result(
+                json!({"status": "confirmed", "block_number": 1230, "amount": "5", "fee": 1, "extra": true}),
+            ),
+            result(Value::Null),
+        ]);
+
+        assert_eq!(client.balance(address).unwrap(), 1_500_000_000_000_000_000);
+        assert_eq!(client.balance(address).unwrap(), u128::MAX);
+        assert_eq!(client.balance(address).unwrap(), u128::MAX);
+        assert_eq!(client.nonce(address).unwrap(), 7);
+        assert_eq!(client.block_number().unwrap(), 1234);
+
+        let tx = transfer(address);
+        assert_eq!(client.submit_transaction(&tx).unwrap(), "ab".repeat(32));
+
+        let info = client.transaction(&"ab".repeat(32)).unwrap().unwrap();
+        assert_eq!(info.status.as_deref(), Some("confirmed"));
+        assert_eq!(
+            (info.block_number, info.amount, info.fee, info.nonce),
+            (Some(1230), Some(5), Some(1), None)
+        );
+        assert_eq!(client.transaction("unknown").unwrap(), None);
+
+        let requests = node.join().unwrap();
+        assert_eq!(
+            requests[0],
+            json!({"jsonrpc": "2.0", "id": 1, "method": "account_balance", "params": [address]})
+        );
+        assert_eq!(requests[4]["method"], "block_number");
+        assert_eq!(requests[4]["params"], json!([]));
+        // Submitted as the send_transaction.py wire string
+        assert_eq!(requests[5]["params"][0], tx.to_json().unwrap());
+    }
+
+    #[test]
+    fn test_errors_and_retries() {
+        let (client, node) = mock_node(vec![
+            // JSON-RPC errors are final, even with an HTTP error status
+            (
+                500,
+                json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32000, "message": "nonce too low"}})
+                    .to_string(),
+            ),
+            // Read-only calls are retried after 5xx and dropped connections
+            (503, "{}".to_string()),
+            (0, String::new()),
+            result(json!(9)),
+            // Submissions are not retried once sent
+            (0, String::new()),
+            (404, "{}".to_string()),
+            result(json!({"unexpected": "object"})),
+        ]);
+
+        match client.nonce("aa") {
+            Err(Error::Rpc { code, message }) => {
+                assert_eq!((code, message.as_str()), (-32000, "nonce too low"))
+            }
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(client.block_number().unwrap(), 9);
+
+        let tx = transfer(&"aa".repeat(32));
+        assert!(matches!(
+            client.submit_transaction(&tx),
+            Err(Error::NodeUnreachable(_))
+        ));
+        assert!(matches!(
+            client.block_number(),
+            Err(Error::NodeUnreachable(_))
+        ));
+        // Answered, but not with a block number
+        let error = client.block_number().unwrap_err();
+        assert_eq!((error.id(), error.exit_code()), ("encoding_error", 9));
+        assert_eq!(node.join().unwrap().len(), 7);
+
+        // Nothing listening: every attempt fails to connect
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let client = RpcClient::new(&format!("127.0.0.1:{}", port))
+            .unwrap()
+            .with_retries(0);
+        let error = client.block_number().unwrap_err();
+        assert_eq!((error.id(), error.exit_code()), ("node_unreachable", 11));
+
+        // Connected but never answered
+        let silent = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = RpcClient::new(&format!("{}", silent.local_addr().unwrap()))
+            .unwrap()
+            .with_timeout(Duration::from_millis(200))
+            .with_retries(0);
+        assert!(matches!(
+            client.block_number(),
+            Err(Error::NodeUnreachable(_))
+        ));
+    }
+}
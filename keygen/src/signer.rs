@@ -0,0 +1,612 @@
+//! Remote signer: KeepBox keys behind a local HTTP/JSON API
+//!
+//! `boundless-signer serve` unlocks its KeepBoxes once and signs for miners
+//! and payout bots that should never hold a mnemonic. Every transaction is
+//! checked against the key's [`Policy`] and the [`ProtectionDb`], which is
+//! saved before the signature is returned, and every decision is appended to
+//! the log as one JSON line.
+//!
+//! ```text
+//! GET  /keys              -> {"keys":[{"address":..,"key_type":..,"public_key":..,"label":..,"policy":{..}}]}
+//! POST /sign/transaction  {"from":..,"to":..,"amount":..,"nonce":..,"fee":..}
+//!                         -> {"transaction":{..,"signature":..},"repeat":false}
+//! POST /sign/message      {"address":..,"message":"<hex>"}
+//!                         -> {"address":..,"public_key":..,"key_type":..,"prefixed":true,"signature":..}
+//! ```
+//!
+//! Failures are answered with `{"error":{"id":..,"message":..}}`, the id
+//! being the [`Error::id`] of the failure; a refusal by policy is a 403 with
+//! id `policy_violation`.
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use zeroize::Zeroizing;
+
+use crate::address::Address;
+use crate::error::{Error, Result};
+use crate::http::{Request, Response};
+use crate::keys::{HeldKey, KeyRing, Keypair};
+use crate::message::sign_message;
+use crate::policy::{Approval, Policy, ProtectionDb};
+use crate::tx::{format_amount, SignedTransaction, Transaction};
+
+/// Listen address when the configuration names none
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:9960";
+
+/// How long a client may take to send its request
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// `POST /sign/transaction` answer. Serialized as is rather than through a
+/// [`Value`], which cannot hold amounts above 64 bits.
+#[derive(Debug, Serialize)]
+struct SignedReply {
+    transaction: SignedTransaction,
+    repeat: bool,
+}
+
+/// `boundless-signer` configuration file (JSON). Relative paths are relative
+/// to the file's directory.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Address to listen on, default [`DEFAULT_LISTEN`]
+    #[serde(default)]
+    pub listen: Option<String>,
+
+    /// KeepBoxes unlocked at start; every wallet of a vault is served
+    pub keepboxes: Vec<PathBuf>,
+
+    /// Signing history, created if missing
+    pub protection_db: PathBuf,
+
+    /// Decision log (JSON lines); stderr if absent
+    #[serde(default)]
+    pub log_file: Option<PathBuf>,
+
+    /// File holding a token clients must send as `Authorization: Bearer`
+    #[serde(default)]
+    pub token_file: Option<PathBuf>,
+
+    /// Policy per key address
+    pub policies: BTreeMap<String, Policy>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let json =
+            fs::read_to_string(path).map_err(|e| Error::io("Failed to read signer config", e))?;
+        let mut config: Config = serde_json::from_str(&json)
+            .map_err(|e| Error::Encoding(format!("Failed to parse signer config: {}", e)))?;
+
+        let base = path.parent().unwrap_or(Path::new(""));
+        let resolve = |p: &mut PathBuf| {
+            if p.is_relative() {
+                *p = base.join(&*p);
+            }
+        };
+        config.keepboxes.iter_mut().for_each(resolve);
+        resolve(&mut config.protection_db);
+        config.log_file.iter_mut().for_each(resolve);
+        config.token_file.iter_mut().for_each(resolve);
+
+        let mut policies = BTreeMap::new();
+        for (address, mut policy) in std::mem::take(&mut config.policies) {
+            policy.validate()?;
+            policies.insert(address.parse::<Address>()?.to_hex(), policy);
+        }
+        config.policies = policies;
+        Ok(config)
+    }
+
+    /// The listen address, or [`DEFAULT_LISTEN`]
+    pub fn listen(&self) -> &str {
+        self.listen.as_deref().unwrap_or(DEFAULT_LISTEN)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MessageRequest {
+    address: String,
+    /// Message bytes (hex)
+    message: String,
+}
+
+/// Keys, policies and signing history of a running signer
+pub struct RemoteSigner {
+    keys: KeyRing,
+    policies: BTreeMap<String, Policy>,
+    db: ProtectionDb,
+    db_path: PathBuf,
+    token: Option<Zeroizing<String>>,
+    log: Box<dyn Write + Send>,
+}
+
+impl RemoteSigner {
+    /// Load the protection database and token and open the log named by
+    /// `config`; keys are added through [`RemoteSigner::keys_mut`]
+    pub fn new(config: &Config) -> Result<Self> {
+        let db = ProtectionDb::load(&config.protection_db)?;
+
+        let token = match &config.token_file {
+            Some(path) => {
+                let token = Zeroizing::new(
+                    fs::read_to_string(path)
+                        .map_err(|e| Error::io("Failed to read token file", e))?,
+                );
+                if token.trim().is_empty() {
+                    return Err(Error::InvalidArgument(format!(
+                        "token file {} is empty",
+                        path.display()
+                    )));
+                }
+                Some(Zeroizing::new(token.trim().to_string()))
+            }
+            None => None,
+        };
+
+        let log: Box<dyn Write + Send> = match &config.log_file {
+            Some(path) => {
+                let mut options = OpenOptions::new();
+                options.create(true).append(true);
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::OpenOptionsExt;
+                    options.mode(0o600);
+                }
+                Box::new(
+                    options
+                        .open(path)
+                        .map_err(|e| Error::io("Failed to open signer log", e))?,
+                )
+            }
+            None => Box::new(io::stderr()),
+        };
+
+        Ok(RemoteSigner {
+            keys: KeyRing::new(),
+            policies: config.policies.clone(),
+            db,
+            db_path: config.protection_db.clone(),
+            token,
+            log,
+        })
+    }
+
+    pub fn keys(&self) -> &KeyRing {
+        &self.keys
+    }
+
+    /// The keys to serve; see [`KeyRing::lock_memory`] once they are added
+    pub fn keys_mut(&mut self) -> &mut KeyRing {
+        &mut self.keys
+    }
+
+    /// The policy of the key for `address`; a key without one signs nothing
+    pub fn policy(&self, address: &str) -> Option<&Policy> {
+        self.policies.get(&address.to_ascii_lowercase())
+    }
+
+    /// Answer one request from `peer`, logging the decision. A decision
+    /// that cannot be logged is answered with an error instead.
+    pub fn handle(&mut self, request: &Request, peer: &str) -> Response {
+        let mut entry = Map::new();
+        entry.insert("time".into(), json!(chrono::Utc::now().to_rfc3339()));
+        entry.insert("peer".into(), json!(peer));
+        entry.insert("method".into(), json!(request.method));
+        entry.insert("path".into(), json!(request.path));
+
+        let (status, result) = if !self.authorized(request) {
+            (401, Err(None))
+        } else {
+            match (request.method.as_str(), request.path.as_str()) {
+                ("GET", "/keys") => (
+                    200,
+                    Ok(Response::json(200, &json!({ "keys": self.key_list() }))),
+                ),
+                ("POST", "/sign/transaction") => (
+                    200,
+                    self.sign_transaction(&request.body, &mut entry)
+                        .map(|signed| Response::json(200, &signed))
+                        .map_err(Some),
+                ),
+                ("POST", "/sign/message") => (
+                    200,
+                    self.sign_message(&request.body, &mut entry)
+                        .map(|body| Response::json(200, &body))
+                        .map_err(Some),
+                ),
+                (_, "/keys" | "/sign/transaction" | "/sign/message") => (405, Err(None)),
+                _ => (404, Err(None)),
+            }
+        };
+
+        let response = match result {
+            Ok(response) => {
+                entry.insert("decision".into(), json!("allowed"));
+                response
+            }
+            Err(Some(error)) => {
+                let decision = match error {
+                    Error::PolicyViolation(_) => "refused",
+                    _ => "error",
+                };
+                entry.insert("decision".into(), json!(decision));
+                entry.insert("error".into(), json!(error.id()));
+                entry.insert("reason".into(), json!(error.to_string()));
+                Response::error(&error)
+            }
+            Err(None) => {
+                let (id, message) = match status {
+                    401 => ("unauthorized", "missing or wrong bearer token"),
+                    405 => ("method_not_allowed", "method not allowed"),
+                    _ => ("not_found", "no such endpoint"),
+                };
+                entry.insert("decision".into(), json!("error"));
+                entry.insert("error".into(), json!(id));
+                Response::json(
+                    status,
+                    &json!({ "error": { "id": id, "message": message } }),
+                )
+            }
+        };
+
+        match self.log(&Value::Object(entry)) {
+            Ok(()) => response,
+            Err(e) => Response::error(&e),
+        }
+    }
+
+    /// Answer requests on `listener` until accepting fails
+    pub fn serve(&mut self, listener: &TcpListener) -> Result<()> {
+        for stream in listener.incoming() {
+            match stream {
+                // A misbehaving client only loses its own connection
+                Ok(stream) => {
+                    let _ = self.connection(stream);
+                }
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::Interrupted | io::ErrorKind::ConnectionAborted
+                    ) => {}
+                Err(e) => return Err(Error::io("Failed to accept signer connection", e)),
+            }
+        }
+        Ok(())
+    }
+
+    fn connection(&mut self, stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+        let peer = stream
+            .peer_addr()
+            .map(|addr| addr.to_string())
+            .unwrap_or_default();
+
+        let response = match Request::read_from(&mut BufReader::new(&stream)) {
+            Ok(request) => self.handle(&request, &peer),
+            Err(e) => {
+                let entry = json!({
+                    "time": chrono::Utc::now().to_rfc3339(),
+                    "peer": peer,
+                    "decision": "error",
+                    "error": e.id(),
+                    "reason": e.to_string(),
+                });
+                let _ = self.log(&entry);
+                Response::error(&e)
+            }
+        };
+        response.write_to(&mut &stream)
+    }
+
+    fn authorized(&self, request: &Request) -> bool {
+        let Some(token) = &self.token else {
+            return true;
+        };
+        let given = request
+            .header("authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        // Constant time, so the token cannot be guessed byte by byte
+        given.len() == token.len()
+            && given
+                .bytes()
+                .zip(token.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    fn key_list(&self) -> Vec<Value> {
+        self.keys
+            .iter()
+            .map(|key| {
+                let mut value = json!(key);
+                value["policy"] = json!(self.policy(&key.address));
+                value
+            })
+            .collect()
+    }
+
+    fn key(&self, address: &str) -> Result<(&HeldKey, &Keypair)> {
+        self.keys
+            .get(address)
+            .ok_or_else(|| Error::EntryNotFound(address.to_string()))
+    }
+
+    fn sign_transaction(
+        &mut self,
+        body: &[u8],
+        entry: &mut Map<String, Value>,
+    ) -> Result<SignedReply> {
+        let tx: Transaction = serde_json::from_slice(body)
+            .map_err(|e| Error::Encoding(format!("Failed to parse transaction JSON: {}", e)))?;
+        entry.insert("address".into(), json!(tx.from));
+        entry.insert("to".into(), json!(tx.to));
+        entry.insert("amount".into(), json!(format_amount(tx.amount)));
+        entry.insert("fee".into(), json!(format_amount(tx.fee)));
+        entry.insert("nonce".into(), json!(tx.nonce));
+        let tx = Transaction::new(&tx.from, &tx.to, tx.amount, tx.nonce, tx.fee)?;
+
+        let (_, keypair) = self.key(&tx.from)?;
+        let policy = self
+            .policy(&tx.from)
+            .ok_or_else(|| Error::PolicyViolation(format!("no policy for key {}", tx.from)))?;
+        let day = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        let approval = self.db.check(policy, &tx, &day)?;
+
+        let signed = tx.sign(keypair)?;
+        if approval == Approval::New {
+            // Recorded on disk before the signature leaves the process
+            let mut db = self.db.clone();
+            db.record(&tx, &day);
+            db.save(&self.db_path)?;
+            self.db = db;
+        }
+        entry.insert("repeat".into(), json!(approval == Approval::Repeat));
+
+        Ok(SignedReply {
+            transaction: signed,
+            repeat: approval == Approval::Repeat,
+        })
+    }
+
+    fn sign_message(&mut self, body: &[u8], entry: &mut Map<String, Value>) -> Result<Value> {
+        let request: MessageRequest = serde_json::from_slice(body)
+            .map_err(|e| Error::Encoding(format!("Failed to parse message request: {}", e)))?;
+        entry.insert("address".into(), json!(request.address));
+        let message = hex::decode(request.message.trim())
+            .map_err(|e| Error::Encoding(format!("Invalid message hex: {}", e)))?;
+        entry.insert("message_len".into(), json!(message.len()));
+
+        let (key, keypair) = self.key(&request.address)?;
+        if !self
+            .policy(&request.address)
+            .is_some_and(|policy| policy.allow_messages)
+        {
+            return Err(Error::PolicyViolation(format!(
+                "message signing is not allowed for key {}",
+                request.address
+            )));
+        }
+
+        let signature = sign_message(keypair, &message, true)?;
+        Ok(json!({
+            "address": key.address,
+            "public_key": key.public_key,
+            "key_type": key.key_type,
+            "prefixed": true,
+            "signature": hex::encode(signature),
+        }))
+    }
+
+    fn log(&mut self, entry: &Value) -> Result<()> {
+        writeln!(self.log, "{}", entry)
+            .and_then(|_| self.log.flush())
+            .map_err(|e| Error::io("Failed to write signer log", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::test_support::{abandon_keypair, ABANDON_ADDRESS as ADDRESS};
+    use crate::keys::KeyType;
+    use crate::message::verify_message;
+    use crate::tx::{parse_amount, SignedTransaction};
+    use std::io::{Read, Write};
+
+    fn serving(dir: &Path, policy: &str, token: bool) -> RemoteSigner {
+        if token {
+            fs::write(dir.join("token"), "s3cret\n").unwrap();
+        }
+        fs::write(
+            dir.join("signer.json"),
+            format!(
+                r#"{{"keepboxes": [], "protection_db": "protection.json", "log_file": "signer.log",
+                    {} "policies": {{"{}": {}}}}}"#,
+                if token {
+                    r#""token_file": "token","#
+                } else {
+                    ""
+                },
+                ADDRESS.to_uppercase(),
+                policy
+            ),
+        )
+        .unwrap();
+        let config = Config::load(&dir.join("signer.json")).unwrap();
+        assert_eq!(config.protection_db, dir.join("protection.json"));
+
+        let mut signer = RemoteSigner::new(&config).unwrap();
+        signer
+            .keys_mut()
+            .add(abandon_keypair(), None, Path::new("w.keepbox"))
+            .unwrap();
+        signer
+    }
+
+    fn post(path: &str, body: &Value) -> Request {
+        Request {
+            method: "POST".to_string(),
+            path: path.to_string(),
+            headers: Vec::new(),
+            body: serde_json::to_vec(body).unwrap(),
+        }
+    }
+
+    fn transfer(amount: &str, nonce: u64) -> Value {
+        json!(Transaction::new(
+            ADDRESS,
+            &"bb".repeat(32),
+            parse_amount(amount).unwrap(),
+            nonce,
+            0
+        )
+        .unwrap())
+    }
+
+    fn body(response: &Response) -> Value {
+        serde_json::from_slice(&response.body).unwrap()
+    }
+
+    #[test]
+    fn test_policies_and_protection() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut signer = serving(dir.path(), r#"{"max_amount": "2"}"#, false);
+
+        let keys = signer.handle(
+            &Request {
+                method: "GET".into(),
+                ..post("/keys", &json!({}))
+            },
+            "t",
+        );
+        assert_eq!(body(&keys)["keys"][0]["address"], ADDRESS);
+        assert_eq!(body(&keys)["keys"][0]["policy"]["max_amount"], "2.0");
+
+        let signed = signer.handle(&post("/sign/transaction", &transfer("1", 4)), "t");
+        assert_eq!(signed.status, 200);
+        let tx: SignedTransaction =
+            serde_json::from_value(body(&signed)["transaction"].clone()).unwrap();
+        let public_key = hex::decode(&signer.keys().list()[0].public_key).unwrap();
+        tx.verify(KeyType::Ed25519, &public_key).unwrap();
+        assert_eq!(body(&signed)["repeat"], false);
+
+        // The same request again is answered; a competing one is not
+        let again = signer.handle(&post("/sign/transaction", &transfer("1", 4)), "t");
+        assert_eq!(
+            (again.status, body(&again)["repeat"].clone()),
+            (200, json!(true))
+        );
+        let competing = signer.handle(&post("/sign/transaction", &transfer("1.5", 4)), "t");
+        assert_eq!(competing.status, 403);
+        assert_eq!(body(&competing)["error"]["id"], "policy_violation");
+        assert_eq!(
+            signer
+                .handle(&post("/sign/transaction", &transfer("3", 5)), "t")
+                .status,
+            403
+        );
+
+        // The history survives a restart
+        let mut restarted = serving(dir.path(), r#"{"max_amount": "2"}"#, false);
+        assert_eq!(
+            restarted
+                .handle(&post("/sign/transaction", &transfer("1.5", 4)), "t")
+                .status,
+            403
+        );
+        assert_eq!(
+            restarted
+                .handle(&post("/sign/transaction", &transfer("1.5", 5)), "t")
+                .status,
+            200
+        );
+
+        // Messages need allow_messages, and unknown keys are refused
+        let message = json!({"address": ADDRESS, "message": "68656c6c6f"});
+        assert_eq!(
+            restarted
+                .handle(&post("/sign/message", &message), "t")
+                .status,
+            403
+        );
+        let foreign = json!({"address": "cc".repeat(32), "message": "00"});
+        assert_eq!(
+            restarted
+                .handle(&post("/sign/message", &foreign), "t")
+                .status,
+            404
+        );
+        assert_eq!(
+            restarted.handle(&post("/nope", &json!({})), "t").status,
+            404
+        );
+        assert_eq!(
+            restarted
+                .handle(&post("/sign/transaction", &json!({"from": 1})), "t")
+                .status,
+            400
+        );
+
+        let log = fs::read_to_string(dir.path().join("signer.log")).unwrap();
+        let decisions: Vec<Value> = log
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(decisions.len(), 11);
+        assert_eq!(decisions[3]["decision"], "refused");
+        assert_eq!(decisions[3]["nonce"], 4);
+        assert_eq!(decisions[3]["amount"], "1.5");
+    }
+
+    #[test]
+    fn test_serve_messages_with_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut signer = serving(dir.path(), r#"{"allow_messages": true}"#, true);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = std::thread::spawn(move || {
+            for _ in 0..2 {
+                let (stream, _) = listener.accept().unwrap();
+                signer.connection(stream).unwrap();
+            }
+        });
+
+        let request = |auth: &str| {
+            let body = json!({"address": ADDRESS, "message": "68656c6c6f"}).to_string();
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(
+                stream,
+                "POST /sign/message HTTP/1.1\r\nHost: x\r\n{}Content-Length: {}\r\n\r\n{}",
+                auth,
+                body.len(),
+                body
+            )
+            .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        assert!(request("Authorization: Bearer wrong\r\n").starts_with("HTTP/1.1 401"));
+        let response = request("Authorization: Bearer s3cret\r\n");
+        server.join().unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        let body: Value = serde_json::from_str(response.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        let signature = hex::decode(body["signature"].as_str().unwrap()).unwrap();
+        let public_key = hex::decode(body["public_key"].as_str().unwrap()).unwrap();
+        verify_message(&public_key, ADDRESS, b"hello", &signature, true).unwrap();
+    }
+}
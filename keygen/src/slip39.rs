@@ -0,0 +1,977 @@
+//! SLIP-0039 Shamir secret sharing for mnemonic backups
+//!
+//! Splits a master secret into share mnemonics so that any `threshold` of
+//! them rebuild it, optionally in two levels: the secret is split across
+//! groups, and each group's part across its members. Every share carries
+//! an RS1024 checksum, and a digest inside the shares catches combinations
+//! of shares that do not belong together.
+//!
+//! The CLI shares the BIP39 entropy of a wallet's mnemonic, so combining
+//! gives back the exact same BIP39 mnemonic. Other SLIP-0039 wallets use
+//! the master secret as a seed directly, so they would derive different
+//! keys from these shares.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::error::{Error, Result};
+
+/// Most groups, and most members per group
+pub const MAX_SHARE_COUNT: u8 = 16;
+
+/// Default PBKDF2 cost: 10000 << e iterations in total
+pub const DEFAULT_ITERATION_EXPONENT: u8 = 1;
+
+const RADIX_BITS: usize = 10;
+const ID_EXP_WORDS: usize = 2;
+const GROUP_WORDS: usize = 2;
+const CHECKSUM_WORDS: usize = 3;
+const MIN_SECRET_BYTES: usize = 16;
+const MIN_WORDS: usize =
+    ID_EXP_WORDS + GROUP_WORDS + (MIN_SECRET_BYTES * 8).div_ceil(RADIX_BITS) + CHECKSUM_WORDS;
+
+const DIGEST_INDEX: u8 = 254;
+const SECRET_INDEX: u8 = 255;
+const DIGEST_BYTES: usize = 4;
+
+const ROUND_COUNT: u8 = 4;
+const BASE_ROUND_ITERATIONS: u32 = 2500;
+
+/// One share, decoded from or encoded to its mnemonic
+#[derive(Clone)]
+pub struct Share {
+    /// Random identifier common to all shares of one split (15 bits)
+    pub identifier: u16,
+    /// The identifier is not mixed into the encryption, so more shares can
+    /// be issued later for the same secret
+    pub extendable: bool,
+    pub iteration_exponent: u8,
+    pub group_index: u8,
+    pub group_threshold: u8,
+    pub group_count: u8,
+    pub member_index: u8,
+    pub member_threshold: u8,
+    pub value: Zeroizing<Vec<u8>>,
+}
+
+impl Share {
+    /// Decode a share mnemonic. Words may be abbreviated to their first
+    /// four letters, which are unique in the SLIP-0039 wordlist.
+    pub fn parse(mnemonic: &str) -> Result<Self> {
+        let words = mnemonic
+            .split_whitespace()
+            .map(word_index)
+            .collect::<Result<Vec<u16>>>()
+            .map(Zeroizing::new)?;
+        if words.len() < MIN_WORDS {
+            return Err(Error::InvalidShare(format!(
+                "a share has at least {} words, got {}",
+                MIN_WORDS,
+                words.len()
+            )));
+        }
+
+        let extendable = (words[1] >> 4) & 1 == 1;
+        if !checksum_valid(customization(extendable), &words) {
+            return Err(Error::InvalidShare(
+                "checksum mismatch; check the words for typos".to_string(),
+            ));
+        }
+
+        let value_words = &words[ID_EXP_WORDS + GROUP_WORDS..words.len() - CHECKSUM_WORDS];
+        let padding = (RADIX_BITS * value_words.len()) % 16;
+        if padding > 8 {
+            return Err(Error::InvalidShare("invalid share length".to_string()));
+        }
+        let value = words_to_bytes(value_words, padding)?;
+        if value.len() < MIN_SECRET_BYTES {
+            return Err(Error::InvalidShare("share value is too short".to_string()));
+        }
+
+        let group_count = (((words[2] & 3) << 2) | (words[3] >> 8)) as u8 + 1;
+        let group_threshold = ((words[2] >> 2) & 0xf) as u8 + 1;
+        if group_threshold > group_count {
+            return Err(Error::InvalidShare(
+                "group threshold exceeds the number of groups".to_string(),
+            ));
+        }
+
+        Ok(Share {
+            identifier: (words[0] << 5) | (words[1] >> 5),
+            extendable,
+            iteration_exponent: (words[1] & 0xf) as u8,
+            group_index: (words[2] >> 6) as u8,
+            group_threshold,
+            group_count,
+            member_index: ((words[3] >> 4) & 0xf) as u8,
+            member_threshold: (words[3] & 0xf) as u8 + 1,
+            value,
+        })
+    }
+
+    /// Encode the share as space separated words
+    pub fn to_mnemonic(&self) -> Zeroizing<String> {
+        let mut words = Zeroizing::new(vec![
+            self.identifier >> 5,
+            ((self.identifier & 0x1f) << 5)
+                | ((self.extendable as u16) << 4)
+                | self.iteration_exponent as u16,
+            ((self.group_index as u16) << 6)
+                | (((self.group_threshold - 1) as u16) << 2)
+                | ((self.group_count - 1) as u16 >> 2),
+            ((((self.group_count - 1) & 3) as u16) << 8)
+                | ((self.member_index as u16) << 4)
+                | (self.member_threshold - 1) as u16,
+        ]);
+        words.extend(bytes_to_words(&self.value).iter());
+
+        let checksum = rs1024_checksum(customization(self.extendable), &words);
+        words.extend(checksum);
+
+        let mut mnemonic = Zeroizing::new(String::new());
+        for (i, &word) in words.iter().enumerate() {
+            if i > 0 {
+                mnemonic.push(' ');
+            }
+            mnemonic.push_str(WORDLIST[word as usize]);
+        }
+        mnemonic
+    }
+
+    /// Shares of one split agree on these; `combine` rejects mixtures
+    fn common_parameters(&self) -> (u16, bool, u8, u8, u8) {
+        (
+            self.identifier,
+            self.extendable,
+            self.iteration_exponent,
+            self.group_threshold,
+            self.group_count,
+        )
+    }
+}
+
+impl fmt::Debug for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Share")
+            .field("identifier", &self.identifier)
+            .field("group_index", &self.group_index)
+            .field("member_index", &self.member_index)
+            .field("value", &"<redacted>")
+            .finish_non_exhaustive()
+    }
+}
+
+/// A group of `count` shares of which `threshold` are needed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GroupSpec {
+    pub threshold: u8,
+    pub count: u8,
+}
+
+impl GroupSpec {
+    fn validate(&self) -> Result<()> {
+        if self.threshold == 0 || self.threshold > self.count {
+            return Err(Error::InvalidArgument(format!(
+                "group {} needs a threshold between 1 and its share count",
+                self
+            )));
+        }
+        if self.count > MAX_SHARE_COUNT {
+            return Err(Error::InvalidArgument(format!(
+                "group {} has more than {} shares",
+                self, MAX_SHARE_COUNT
+            )));
+        }
+        if self.threshold == 1 && self.count > 1 {
+            return Err(Error::InvalidArgument(format!(
+                "group {} would hand out copies of the same share; use 1-of-1",
+                self
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for GroupSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-of-{}", self.threshold, self.count)
+    }
+}
+
+impl FromStr for GroupSpec {
+    type Err = Error;
+
+    /// Parse `T-of-N` or `T/N`
+    fn from_str(s: &str) -> Result<Self> {
+        let (threshold, count) = s
+            .split_once("-of-")
+            .or_else(|| s.split_once('/'))
+            .ok_or_else(|| {
+                Error::InvalidArgument(format!("expected THRESHOLD-of-COUNT, got '{}'", s))
+            })?;
+        let number = |n: &str| {
+            n.trim()
+                .parse::<u8>()
+                .map_err(|_| Error::InvalidArgument(format!("invalid share group '{}'", s)))
+        };
+        let spec = GroupSpec {
+            threshold: number(threshold)?,
+            count: number(count)?,
+        };
+        spec.validate()?;
+        Ok(spec)
+    }
+}
+
+/// Split `master_secret` into groups of shares. Any `group_threshold` of
+/// the groups, each with at least its own threshold of shares, recover the
+/// secret; `passphrase` (printable ASCII, may be empty) is then needed to
+/// decrypt it. Returns one list of shares per group.
+pub fn split(
+    master_secret: &[u8],
+    passphrase: &str,
+    group_threshold: u8,
+    groups: &[GroupSpec],
+    iteration_exponent: u8,
+) -> Result<Vec<Vec<Share>>> {
+    if master_secret.len() < MIN_SECRET_BYTES || !master_secret.len().is_multiple_of(2) {
+        return Err(Error::InvalidArgument(format!(
+            "the secret must be an even number of bytes, at least {}",
+            MIN_SECRET_BYTES
+        )));
+    }
+    if groups.is_empty() || groups.len() > MAX_SHARE_COUNT as usize {
+        return Err(Error::InvalidArgument(format!(
+            "between 1 and {} groups are supported",
+            MAX_SHARE_COUNT
+        )));
+    }
+    if group_threshold == 0 || group_threshold as usize > groups.len() {
+        return Err(Error::InvalidArgument(format!(
+            "group threshold must be between 1 and {}",
+            groups.len()
+        )));
+    }
+    if iteration_exponent > 0xf {
+        return Err(Error::InvalidArgument(
+            "iteration exponent must be at most 15".to_string(),
+        ));
+    }
+    for group in groups {
+        group.validate()?;
+    }
+    check_passphrase(passphrase)?;
+
+    let mut id = [0u8; 2];
+    getrandom::getrandom(&mut id).map_err(|e| Error::Entropy(e.to_string()))?;
+    let identifier = u16::from_be_bytes(id) & 0x7fff;
+    let extendable = true;
+
+    let encrypted = feistel(
+        master_secret,
+        passphrase,
+        iteration_exponent,
+        identifier,
+        extendable,
+        false,
+    );
+    let group_secrets = split_secret(group_threshold, groups.len() as u8, &encrypted)?;
+
+    groups
+        .iter()
+        .zip(group_secrets)
+        .map(|(group, (group_index, group_secret))| {
+            let members = split_secret(group.threshold, group.count, &group_secret)?;
+            Ok(members
+                .into_iter()
+                .map(|(member_index, value)| Share {
+                    identifier,
+                    extendable,
+                    iteration_exponent,
+                    group_index,
+                    group_threshold,
+                    group_count: groups.len() as u8,
+                    member_index,
+                    member_threshold: group.threshold,
+                    value,
+                })
+                .collect())
+        })
+        .collect()
+}
+
+/// Recover the master secret from shares of one split, decrypting it with
+/// `passphrase`. Groups without enough shares are ignored as long as
+/// enough other groups are complete.
+pub fn combine(shares: &[Share], passphrase: &str) -> Result<Zeroizing<Vec<u8>>> {
+    let first = shares
+        .first()
+        .ok_or_else(|| Error::InvalidShare("no shares given".to_string()))?;
+    check_passphrase(passphrase)?;
+
+    let mut groups: BTreeMap<u8, BTreeMap<u8, &Share>> = BTreeMap::new();
+    for share in shares {
+        if share.common_parameters() != first.common_parameters() {
+            return Err(Error::InvalidShare(
+                "shares belong to different splits".to_string(),
+            ));
+        }
+        if share.value.len() != first.value.len() {
+            return Err(Error::InvalidShare(
+                "shares have different lengths".to_string(),
+            ));
+        }
+
+        let members = groups.entry(share.group_index).or_default();
+        if let Some(other) = members.values().next() {
+            if other.member_threshold != share.member_threshold {
+                return Err(Error::InvalidShare(format!(
+                    "shares of group {} disagree on its threshold",
+                    share.group_index + 1
+                )));
+            }
+        }
+        if let Some(other) = members.insert(share.member_index, share) {
+            if other.value != share.value {
+                return Err(Error::InvalidShare(format!(
+                    "two different shares claim to be share {} of group {}",
+                    share.member_index + 1,
+                    share.group_index + 1
+                )));
+            }
+        }
+    }
+
+    let mut group_secrets = Vec::new();
+    let mut missing = Vec::new();
+    for (&group_index, members) in &groups {
+        let threshold = members.values().next().map_or(0, |s| s.member_threshold);
+        if members.len() < threshold as usize {
+            if first.group_count == 1 {
+                return Err(Error::InvalidShare(format!(
+                    "{} of the {} shares needed were given",
+                    members.len(),
+                    threshold
+                )));
+            }
+            missing.push(format!(
+                "group {} has {} of {} shares",
+                group_index + 1,
+                members.len(),
+                threshold
+            ));
+            continue;
+        }
+        let points: Vec<(u8, &[u8])> = members
+            .iter()
+            .take(threshold as usize)
+            .map(|(&index, share)| (index, share.value.as_slice()))
+            .collect();
+        group_secrets.push((group_index, recover_secret(threshold, &points)?));
+    }
+
+    if group_secrets.len() < first.group_threshold as usize {
+        let mut message = format!(
+            "{} of {} required groups are complete",
+            group_secrets.len(),
+            first.group_threshold
+        );
+        if !missing.is_empty() {
+            message.push_str(&format!(" ({})", missing.join(", ")));
+        }
+        return Err(Error::InvalidShare(message));
+    }
+
+    let points: Vec<(u8, &[u8])> = group_secrets
+        .iter()
+        .take(first.group_threshold as usize)
+        .map(|(index, secret)| (*index, secret.as_slice()))
+        .collect();
+    let encrypted = recover_secret(first.group_threshold, &points)?;
+
+    Ok(feistel(
+        &encrypted,
+        passphrase,
+        first.iteration_exponent,
+        first.identifier,
+        first.extendable,
+        true,
+    ))
+}
+
+// ===== Secret sharing over GF(256) =====
+
+/// Split `secret` into `count` shares with x = 0..count. Below the
+/// threshold, x = 254 holds a digest of the secret and x = 255 the secret.
+fn split_secret(threshold: u8, count: u8, secret: &[u8]) -> Result<Vec<(u8, Zeroizing<Vec<u8>>)>> {
+    if threshold == 1 {
+        return Ok((0..count)
+            .map(|x| (x, Zeroizing::new(secret.to_vec())))
+            .collect());
+    }
+
+    let random_bytes = |len: usize| -> Result<Zeroizing<Vec<u8>>> {
+        let mut bytes = Zeroizing::new(vec![0u8; len]);
+        getrandom::getrandom(&mut bytes).map_err(|e| Error::Entropy(e.to_string()))?;
+        Ok(bytes)
+    };
+
+    let mut shares = Vec::new();
+    for x in 0..threshold - 2 {
+        shares.push((x, random_bytes(secret.len())?));
+    }
+
+    let random_part = random_bytes(secret.len() - DIGEST_BYTES)?;
+    let mut digest = Zeroizing::new(share_digest(&random_part, secret).to_vec());
+    digest.extend_from_slice(&random_part);
+    let secret = Zeroizing::new(secret.to_vec());
+
+    let mut base: Vec<(u8, &[u8])> = shares.iter().map(|(x, v)| (*x, v.as_slice())).collect();
+    base.push((DIGEST_INDEX, &digest));
+    base.push((SECRET_INDEX, &secret));
+
+    let mut interpolated = Vec::new();
+    for x in threshold - 2..count {
+        interpolated.push((x, interpolate(&base, x)?));
+    }
+    shares.extend(interpolated);
+    Ok(shares)
+}
+
+/// Interpolate the secret from `threshold` points and check its digest
+fn recover_secret(threshold: u8, points: &[(u8, &[u8])]) -> Result<Zeroizing<Vec<u8>>> {
+    if threshold == 1 {
+        return Ok(Zeroizing::new(points[0].1.to_vec()));
+    }
+
+    let secret = interpolate(points, SECRET_INDEX)?;
+    let digest = interpolate(points, DIGEST_INDEX)?;
+    if digest[..DIGEST_BYTES] != share_digest(&digest[DIGEST_BYTES..], &secret) {
+        return Err(Error::InvalidShare(
+            "share digest mismatch; the shares do not belong together".to_string(),
+        ));
+    }
+    Ok(secret)
+}
+
+fn share_digest(random_part: &[u8], secret: &[u8]) -> [u8; DIGEST_BYTES] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(random_part).expect("HMAC accepts any key length");
+    mac.update(secret);
+    let mut digest = [0u8; DIGEST_BYTES];
+    digest.copy_from_slice(&mac.finalize().into_bytes()[..DIGEST_BYTES]);
+    digest
+}
+
+/// Lagrange interpolation of the polynomial through `points` at `x`
+fn interpolate(points: &[(u8, &[u8])], x: u8) -> Result<Zeroizing<Vec<u8>>> {
+    if let Some((_, y)) = points.iter().find(|(xi, _)| *xi == x) {
+        return Ok(Zeroizing::new(y.to_vec()));
+    }
+
+    let len = points[0].1.len();
+    let mut result = Zeroizing::new(vec![0u8; len]);
+    for (i, &(xi, yi)) in points.iter().enumerate() {
+        // log of prod_{j != i} (x - xj) / (xi - xj); subtraction is xor
+        let mut basis_log = 0i32;
+        for (j, &(xj, _)) in points.iter().enumerate() {
+            if i != j {
+                if xi == xj {
+                    return Err(Error::InvalidShare("duplicate share index".to_string()));
+                }
+                basis_log += LOG[(x ^ xj) as usize] as i32 - LOG[(xi ^ xj) as usize] as i32;
+            }
+        }
+        let basis_log = basis_log.rem_euclid(255) as usize;
+
+        for (out, &y) in result.iter_mut().zip(yi) {
+            if y != 0 {
+                *out ^= EXP[(LOG[y as usize] as usize + basis_log) % 255];
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Powers of the generator 3 in GF(256) modulo x^8 + x^4 + x^3 + x + 1, and
+/// their discrete logarithms
+const fn gf256_tables() -> ([u8; 255], [u8; 256]) {
+    let mut exp = [0u8; 255];
+    let mut log = [0u8; 256];
+    let mut poly: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        exp[i] = poly as u8;
+        log[poly as usize] = i as u8;
+        poly = (poly << 1) ^ poly;
+        if poly & 0x100 != 0 {
+            poly ^= 0x11b;
+        }
+        i += 1;
+    }
+    (exp, log)
+}
+
+const EXP: [u8; 255] = gf256_tables().0;
+const LOG: [u8; 256] = gf256_tables().1;
+
+// ===== Passphrase encryption =====
+
+/// Four-round Feistel network keyed by PBKDF2-HMAC-SHA256 of the passphrase
+fn feistel(
+    input: &[u8],
+    passphrase: &str,
+    iteration_exponent: u8,
+    identifier: u16,
+    extendable: bool,
+    decrypt: bool,
+) -> Zeroizing<Vec<u8>> {
+    let half = input.len() / 2;
+    let mut left = Zeroizing::new(input[..half].to_vec());
+    let mut right = Zeroizing::new(input[half..].to_vec());
+
+    let mut salt = Vec::new();
+    if !extendable {
+        salt.extend_from_slice(b"shamir");
+        salt.extend_from_slice(&identifier.to_be_bytes());
+    }
+    let iterations = BASE_ROUND_ITERATIONS << iteration_exponent;
+
+    let mut rounds: Vec<u8> = (0..ROUND_COUNT).collect();
+    if decrypt {
+        rounds.reverse();
+    }
+    for round in rounds {
+        let mut password = Zeroizing::new(vec![round]);
+        password.extend_from_slice(passphrase.as_bytes());
+        let mut round_salt = salt.clone();
+        round_salt.extend_from_slice(&right);
+
+        let mut f = Zeroizing::new(vec![0u8; half]);
+        pbkdf2_sha256(&password, &round_salt, iterations, &mut f);
+        for (l, f) in left.iter_mut().zip(f.iter()) {
+            *l ^= f;
+        }
+        std::mem::swap(&mut left, &mut right);
+    }
+
+    let mut output = right;
+    output.extend_from_slice(&left);
+    output
+}
+
+fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) {
+    let prf = Hmac::<Sha256>::new_from_slice(password).expect("HMAC accepts any key length");
+    for (block, chunk) in out.chunks_mut(32).enumerate() {
+        let mut mac = prf.clone();
+        mac.update(salt);
+        mac.update(&(block as u32 + 1).to_be_bytes());
+        let mut u = mac.finalize().into_bytes();
+        let mut t = u;
+        for _ in 1..iterations {
+            let mut mac = prf.clone();
+            mac.update(&u);
+            u = mac.finalize().into_bytes();
+            for (t, u) in t.iter_mut().zip(u.iter()) {
+                *t ^= u;
+            }
+        }
+        chunk.copy_from_slice(&t[..chunk.len()]);
+        u.as_mut_slice().zeroize();
+        t.as_mut_slice().zeroize();
+    }
+}
+
+/// SLIP-0039 passphrases are printable ASCII so every implementation
+/// encodes them the same way
+fn check_passphrase(passphrase: &str) -> Result<()> {
+    if passphrase.bytes().all(|b| (32..=126).contains(&b)) {
+        Ok(())
+    } else {
+        Err(Error::InvalidArgument(
+            "SLIP-0039 passphrases may only contain printable ASCII characters".to_string(),
+        ))
+    }
+}
+
+// ===== Words and checksum =====
+
+fn customization(extendable: bool) -> &'static [u8] {
+    if extendable {
+        b"shamir_extendable"
+    } else {
+        b"shamir"
+    }
+}
+
+fn rs1024_polymod(values: impl Iterator<Item = u32>) -> u32 {
+    const GEN: [u32; 10] = [
+        0xE0E040, 0x1C1C080, 0x3838100, 0x7070200, 0xE0E0009, 0x1C0C2412, 0x38086C24, 0x3090FC48,
+        0x21B1F890, 0x3F3F120,
+    ];
+    let mut chk = 1u32;
+    for v in values {
+        let b = chk >> 20;
+        chk = ((chk & 0xfffff) << 10) ^ v;
+        for (i, gen) in GEN.iter().enumerate() {
+            if (b >> i) & 1 == 1 {
+                chk ^= gen;
+            }
+        }
+    }
+    chk
+}
+
+fn checksum_valid(customization: &[u8], words: &[u16]) -> bool {
+    let values = customization
+        .iter()
+        .map(|&b| b as u32)
+        .chain(words.iter().map(|&w| w as u32));
+    rs1024_polymod(values) == 1
+}
+
+fn rs1024_checksum(customization: &[u8], words: &[u16]) -> [u16; CHECKSUM_WORDS] {
+    let values = customization
+        .iter()
+        .map(|&b| b as u32)
+        .chain(words.iter().map(|&w| w as u32))
+        .chain([0; CHECKSUM_WORDS]);
+    let polymod = rs1024_polymod(values) ^ 1;
+    [
+        (polymod >> 20) as u16 & 0x3ff,
+        (polymod >> 10) as u16 & 0x3ff,
+        polymod as u16 & 0x3ff,
+    ]
+}
+
+/// Pack bytes into 10-bit words, zero padding at the front
+fn bytes_to_words(bytes: &[u8]) -> Zeroizing<Vec<u16>> {
+    let count = (bytes.len() * 8).div_ceil(RADIX_BITS);
+    let mut words = Zeroizing::new(vec![0u16; count]);
+    let padding = count * RADIX_BITS - bytes.len() * 8;
+    for (i, &byte) in bytes.iter().enumerate() {
+        for bit in 0..8 {
+            if byte & (0x80 >> bit) != 0 {
+                let position = padding + i * 8 + bit;
+                words[position / RADIX_BITS] |= 1 << (RADIX_BITS - 1 - position % RADIX_BITS);
+            }
+        }
+    }
+    words
+}
+
+/// Unpack 10-bit words into bytes, requiring the leading `padding` bits to
+/// be zero
+fn words_to_bytes(words: &[u16], padding: usize) -> Result<Zeroizing<Vec<u8>>> {
+    let bit = |position: usize| {
+        (words[position / RADIX_BITS] >> (RADIX_BITS - 1 - position % RADIX_BITS)) & 1 == 1
+    };
+    if (0..padding).any(bit) {
+        return Err(Error::InvalidShare("invalid share padding".to_string()));
+    }
+
+    let len = (words.len() * RADIX_BITS - padding) / 8;
+    let mut bytes = Zeroizing::new(vec![0u8; len]);
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        for b in 0..8 {
+            if bit(padding + i * 8 + b) {
+                *byte |= 0x80 >> b;
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+/// Look up a word, or its unique four letter prefix
+fn word_index(word: &str) -> Result<u16> {
+    let word = word.to_ascii_lowercase();
+    let prefix = word.get(..4).unwrap_or(&word);
+    let index = WORDLIST.partition_point(|w| &w[..4] < prefix);
+    match WORDLIST.get(index) {
+        Some(w) if word.len() >= 4 && w.starts_with(word.as_str()) => Ok(index as u16),
+        _ => Err(Error::InvalidShare(format!(
+            "'{}' is not a SLIP-0039 word",
+            word
+        ))),
+    }
+}
+
+/// SLIP-0039 wordlist; the first four letters of each word are unique
+#[rustfmt::skip]
+const WORDLIST: [&str; 1024] = [
+    "academic", "acid", "acne", "acquire", "acrobat", "activity", "actress", "adapt",
+    "adequate", "adjust", "admit", "adorn", "adult", "advance", "advocate", "afraid",
+    "again", "agency", "agree", "aide", "aircraft", "airline", "airport", "ajar",
+    "alarm", "album", "alcohol", "alien", "alive", "alpha", "already", "alto",
+    "aluminum", "always", "amazing", "ambition", "amount", "amuse", "analysis", "anatomy",
+    "ancestor", "ancient", "angel", "angry", "animal", "answer", "antenna", "anxiety",
+    "apart", "aquatic", "arcade", "arena", "argue", "armed", "artist", "artwork",
+    "aspect", "auction", "august", "aunt", "average", "aviation", "avoid", "award",
+    "away", "axis", "axle", "beam", "beard", "beaver", "become", "bedroom",
+    "behavior", "being", "believe", "belong", "benefit", "best", "beyond", "bike",
+    "biology", "birthday", "bishop", "black", "blanket", "blessing", "blimp", "blind",
+    "blue", "body", "bolt", "boring", "born", "both", "boundary", "bracelet",
+    "branch", "brave", "breathe", "briefing", "broken", "brother", "browser", "bucket",
+    "budget", "building", "bulb", "bulge", "bumpy", "bundle", "burden", "burning",
+    "busy", "buyer", "cage", "calcium", "camera", "campus", "canyon", "capacity",
+    "capital", "capture", "carbon", "cards", "careful", "cargo", "carpet", "carve",
+    "category", "cause", "ceiling", "center", "ceramic", "champion", "change", "charity",
+    "check", "chemical", "chest", "chew", "chubby", "cinema", "civil", "class",
+    "clay", "cleanup", "client", "climate", "clinic", "clock", "clogs", "closet",
+    "clothes", "club", "cluster", "coal", "coastal", "coding", "column", "company",
+    "corner", "costume", "counter", "course", "cover", "cowboy", "cradle", "craft",
+    "crazy", "credit", "cricket", "criminal", "crisis", "critical", "crowd", "crucial",
+    "crunch", "crush", "crystal", "cubic", "cultural", "curious", "curly", "custody",
+    "cylinder", "daisy", "damage", "dance", "darkness", "database", "daughter", "deadline",
+    "deal", "debris", "debut", "decent", "decision", "declare", "decorate", "decrease",
+    "deliver", "demand", "density", "deny", "depart", "depend", "depict", "deploy",
+    "describe", "desert", "desire", "desktop", "destroy", "detailed", "detect", "device",
+    "devote", "diagnose", "dictate", "diet", "dilemma", "diminish", "dining", "diploma",
+    "disaster", "discuss", "disease", "dish", "dismiss", "display", "distance", "dive",
+    "divorce", "document", "domain", "domestic", "dominant", "dough", "downtown", "dragon",
+    "dramatic", "dream", "dress", "drift", "drink", "drove", "drug", "dryer",
+    "duckling", "duke", "duration", "dwarf", "dynamic", "early", "earth", "easel",
+    "easy", "echo", "eclipse", "ecology", "edge", "editor", "educate", "either",
+    "elbow", "elder", "election", "elegant", "element", "elephant", "elevator", "elite",
+    "else", "email", "emerald", "emission", "emperor", "emphasis", "employer", "empty",
+    "ending", "endless", "endorse", "enemy", "energy", "enforce", "engage", "enjoy",
+    "enlarge", "entrance", "envelope", "envy", "epidemic", "episode", "equation", "equip",
+    "eraser", "erode", "escape", "estate", "estimate", "evaluate", "evening", "evidence",
+    "evil", "evoke", "exact", "example", "exceed", "exchange", "exclude", "excuse",
+    "execute", "exercise", "exhaust", "exotic", "expand", "expect", "explain", "express",
+    "extend", "extra", "eyebrow", "facility", "fact", "failure", "faint", "fake",
+    "false", "family", "famous", "fancy", "fangs", "fantasy", "fatal", "fatigue",
+    "favorite", "fawn", "fiber", "fiction", "filter", "finance", "findings", "finger",
+    "firefly", "firm", "fiscal", "fishing", "fitness", "flame", "flash", "flavor",
+    "flea", "flexible", "flip", "float", "floral", "fluff", "focus", "forbid",
+    "force", "forecast", "forget", "formal", "fortune", "forward", "founder", "fraction",
+    "fragment", "frequent", "freshman", "friar", "fridge", "friendly", "frost", "froth",
+    "frozen", "fumes", "funding", "furl", "fused", "galaxy", "game", "garbage",
+    "garden", "garlic", "gasoline", "gather", "general", "genius", "genre", "genuine",
+    "geology", "gesture", "glad", "glance", "glasses", "glen", "glimpse", "goat",
+    "golden", "graduate", "grant", "grasp", "gravity", "gray", "greatest", "grief",
+    "grill", "grin", "grocery", "gross", "group", "grownup", "grumpy", "guard",
+    "guest", "guilt", "guitar", "gums", "hairy", "hamster", "hand", "hanger",
+    "harvest", "have", "havoc", "hawk", "hazard", "headset", "health", "hearing",
+    "heat", "helpful", "herald", "herd", "hesitate", "hobo", "holiday", "holy",
+    "home", "hormone", "hospital", "hour", "huge", "human", "humidity", "hunting",
+    "husband", "hush", "husky", "hybrid", "idea", "identify", "idle", "image",
+    "impact", "imply", "improve", "impulse", "include", "income", "increase", "index",
+    "indicate", "industry", "infant", "inform", "inherit", "injury", "inmate", "insect",
+    "inside", "install", "intend", "intimate", "invasion", "involve", "iris", "island",
+    "isolate", "item", "ivory", "jacket", "jerky", "jewelry", "join", "judicial",
+    "juice", "jump", "junction", "junior", "junk", "jury", "justice", "kernel",
+    "keyboard", "kidney", "kind", "kitchen", "knife", "knit", "laden", "ladle",
+    "ladybug", "lair", "lamp", "language", "large", "laser", "laundry", "lawsuit",
+    "leader", "leaf", "learn", "leaves", "lecture", "legal", "legend", "legs",
+    "lend", "length", "level", "liberty", "library", "license", "lift", "likely",
+    "lilac", "lily", "lips", "liquid", "listen", "literary", "living", "lizard",
+    "loan", "lobe", "location", "losing", "loud", "loyalty", "luck", "lunar",
+    "lunch", "lungs", "luxury", "lying", "lyrics", "machine", "magazine", "maiden",
+    "mailman", "main", "makeup", "making", "mama", "manager", "mandate", "mansion",
+    "manual", "marathon", "march", "market", "marvel", "mason", "material", "math",
+    "maximum", "mayor", "meaning", "medal", "medical", "member", "memory", "mental",
+    "merchant", "merit", "method", "metric", "midst", "mild", "military", "mineral",
+    "minister", "miracle", "mixed", "mixture", "mobile", "modern", "modify", "moisture",
+    "moment", "morning", "mortgage", "mother", "mountain", "mouse", "move", "much",
+    "mule", "multiple", "muscle", "museum", "music", "mustang", "nail", "national",
+    "necklace", "negative", "nervous", "network", "news", "nuclear", "numb", "numerous",
+    "nylon", "oasis", "obesity", "object", "observe", "obtain", "ocean", "often",
+    "olympic", "omit", "oral", "orange", "orbit", "order", "ordinary", "organize",
+    "ounce", "oven", "overall", "owner", "paces", "pacific", "package", "paid",
+    "painting", "pajamas", "pancake", "pants", "papa", "paper", "parcel", "parking",
+    "party", "patent", "patrol", "payment", "payroll", "peaceful", "peanut", "peasant",
+    "pecan", "penalty", "pencil", "percent", "perfect", "permit", "petition", "phantom",
+    "pharmacy", "photo", "phrase", "physics", "pickup", "picture", "piece", "pile",
+    "pink", "pipeline", "pistol", "pitch", "plains", "plan", "plastic", "platform",
+    "playoff", "pleasure", "plot", "plunge", "practice", "prayer", "preach", "predator",
+    "pregnant", "premium", "prepare", "presence", "prevent", "priest", "primary", "priority",
+    "prisoner", "privacy", "prize", "problem", "process", "profile", "program", "promise",
+    "prospect", "provide", "prune", "public", "pulse", "pumps", "punish", "puny",
+    "pupal", "purchase", "purple", "python", "quantity", "quarter", "quick", "quiet",
+    "race", "racism", "radar", "railroad", "rainbow", "raisin", "random", "ranked",
+    "rapids", "raspy", "reaction", "realize", "rebound", "rebuild", "recall", "receiver",
+    "recover", "regret", "regular", "reject", "relate", "remember", "remind", "remove",
+    "render", "repair", "repeat", "replace", "require", "rescue", "research", "resident",
+    "response", "result", "retailer", "retreat", "reunion", "revenue", "review", "reward",
+    "rhyme", "rhythm", "rich", "rival", "river", "robin", "rocky", "romantic",
+    "romp", "roster", "round", "royal", "ruin", "ruler", "rumor", "sack",
+    "safari", "salary", "salon", "salt", "satisfy", "satoshi", "saver", "says",
+    "scandal", "scared", "scatter", "scene", "scholar", "science", "scout", "scramble",
+    "screw", "script", "scroll", "seafood", "season", "secret", "security", "segment",
+    "senior", "shadow", "shaft", "shame", "shaped", "sharp", "shelter", "sheriff",
+    "short", "should", "shrimp", "sidewalk", "silent", "silver", "similar", "simple",
+    "single", "sister", "skin", "skunk", "slap", "slavery", "sled", "slice",
+    "slim", "slow", "slush", "smart", "smear", "smell", "smirk", "smith",
+    "smoking", "smug", "snake", "snapshot", "sniff", "society", "software", "soldier",
+    "solution", "soul", "source", "space", "spark", "speak", "species", "spelling",
+    "spend", "spew", "spider", "spill", "spine", "spirit", "spit", "spray",
+    "sprinkle", "square", "squeeze", "stadium", "staff", "standard", "starting", "station",
+    "stay", "steady", "step", "stick", "stilt", "story", "strategy", "strike",
+    "style", "subject", "submit", "sugar", "suitable", "sunlight", "superior", "surface",
+    "surprise", "survive", "sweater", "swimming", "swing", "switch", "symbolic", "sympathy",
+    "syndrome", "system", "tackle", "tactics", "tadpole", "talent", "task", "taste",
+    "taught", "taxi", "teacher", "teammate", "teaspoon", "temple", "tenant", "tendency",
+    "tension", "terminal", "testify", "texture", "thank", "that", "theater", "theory",
+    "therapy", "thorn", "threaten", "thumb", "thunder", "ticket", "tidy", "timber",
+    "timely", "ting", "tofu", "together", "tolerate", "total", "toxic", "tracks",
+    "traffic", "training", "transfer", "trash", "traveler", "treat", "trend", "trial",
+    "tricycle", "trip", "triumph", "trouble", "true", "trust", "twice", "twin",
+    "type", "typical", "ugly", "ultimate", "umbrella", "uncover", "undergo", "unfair",
+    "unfold", "unhappy", "union", "universe", "unkind", "unknown", "unusual", "unwrap",
+    "upgrade", "upstairs", "username", "usher", "usual", "valid", "valuable", "vampire",
+    "vanish", "various", "vegan", "velvet", "venture", "verdict", "verify", "very",
+    "veteran", "vexed", "victim", "video", "view", "vintage", "violence", "viral",
+    "visitor", "visual", "vitamins", "vocal", "voice", "volume", "voter", "voting",
+    "walnut", "warmth", "warn", "watch", "wavy", "wealthy", "weapon", "webcam",
+    "welcome", "welfare", "western", "width", "wildlife", "window", "wine", "wireless",
+    "wisdom", "withdraw", "wits", "wolf", "woman", "work", "worthy", "wrap",
+    "wrist", "writing", "wrote", "year", "yelp", "yield", "yoga", "zero",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_all(mnemonics: &[&str]) -> Vec<Share> {
+        mnemonics.iter().map(|m| Share::parse(m).unwrap()).collect()
+    }
+
+    fn groups(specs: &[(u8, u8)]) -> Vec<GroupSpec> {
+        specs
+            .iter()
+            .map(|&(threshold, count)| GroupSpec { threshold, count })
+            .collect()
+    }
+
+    // Vectors from the SLIP-0039 reference test set, passphrase "TREZOR"
+    #[test]
+    fn test_reference_vectors() {
+        let single = parse_all(&["duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard"]);
+        assert!(!single[0].extendable);
+        assert_eq!(
+            hex::encode(combine(&single, "TREZOR").unwrap()),
+            "bb54aac4b89dc868ba37d9cc21b2cece"
+        );
+
+        let two_of_three = parse_all(&[
+            "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+            "shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking",
+        ]);
+        assert_eq!(
+            hex::encode(combine(&two_of_three, "TREZOR").unwrap()),
+            "b43ceb7e57a0ea8766221624d01b0864"
+        );
+
+        // One share of a 2-of-3 group is not enough
+        let err = combine(&two_of_three[..1], "TREZOR").unwrap_err();
+        assert!(matches!(err, Error::InvalidShare(_)));
+    }
+
+    #[test]
+    fn test_checksum_and_words() {
+        let mnemonic = "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard";
+        let share = Share::parse(mnemonic).unwrap();
+        assert_eq!(share.to_mnemonic().as_str(), mnemonic);
+
+        // Four letter prefixes and any case are accepted
+        let short: Vec<String> = mnemonic.split(' ').map(|w| w[..4].to_uppercase()).collect();
+        assert_eq!(
+            Share::parse(&short.join(" "))
+                .unwrap()
+                .to_mnemonic()
+                .as_str(),
+            mnemonic
+        );
+
+        // A single changed word breaks the checksum
+        let typo = mnemonic.replacen("fridge", "friend", 1);
+        assert!(matches!(Share::parse(&typo), Err(Error::InvalidShare(_))));
+        assert!(Share::parse("duckling enlarge academic").is_err());
+        assert!(Share::parse(&mnemonic.replacen("duckling", "duckbill", 1)).is_err());
+    }
+
+    #[test]
+    fn test_split_and_combine() {
+        let secret =
+            hex::decode("0c1e24e5917779d297e14d45f14e1a1a00112233445566778899aabbccddeeff")
+                .unwrap();
+        let shares = split(&secret, "", 1, &groups(&[(3, 5)]), 0).unwrap();
+        assert_eq!(shares.len(), 1);
+        assert_eq!(shares[0].len(), 5);
+
+        let decoded: Vec<Share> = shares[0]
+            .iter()
+            .map(|s| Share::parse(&s.to_mnemonic()).unwrap())
+            .collect();
+        assert_eq!(decoded[0].to_mnemonic().split(' ').count(), 33);
+
+        for picked in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+            let subset: Vec<Share> = picked.iter().map(|&i| decoded[i].clone()).collect();
+            assert_eq!(combine(&subset, "").unwrap().as_slice(), secret.as_slice());
+        }
+        assert!(combine(&decoded[..2], "").is_err());
+
+        // A wrong passphrase decrypts to a different secret
+        assert_ne!(
+            combine(&decoded[..3], "other").unwrap().as_slice(),
+            secret.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_groups() {
+        let secret = [7u8; 16];
+        let shares = split(&secret, "TREZOR", 2, &groups(&[(1, 1), (2, 3), (3, 5)]), 0).unwrap();
+
+        // Group 1 alone is not enough, group 1 plus two of group 2 is
+        let mut picked = vec![shares[0][0].clone()];
+        assert!(combine(&picked, "TREZOR").is_err());
+        picked.push(shares[1][2].clone());
+        let err = combine(&picked, "TREZOR").unwrap_err().to_string();
+        assert!(err.contains("group 2 has 1 of 2 shares"), "{}", err);
+        picked.push(shares[1][0].clone());
+        assert_eq!(combine(&picked, "TREZOR").unwrap().as_slice(), &secret);
+
+        // Groups 2 and 3, with an extra incomplete group ignored
+        let mut picked: Vec<Share> = shares[1][1..].to_vec();
+        picked.extend(shares[2][..3].iter().cloned());
+        assert_eq!(combine(&picked, "TREZOR").unwrap().as_slice(), &secret);
+
+        // Shares from another split are rejected
+        let other = split(&secret, "TREZOR", 2, &groups(&[(1, 1), (2, 3), (3, 5)]), 0).unwrap();
+        picked.push(other[0][0].clone());
+        assert!(combine(&picked, "TREZOR").is_err());
+    }
+
+    #[test]
+    fn test_parameters_are_checked() {
+        assert_eq!(
+            "3-of-5".parse::<GroupSpec>().unwrap(),
+            GroupSpec {
+                threshold: 3,
+                count: 5
+            }
+        );
+        assert_eq!(
+            "2/3".parse::<GroupSpec>().unwrap(),
+            GroupSpec {
+                threshold: 2,
+                count: 3
+            }
+        );
+        for bad in ["5-of-3", "1-of-2", "0-of-1", "3-of-17", "three"] {
+            assert!(bad.parse::<GroupSpec>().is_err(), "{}", bad);
+        }
+
+        let group = groups(&[(2, 3)]);
+        assert!(split(&[0u8; 15], "", 1, &group, 0).is_err());
+        assert!(split(&[0u8; 17], "", 1, &group, 0).is_err());
+        assert!(split(&[0u8; 16], "", 2, &group, 0).is_err());
+        assert!(split(&[0u8; 16], "\u{e9}", 1, &group, 0).is_err());
+    }
+}
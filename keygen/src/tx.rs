@@ -0,0 +1,311 @@
+//! Boundless transfer transactions
+//!
+//! Mirrors `send_transaction.py` byte for byte: the signed message is the
+//! plain concatenation `from | to | amount | nonce | fee` (decimal integers,
+//! no separators), and the submitted transaction is Python's
+//! `json.dumps(tx)` output with `signature` appended last.
+//!
+//! Files and the node see amounts as JSON integers. CLI output documents
+//! are built as [`serde_json::Value`]s, which hold at most 64-bit integers,
+//! so there amounts are decimal strings instead (see
+//! [`Transaction::to_output`]).
+
+use std::io;
+
+use serde::{Deserialize, Serialize};
+use serde_json::ser::Formatter;
+use serde_json::{json, Value};
+
+use crate::address::Address;
+use crate::error::{Error, Result};
+use crate::keys::{verify_signature, KeyType, Signer};
+
+/// Decimal places of the BLS token
+pub const DECIMALS: u32 = 18;
+
+const UNIT: u128 = 10u128.pow(DECIMALS);
+
+/// Parse a BLS amount such as `"1.5"` into base units.
+///
+/// Digits beyond 18 decimals are truncated, as in `send_transaction.py`.
+pub fn parse_amount(amount: &str) -> Result<u128> {
+    let invalid = || Error::InvalidAmount(amount.to_string());
+
+    let (whole, frac) = amount.split_once('.').unwrap_or((amount, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() || !is_digits(whole) || !is_digits(frac) {
+        return Err(invalid());
+    }
+
+    let frac = &frac[..frac.len().min(DECIMALS as usize)];
+    let frac = format!("{:0<width$}", frac, width = DECIMALS as usize);
+
+    let whole: u128 = whole.parse().map_err(|_| invalid())?;
+    let frac: u128 = frac.parse().map_err(|_| invalid())?;
+    whole
+        .checked_mul(UNIT)
+        .and_then(|units| units.checked_add(frac))
+        .ok_or_else(invalid)
+}
+
+/// Format base units as BLS, e.g. `1500000000000000000` as `"1.5"`
+pub fn format_amount(amount: u128) -> String {
+    let frac = format!("{:0>width$}", amount % UNIT, width = DECIMALS as usize);
+    let frac = frac.trim_end_matches('0');
+    format!(
+        "{}.{}",
+        amount / UNIT,
+        if frac.is_empty() { "0" } else { frac }
+    )
+}
+
+/// Unsigned transfer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transaction {
+    /// Sender address (hex)
+    pub from: String,
+
+    /// Recipient address (hex)
+    pub to: String,
+
+    /// Amount in base units
+    pub amount: u128,
+
+    /// Sender account nonce
+    pub nonce: u64,
+
+    /// Fee in base units
+    pub fee: u128,
+}
+
+impl Transaction {
+    /// Build a transfer, checking both addresses are well-formed
+    pub fn new(from: &str, to: &str, amount: u128, nonce: u64, fee: u128) -> Result<Self> {
+        from.parse::<Address>()?;
+        to.parse::<Address>()?;
+
+        Ok(Transaction {
+            from: from.to_string(),
+            to: to.to_string(),
+            amount,
+            nonce,
+            fee,
+        })
+    }
+
+    /// Form used in output documents, with amounts as decimal strings
+    pub fn to_output(&self) -> Value {
+        json!({
+            "from": self.from,
+            "to": self.to,
+            "amount": self.amount.to_string(),
+            "nonce": self.nonce,
+            "fee": self.fee.to_string(),
+        })
+    }
+
+    /// Message that is signed (`_serialize_tx` in `send_transaction.py`)
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        format!(
+            "{}{}{}{}{}",
+            self.from, self.to, self.amount, self.nonce, self.fee
+        )
+        .into_bytes()
+    }
+
+    /// Sign with the sender's keypair (or the agent holding it)
+    pub fn sign(&self, keypair: &(impl Signer + ?Sized)) -> Result<SignedTransaction> {
+        let address = keypair.address().to_hex();
+        if !address.eq_ignore_ascii_case(&self.from) {
+            return Err(Error::InvalidTransaction(format!(
+                "sender {} does not match signing wallet {}",
+                self.from, address
+            )));
+        }
+
+        let signature = keypair.sign(&self.signing_bytes())?;
+        Ok(SignedTransaction {
+            from: self.from.clone(),
+            to: self.to.clone(),
+            amount: self.amount,
+            nonce: self.nonce,
+            fee: self.fee,
+            signature: hex::encode(signature),
+        })
+    }
+}
+
+/// Transfer with its signature, in the field order the node expects
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedTransaction {
+    pub from: String,
+    pub to: String,
+    pub amount: u128,
+    pub nonce: u64,
+    pub fee: u128,
+
+    /// Signature over [`Transaction::signing_bytes`] (hex)
+    pub signature: String,
+}
+
+impl SignedTransaction {
+    /// The unsigned transaction
+    pub fn transaction(&self) -> Transaction {
+        Transaction {
+            from: self.from.clone(),
+            to: self.to.clone(),
+            amount: self.amount,
+            nonce: self.nonce,
+            fee: self.fee,
+        }
+    }
+
+    /// Form used in output documents, with amounts as decimal strings
+    pub fn to_output(&self) -> Value {
+        let mut output = self.transaction().to_output();
+        output["signature"] = json!(self.signature);
+        output
+    }
+
+    /// Check the signature and that `public_key` owns the sender address
+    pub fn verify(&self, key_type: KeyType, public_key: &[u8]) -> Result<()> {
+        let owner = Address::from_public_key(public_key).to_hex();
+        if !owner.eq_ignore_ascii_case(&self.from) {
+            return Err(Error::InvalidTransaction(format!(
+                "public key belongs to {}, not sender {}",
+                owner, self.from
+            )));
+        }
+
+        let signature = hex::decode(&self.signature)
+            .map_err(|e| Error::InvalidSignature(format!("signature is not hex: {}", e)))?;
+        verify_signature(
+            key_type,
+            public_key,
+            &self.transaction().signing_bytes(),
+            &signature,
+        )
+    }
+
+    /// Wire encoding submitted to `submit_transaction`, identical to
+    /// Python's `json.dumps(tx)`
+    pub fn to_json(&self) -> Result<String> {
+        let mut out = Vec::new();
+        let mut serializer = serde_json::Serializer::with_formatter(&mut out, PythonFormatter);
+        self.serialize(&mut serializer)
+            .map_err(|e| Error::Encoding(format!("Failed to serialize transaction: {}", e)))?;
+        String::from_utf8(out).map_err(|e| Error::Encoding(e.to_string()))
+    }
+}
+
+/// `json.dumps` default separators: `", "` and `": "`
+struct PythonFormatter;
+
+impl Formatter for PythonFormatter {
+    fn begin_array_value<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_key<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b": ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::test_support::abandon_keypair;
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(parse_amount("1").unwrap(), UNIT);
+        assert_eq!(parse_amount("1.5").unwrap(), 3 * UNIT / 2);
+        assert_eq!(parse_amount("100.0").unwrap(), 100 * UNIT);
+        assert_eq!(parse_amount("0.000000000000000001").unwrap(), 1);
+        assert_eq!(parse_amount("2.").unwrap(), 2 * UNIT);
+        // Extra precision is truncated, not rounded
+        assert_eq!(parse_amount("0.0000000000000000019").unwrap(), 1);
+
+        for bad in ["", ".5", "-1", "1.2.3", "1e3", " 1", "abc"] {
+            assert!(parse_amount(bad).is_err(), "{:?}", bad);
+        }
+        assert!(parse_amount(&u128::MAX.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(0), "0.0");
+        assert_eq!(format_amount(1), "0.000000000000000001");
+        assert_eq!(format_amount(3 * UNIT / 2), "1.5");
+        assert_eq!(format_amount(100 * UNIT), "100.0");
+        assert_eq!(format_amount(parse_amount("12.345").unwrap()), "12.345");
+    }
+
+    #[test]
+    fn test_signed_transaction_matches_python() {
+        // Produced by BoundlessWallet.from_mnemonic(ABANDON_ART).sign_transaction(...)
+        const EXPECTED: &str = r#"{"from": "10e8a4f849828a2226294c24b05db8a151563f91ec3fafdc46aaf6df85c82b22", "to": "abababababababababababababababababababababababababababababababab", "amount": 1500000000000000000, "nonce": 7, "fee": 1000000000000000, "signature": "f5cd659b570c5626f01f3d94fa6500ac16268f480ca100ff505ea1e036ce9cf8655e87e3951d319297acba5a942a85c1de7837c8fa33e7c12a23bd6d9ae48a02"}"#;
+
+        let keypair = abandon_keypair();
+        let tx = Transaction::new(
+            &keypair.address().to_hex(),
+            &"ab".repeat(32),
+            parse_amount("1.5").unwrap(),
+            7,
+            parse_amount("0.001").unwrap(),
+        )
+        .unwrap();
+
+        let signed = tx.sign(&keypair).unwrap();
+        assert_eq!(signed.to_json().unwrap(), EXPECTED);
+        signed
+            .verify(KeyType::Ed25519, &keypair.public_key_bytes())
+            .unwrap();
+
+        let parsed: SignedTransaction = serde_json::from_str(EXPECTED).unwrap();
+        assert_eq!(parsed, signed);
+    }
+
+    #[test]
+    fn test_sign_rejects_foreign_sender() {
+        let tx = Transaction::new(&"cd".repeat(32), &"ab".repeat(32), 1, 0, 0).unwrap();
+        assert!(matches!(
+            tx.sign(&abandon_keypair()),
+            Err(Error::InvalidTransaction(_))
+        ));
+        assert!(Transaction::new("not-an-address", &"ab".repeat(32), 1, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_tampered_transaction_fails_verification() {
+        let keypair = abandon_keypair();
+        let tx = Transaction::new(&keypair.address().to_hex(), &"ab".repeat(32), 5, 0, 0).unwrap();
+        let mut signed = tx.sign(&keypair).unwrap();
+        signed.amount += 1;
+
+        assert!(matches!(
+            signed.verify(KeyType::Ed25519, &keypair.public_key_bytes()),
+            Err(Error::InvalidSignature(_))
+        ));
+    }
+}
@@ -0,0 +1,586 @@
+//! Multi-wallet vaults
+//!
+//! A vault is a KeepBox variant holding many labelled wallets under one
+//! password. The encrypted payload is a list of [`VaultEntry`]s; each entry's
+//! label, address and key type are repeated in the clear-text metadata so
+//! they can be listed without the password. The whole header, entry list
+//! included, is authenticated as AES-GCM associated data.
+//!
+//! Vault files carry `"kind": "vault"` and format version 2.1.0. Use
+//! [`KeepBoxFile`] to open a file that may hold either one wallet or a vault.
+
+use std::fs;
+use std::path::Path;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::address::Address;
+use crate::error::{Error, Result};
+use crate::files;
+use crate::keepbox::{
+    decrypt_payload, encrypt_payload, probe, CryptoParams, FormatVersion, KdfCost, KeepBox,
+    WalletData,
+};
+use crate::keys::KeyType;
+
+/// Value of the `kind` field that marks a vault file
+pub const VAULT_KIND: &str = "vault";
+
+/// Longest accepted entry label, in characters
+pub const MAX_LABEL_LEN: usize = 64;
+
+// ===== Data Structures =====
+
+/// On-disk vault file
+#[derive(Serialize, Deserialize)]
+pub struct Vault {
+    pub version: FormatVersion,
+    pub kind: String,
+    pub crypto: CryptoParams,
+    pub encrypted_data: String, // Base64 encoded
+    pub metadata: VaultMetadata,
+}
+
+/// Unencrypted vault metadata, readable without the password
+#[derive(Serialize, Deserialize)]
+pub struct VaultMetadata {
+    pub created: String,
+    pub modified: String,
+    pub label: Option<String>,
+    pub entries: Vec<EntryMetadata>,
+}
+
+/// Public details of one vault entry, stored in clear and in the payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryMetadata {
+    pub label: String,
+    pub address: String,
+    pub key_type: KeyType,
+    pub created: String,
+    pub modified: String,
+}
+
+/// Decrypted vault contents
+#[derive(Default, Serialize, Deserialize)]
+pub struct VaultData {
+    pub entries: Vec<VaultEntry>,
+}
+
+/// One labelled wallet inside a vault
+#[derive(Serialize, Deserialize)]
+pub struct VaultEntry {
+    pub metadata: EntryMetadata,
+    pub wallet: WalletData,
+}
+
+/// Header fields authenticated as AES-GCM associated data
+#[derive(Serialize)]
+struct AuthenticatedHeader<'a> {
+    version: &'a str,
+    kind: &'a str,
+    crypto: &'a CryptoParams,
+    metadata: &'a VaultMetadata,
+}
+
+// ===== Entries =====
+
+impl VaultData {
+    /// Add a wallet under a new label. Labels and addresses must be unique.
+    pub fn add(&mut self, label: &str, wallet: WalletData) -> Result<&EntryMetadata> {
+        validate_label(label)?;
+        if self.entries.iter().any(|e| e.metadata.label == label) {
+            return Err(Error::DuplicateEntry(format!("labelled '{}'", label)));
+        }
+        if let Some(existing) = self
+            .entries
+            .iter()
+            .find(|e| e.metadata.address == wallet.address)
+        {
+            return Err(Error::DuplicateEntry(format!(
+                "with address {} (labelled '{}')",
+                wallet.address, existing.metadata.label
+            )));
+        }
+
+        let now = chrono::Utc::now().to_rfc3339();
+        self.entries.push(VaultEntry {
+            metadata: EntryMetadata {
+                label: label.to_string(),
+                address: wallet.address.clone(),
+                key_type: wallet.key_type,
+                created: now.clone(),
+                modified: now,
+            },
+            wallet,
+        });
+        Ok(&self.entries[self.entries.len() - 1].metadata)
+    }
+
+    /// Remove the entry with this label or address
+    pub fn remove(&mut self, selector: &str) -> Result<VaultEntry> {
+        let index = select(&self.metadata(), Some(selector))?;
+        Ok(self.entries.remove(index))
+    }
+
+    /// Give the entry with this label or address a new label
+    pub fn rename(&mut self, selector: &str, new_label: &str) -> Result<()> {
+        validate_label(new_label)?;
+        let index = select(&self.metadata(), Some(selector))?;
+        if self
+            .entries
+            .iter()
+            .enumerate()
+            .any(|(i, e)| i != index && e.metadata.label == new_label)
+        {
+            return Err(Error::DuplicateEntry(format!("labelled '{}'", new_label)));
+        }
+
+        let metadata = &mut self.entries[index].metadata;
+        metadata.label = new_label.to_string();
+        metadata.modified = chrono::Utc::now().to_rfc3339();
+        Ok(())
+    }
+
+    /// The entry with this label or address, or the only entry if `selector`
+    /// is `None`
+    pub fn get(&self, selector: Option<&str>) -> Result<&VaultEntry> {
+        Ok(&self.entries[select(&self.metadata(), selector)?])
+    }
+
+    /// Public details of every entry, in order
+    pub fn metadata(&self) -> Vec<EntryMetadata> {
+        self.entries.iter().map(|e| e.metadata.clone()).collect()
+    }
+}
+
+/// Reject labels that are empty, padded, too long, contain control
+/// characters, or could be mistaken for an address when selecting entries
+pub fn validate_label(label: &str) -> Result<()> {
+    if label.trim().is_empty() {
+        return Err(Error::InvalidLabel("label cannot be empty".to_string()));
+    }
+    if label.trim() != label {
+        return Err(Error::InvalidLabel(format!(
+            "'{}' has leading or trailing whitespace",
+            label
+        )));
+    }
+    if label.chars().count() > MAX_LABEL_LEN {
+        return Err(Error::InvalidLabel(format!(
+            "longer than {} characters",
+            MAX_LABEL_LEN
+        )));
+    }
+    if label.chars().any(char::is_control) {
+        return Err(Error::InvalidLabel(
+            "contains control characters".to_string(),
+        ));
+    }
+    if label.parse::<Address>().is_ok() {
+        return Err(Error::InvalidLabel(format!(
+            "'{}' looks like an address",
+            label
+        )));
+    }
+    Ok(())
+}
+
+/// Index of the entry whose label or address is `selector`; with no selector
+/// the vault must hold exactly one wallet
+fn select(entries: &[EntryMetadata], selector: Option<&str>) -> Result<usize> {
+    let Some(selector) = selector else {
+        return match entries.len() {
+            1 => Ok(0),
+            count => Err(Error::EntryRequired { count }),
+        };
+    };
+
+    entries
+        .iter()
+        .position(|e| e.label == selector)
+        .or_else(|| {
+            entries
+                .iter()
+                .position(|e| e.address.eq_ignore_ascii_case(selector.trim()))
+        })
+        .ok_or_else(|| Error::EntryNotFound(selector.to_string()))
+}
+
+// ===== Vault File =====
+
+impl Vault {
+    /// Create an empty vault encrypted under `password`
+    pub fn create(password: &str, label: Option<String>, kdf: &KdfCost) -> Result<Self> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let mut vault = Vault {
+            version: FormatVersion::VAULT,
+            kind: VAULT_KIND.to_string(),
+            crypto: CryptoParams::new(kdf),
+            encrypted_data: String::new(),
+            metadata: VaultMetadata {
+                created: now.clone(),
+                modified: now,
+                label,
+                entries: Vec::new(),
+            },
+        };
+        vault.encrypt(&VaultData::default(), password, kdf)?;
+        Ok(vault)
+    }
+
+    /// Decrypt every entry using the file's own KDF parameters
+    pub fn unseal(&self, password: &str) -> Result<VaultData> {
+        let (ciphertext, salt, nonce) = self.crypto.decode(&self.encrypted_data)?;
+        let data: VaultData = decrypt_payload(
+            &ciphertext,
+            password,
+            &salt,
+            &nonce,
+            &self.crypto.kdf_params.cost(),
+            &self.associated_data()?,
+        )?;
+
+        // The header is authenticated, so this only fails if a writer
+        // sealed a payload without updating the entry list
+        if data.metadata() != self.metadata.entries {
+            return Err(Error::MetadataMismatch(
+                "vault entry list differs from the encrypted entries".to_string(),
+            ));
+        }
+        Ok(data)
+    }
+
+    /// Re-encrypt `data` with a fresh salt and nonce, keeping the current
+    /// KDF cost, and refresh the clear-text entry list
+    pub fn reseal(&mut self, data: &VaultData, password: &str) -> Result<()> {
+        let kdf = self.crypto.kdf_params.cost();
+        self.reseal_with(data, password, &kdf)
+    }
+
+    /// Re-encrypt `data` with a new password and KDF cost
+    pub fn reseal_with(&mut self, data: &VaultData, password: &str, kdf: &KdfCost) -> Result<()> {
+        self.metadata.entries = data.metadata();
+        self.metadata.modified = chrono::Utc::now().to_rfc3339();
+        self.encrypt(data, password, kdf)
+    }
+
+    /// Address of the entry `selector` picks, without decrypting
+    pub fn address(&self, selector: Option<&str>) -> Result<&str> {
+        Ok(&self.metadata.entries[select(&self.metadata.entries, selector)?].address)
+    }
+
+    /// Check that the algorithms are supported, the KDF parameters are in
+    /// bounds and the base64 fields decode, without a password
+    pub fn check_encoding(&self) -> Result<()> {
+        self.crypto.decode(&self.encrypted_data).map(|_| ())
+    }
+
+    /// Read and parse a vault file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let json =
+            fs::read_to_string(path).map_err(|e| Error::io("Failed to read KeepBox file", e))?;
+        Self::from_json(&json)
+    }
+
+    /// Parse a vault, rejecting single-wallet KeepBox files
+    pub fn from_json(json: &str) -> Result<Self> {
+        let (version, kind) = probe(json)?;
+        if kind.as_deref() != Some(VAULT_KIND) {
+            return Err(Error::Unsupported(
+                "single-wallet KeepBox where a vault is expected".to_string(),
+            ));
+        }
+        if version < FormatVersion::VAULT {
+            return Err(Error::Unsupported(format!(
+                "vault in format {} (vaults start at {})",
+                version,
+                FormatVersion::VAULT
+            )));
+        }
+
+        serde_json::from_str(json)
+            .map_err(|e| Error::Encoding(format!("Failed to parse vault: {}", e)))
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Encoding(format!("Failed to serialize vault: {}", e)))
+    }
+
+    /// Create or replace `path` crash-safely (see
+    /// [`files::replace_private_file`]). The written file must parse and
+    /// decrypt with `password` to the entries listed in its metadata before
+    /// it replaces the original.
+    pub fn save_atomic(&self, path: impl AsRef<Path>, password: &str) -> Result<()> {
+        files::replace_private_file(path.as_ref(), self.to_json()?.as_bytes(), |written| {
+            let written = Vault::from_json(&String::from_utf8_lossy(written))?;
+            written.unseal(password)?;
+            if written.metadata.entries != self.metadata.entries {
+                return Err(Error::MetadataMismatch(
+                    "written vault holds different entries".to_string(),
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    fn encrypt(&mut self, data: &VaultData, password: &str, kdf: &KdfCost) -> Result<()> {
+        let (salt, nonce) = self.crypto.refresh(kdf)?;
+        let aad = self.associated_data()?;
+        let ciphertext = encrypt_payload(data, password, &salt, &nonce, kdf, &aad)?;
+        self.encrypted_data = BASE64.encode(ciphertext);
+        Ok(())
+    }
+
+    fn associated_data(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec(&AuthenticatedHeader {
+            version: self.version.as_str(),
+            kind: &self.kind,
+            crypto: &self.crypto,
+            metadata: &self.metadata,
+        })
+        .map_err(|e| Error::Encoding(format!("Failed to encode vault header: {}", e)))
+    }
+}
+
+// ===== Either Kind =====
+
+/// A KeepBox file holding a single wallet or a vault
+pub enum KeepBoxFile {
+    Wallet(KeepBox),
+    Vault(Vault),
+}
+
+impl KeepBoxFile {
+    /// Read a KeepBox file of either kind
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let json =
+            fs::read_to_string(path).map_err(|e| Error::io("Failed to read KeepBox file", e))?;
+        Self::from_json(&json)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        match probe(json)?.1.as_deref() {
+            None => KeepBox::from_json(json).map(KeepBoxFile::Wallet),
+            Some(VAULT_KIND) => Vault::from_json(json).map(KeepBoxFile::Vault),
+            Some(kind) => Err(Error::Unsupported(format!("KeepBox kind '{}'", kind))),
+        }
+    }
+
+    /// Address of the wallet `selector` picks, without decrypting. A
+    /// single-wallet file matches its own label or address, or no selector.
+    pub fn address(&self, selector: Option<&str>) -> Result<&str> {
+        match self {
+            KeepBoxFile::Wallet(keepbox) => {
+                if let Some(selector) = selector {
+                    let label = keepbox.metadata.label.as_deref();
+                    if label != Some(selector)
+                        && !keepbox
+                            .metadata
+                            .address
+                            .eq_ignore_ascii_case(selector.trim())
+                    {
+                        return Err(Error::EntryNotFound(selector.to_string()));
+                    }
+                }
+                Ok(&keepbox.metadata.address)
+            }
+            KeepBoxFile::Vault(vault) => vault.address(selector),
+        }
+    }
+
+    /// Decrypt the wallet `selector` picks (see [`KeepBoxFile::address`])
+    pub fn unseal_wallet(&self, password: &str, selector: Option<&str>) -> Result<WalletData> {
+        match self {
+            KeepBoxFile::Wallet(keepbox) => {
+                self.address(selector)?;
+                keepbox.unseal(password)
+            }
+            KeepBoxFile::Vault(vault) => {
+                let mut data = vault.unseal(password)?;
+                let index = select(&data.metadata(), selector)?;
+                Ok(data.entries.swap_remove(index).wallet)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::derivation::Derivation;
+    use crate::mnemonic::Mnemonic;
+
+    const PASSWORD: &str = "Test-Password-2025";
+
+    // Minimum accepted cost keeps the tests fast
+    const TEST_KDF: KdfCost = KdfCost {
+        memory_cost: 8 * 1024,
+        time_cost: 1,
+        parallelism: 1,
+    };
+
+    fn test_wallet() -> WalletData {
+        WalletData::from_mnemonic(
+            &Mnemonic::generate().unwrap(),
+            "",
+            KeyType::Ed25519,
+            &Derivation::Legacy,
+        )
+        .unwrap()
+    }
+
+    fn vault_with(labels: &[&str]) -> (Vault, Vec<String>) {
+        let mut vault = Vault::create(PASSWORD, Some("miners".into()), &TEST_KDF).unwrap();
+        let mut data = vault.unseal(PASSWORD).unwrap();
+        let addresses = labels
+            .iter()
+            .map(|label| data.add(label, test_wallet()).unwrap().address.clone())
+            .collect();
+        vault.reseal(&data, PASSWORD).unwrap();
+        (vault, addresses)
+    }
+
+    #[test]
+    fn test_vault_roundtrip() {
+        let (vault, addresses) = vault_with(&["miner-01", "miner-02", "miner-03"]);
+        assert_eq!(vault.version, FormatVersion::VAULT);
+
+        // Entries are listed without the password
+        let reloaded = Vault::from_json(&vault.to_json().unwrap()).unwrap();
+        let listed: Vec<_> = reloaded
+            .metadata
+            .entries
+            .iter()
+            .map(|e| &e.address)
+            .collect();
+        assert_eq!(listed, addresses.iter().collect::<Vec<_>>());
+        assert_eq!(reloaded.address(Some("miner-02")).unwrap(), addresses[1]);
+
+        let data = reloaded.unseal(PASSWORD).unwrap();
+        assert_eq!(data.entries.len(), 3);
+        for entry in &data.entries {
+            entry.wallet.verify("").unwrap();
+        }
+        assert!(matches!(
+            reloaded.unseal("Wrong-Password-2025"),
+            Err(Error::DecryptionFailed)
+        ));
+    }
+
+    #[test]
+    fn test_entry_list_is_authenticated() {
+        let (vault, _) = vault_with(&["a", "b"]);
+
+        let tampered: [fn(&mut Vault); 4] = [
+            |v| v.metadata.entries[0].label = "hot".into(),
+            |v| v.metadata.entries[1].address = "ab".repeat(32),
+            |v| {
+                v.metadata.entries.pop();
+            },
+            |v| v.metadata.label = None,
+        ];
+        for tamper in tampered {
+            let mut copy = Vault::from_json(&vault.to_json().unwrap()).unwrap();
+            tamper(&mut copy);
+            assert!(matches!(
+                copy.unseal(PASSWORD),
+                Err(Error::DecryptionFailed)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_add_remove_rename() {
+        let mut data = VaultData::default();
+        let wallet = test_wallet();
+        let address = wallet.address.clone();
+        data.add("miner-01", wallet).unwrap();
+
+        assert!(matches!(
+            data.add("miner-01", test_wallet()),
+            Err(Error::DuplicateEntry(_))
+        ));
+        let copy: WalletData =
+            serde_json::from_str(&serde_json::to_string(&data.get(None).unwrap().wallet).unwrap())
+                .unwrap();
+        assert!(matches!(
+            data.add("miner-02", copy),
+            Err(Error::DuplicateEntry(_))
+        ));
+
+        data.add("miner-02", test_wallet()).unwrap();
+        assert!(matches!(
+            data.get(None),
+            Err(Error::EntryRequired { count: 2 })
+        ));
+        assert!(matches!(
+            data.rename("miner-02", "miner-01"),
+            Err(Error::DuplicateEntry(_))
+        ));
+
+        // Entries are selected by label or (case-insensitive) address
+        data.rename(&address.to_uppercase(), "cold").unwrap();
+        assert_eq!(data.get(Some("cold")).unwrap().wallet.address, address);
+
+        let removed = data.remove("cold").unwrap();
+        assert_eq!(removed.metadata.address, address);
+        assert!(matches!(data.remove("cold"), Err(Error::EntryNotFound(_))));
+        assert_eq!(data.get(None).unwrap().metadata.label, "miner-02");
+    }
+
+    #[test]
+    fn test_invalid_labels() {
+        let long = "x".repeat(MAX_LABEL_LEN + 1);
+        let address = "ab".repeat(32);
+        for label in ["", "  ", " padded", "tab\tbed", &long, &address] {
+            assert!(
+                matches!(validate_label(label), Err(Error::InvalidLabel(_))),
+                "{:?}",
+                label
+            );
+        }
+        validate_label("miner-01 (rack 3)").unwrap();
+    }
+
+    #[test]
+    fn test_keepbox_file_kinds() {
+        let (vault, addresses) = vault_with(&["a", "b"]);
+        let vault_json = vault.to_json().unwrap();
+        assert!(vault_json.contains("\"version\": \"2.1.0\""));
+        assert!(vault_json.contains("\"kind\": \"vault\""));
+
+        // Single-wallet readers refuse vaults rather than misreading them
+        assert!(matches!(
+            KeepBox::from_json(&vault_json),
+            Err(Error::Unsupported(_))
+        ));
+
+        let file = KeepBoxFile::from_json(&vault_json).unwrap();
+        assert!(matches!(
+            file.address(None),
+            Err(Error::EntryRequired { count: 2 })
+        ));
+        let wallet = file.unseal_wallet(PASSWORD, Some(&addresses[1])).unwrap();
+        assert_eq!(wallet.address, addresses[1]);
+
+        let single = test_wallet();
+        let keepbox =
+            KeepBox::seal_with(&single, PASSWORD, Some("cold".into()), &TEST_KDF).unwrap();
+        let keepbox_json = keepbox.to_json().unwrap();
+        assert!(matches!(
+            Vault::from_json(&keepbox_json),
+            Err(Error::Unsupported(_))
+        ));
+
+        let file = KeepBoxFile::from_json(&keepbox_json).unwrap();
+        assert_eq!(file.address(None).unwrap(), single.address);
+        assert_eq!(file.address(Some("cold")).unwrap(), single.address);
+        assert!(matches!(
+            file.unseal_wallet(PASSWORD, Some("hot")),
+            Err(Error::EntryNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,297 @@
+//! Plaintext wallet files produced by `boundless-wallet-gen`
+
+use serde::{Deserialize, Serialize};
+
+use crate::derivation::Derivation;
+use crate::error::Result;
+use crate::keys::{KeyType, Keypair, PublicKeyInfo};
+use crate::mnemonic::{Language, Mnemonic};
+
+/// Wallet output structure (matches Boundless conventions)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletOutput {
+    /// BIP39 mnemonic phrase (12-24 words)
+    pub mnemonic: String,
+
+    /// Wordlist of the mnemonic
+    #[serde(default)]
+    pub language: Language,
+
+    /// Public key (hex-encoded)
+    pub public_key: String,
+
+    /// Boundless address (hex-encoded SHA3-256 of public key)
+    pub address: String,
+
+    /// Private key (hex-encoded) - ONLY included if --show-private flag is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+
+    /// Key type used ("Ed25519", "ML-DSA-44" or "Ed25519+ML-DSA-44")
+    pub key_type: KeyType,
+
+    /// Component public keys; two entries for hybrid wallets, where
+    /// `public_key` holds the composite encoding
+    #[serde(default)]
+    pub keys: Vec<PublicKeyInfo>,
+
+    /// Key derivation scheme ("legacy" or a SLIP-0010 path)
+    #[serde(default)]
+    pub derivation: Derivation,
+}
+
+impl WalletOutput {
+    /// Build the wallet file contents for a mnemonic
+    pub fn from_mnemonic(
+        mnemonic: &Mnemonic,
+        passphrase: &str,
+        key_type: KeyType,
+        derivation: &Derivation,
+        show_private: bool,
+    ) -> Result<Self> {
+        let keypair = Keypair::derive(&mnemonic.to_seed(passphrase), key_type, derivation)?;
+
+        Ok(WalletOutput {
+            mnemonic: mnemonic.phrase(),
+            language: mnemonic.language(),
+            public_key: hex::encode(keypair.public_key_bytes()),
+            address: keypair.address().to_hex(),
+            private_key: show_private.then(|| hex::encode(keypair.secret_bytes().as_slice())),
+            key_type: keypair.key_type(),
+            keys: keypair.public_keys(),
+            derivation: derivation.clone(),
+        })
+    }
+}
+
+/// Generate a brand new wallet with a `word_count` word mnemonic in
+/// `language`
+pub fn generate_wallet(
+    show_private: bool,
+    passphrase: Option<&str>,
+    key_type: KeyType,
+    derivation: &Derivation,
+    language: Language,
+    word_count: usize,
+) -> Result<WalletOutput> {
+    let mnemonic = Mnemonic::generate_in(language, word_count)?;
+    WalletOutput::from_mnemonic(
+        &mnemonic,
+        passphrase.unwrap_or(""),
+        key_type,
+        derivation,
+        show_private,
+    )
+}
+
+/// Restore wallet from existing mnemonic, detecting its language unless
+/// one is given
+pub fn restore_wallet(
+    mnemonic_phrase: &str,
+    language: Option<Language>,
+    show_private: bool,
+    passphrase: Option<&str>,
+    key_type: KeyType,
+    derivation: &Derivation,
+) -> Result<WalletOutput> {
+    let mnemonic = Mnemonic::parse_with(language, mnemonic_phrase)?;
+    WalletOutput::from_mnemonic(
+        &mnemonic,
+        passphrase.unwrap_or(""),
+        key_type,
+        derivation,
+        show_private,
+    )
+}
+
+/// One row of `derive --count` output
+#[derive(Debug, Serialize)]
+pub struct DerivedAccount {
+    pub account: u32,
+    pub path: String,
+    pub public_key: String,
+    pub address: String,
+}
+
+/// Derive `count` consecutive Boundless accounts starting at `start`
+pub fn derive_accounts(
+    mnemonic_phrase: &str,
+    language: Option<Language>,
+    passphrase: Option<&str>,
+    key_type: KeyType,
+    start: u32,
+    count: u32,
+) -> Result<Vec<DerivedAccount>> {
+    let mnemonic = Mnemonic::parse_with(language, mnemonic_phrase)?;
+    let seed = mnemonic.to_seed(passphrase.unwrap_or(""));
+
+    (start..start.saturating_add(count))
+        .map(|account| {
+            let derivation = Derivation::account(account)?;
+            let keypair = Keypair::derive(&seed, key_type, &derivation)?;
+            Ok(DerivedAccount {
+                account,
+                path: derivation.to_string(),
+                public_key: hex::encode(keypair.public_key_bytes()),
+                address: keypair.address().to_hex(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::derive_address;
+    use crate::keys::test_support::ABANDON_ART;
+
+    #[test]
+    fn test_mnemonic_deterministic() {
+        let wallet1 = restore_wallet(
+            ABANDON_ART,
+            None,
+            false,
+            None,
+            KeyType::Ed25519,
+            &Derivation::Legacy,
+        )
+        .unwrap();
+        let wallet2 = restore_wallet(
+            ABANDON_ART,
+            None,
+            false,
+            None,
+            KeyType::Ed25519,
+            &Derivation::Legacy,
+        )
+        .unwrap();
+
+        assert_eq!(wallet1.public_key, wallet2.public_key);
+        assert_eq!(wallet1.address, wallet2.address);
+        assert!(wallet1.private_key.is_none());
+    }
+
+    #[test]
+    fn test_address_format() {
+        let wallet = generate_wallet(
+            false,
+            None,
+            KeyType::Ed25519,
+            &Derivation::Legacy,
+            Language::English,
+            24,
+        )
+        .unwrap();
+
+        assert_eq!(wallet.address.len(), 64);
+        assert!(hex::decode(&wallet.address).is_ok());
+
+        let pubkey_bytes = hex::decode(&wallet.public_key).unwrap();
+        assert_eq!(wallet.address, derive_address(&pubkey_bytes));
+    }
+
+    #[test]
+    fn test_passphrase_changes_address() {
+        let plain = restore_wallet(
+            ABANDON_ART,
+            None,
+            false,
+            None,
+            KeyType::Ed25519,
+            &Derivation::Legacy,
+        )
+        .unwrap();
+        let protected = restore_wallet(
+            ABANDON_ART,
+            None,
+            false,
+            Some("extra words"),
+            KeyType::Ed25519,
+            &Derivation::Legacy,
+        )
+        .unwrap();
+        assert_ne!(plain.address, protected.address);
+    }
+
+    #[test]
+    fn test_derive_accounts_matches_restore() {
+        let accounts = derive_accounts(ABANDON_ART, None, None, KeyType::Ed25519, 2, 3).unwrap();
+        assert_eq!(accounts.len(), 3);
+        assert_eq!(accounts[0].path, "m/44'/777'/2'/0'");
+
+        let restored = restore_wallet(
+            ABANDON_ART,
+            None,
+            false,
+            None,
+            KeyType::Ed25519,
+            &Derivation::account(3).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(accounts[1].address, restored.address);
+        assert_eq!(restored.derivation.to_string(), "m/44'/777'/3'/0'");
+    }
+
+    #[cfg(feature = "pqc")]
+    #[test]
+    fn test_ml_dsa_44_wallet_roundtrip() {
+        let generated = generate_wallet(
+            false,
+            None,
+            KeyType::MlDsa44,
+            &Derivation::Legacy,
+            Language::English,
+            24,
+        )
+        .unwrap();
+        assert_eq!(generated.key_type, KeyType::MlDsa44);
+        assert_eq!(generated.public_key.len(), 1312 * 2);
+
+        let json = serde_json::to_string(&generated).unwrap();
+        assert!(json.contains("\"key_type\":\"ML-DSA-44\""));
+
+        let restored = restore_wallet(
+            &generated.mnemonic,
+            None,
+            false,
+            None,
+            KeyType::MlDsa44,
+            &Derivation::Legacy,
+        )
+        .unwrap();
+        assert_eq!(restored.address, generated.address);
+        assert_eq!(
+            restored.address,
+            derive_address(&hex::decode(&restored.public_key).unwrap())
+        );
+    }
+
+    #[cfg(feature = "pqc")]
+    #[test]
+    fn test_hybrid_wallet_lists_component_keys() {
+        let derivation = Derivation::account(0).unwrap();
+        let hybrid =
+            restore_wallet(ABANDON_ART, None, false, None, KeyType::Hybrid, &derivation).unwrap();
+        let ed25519 = restore_wallet(
+            ABANDON_ART,
+            None,
+            false,
+            None,
+            KeyType::Ed25519,
+            &derivation,
+        )
+        .unwrap();
+
+        assert_eq!(hybrid.keys.len(), 2);
+        assert_eq!(hybrid.keys[0].key_type, KeyType::Ed25519);
+        assert_eq!(hybrid.keys[0].public_key, ed25519.public_key);
+        assert_eq!(hybrid.keys[1].public_key.len(), 1312 * 2);
+        assert_eq!(
+            hybrid.address,
+            derive_address(&hex::decode(&hybrid.public_key).unwrap())
+        );
+
+        let json = serde_json::to_string(&hybrid).unwrap();
+        assert!(json.contains("\"key_type\":\"Ed25519+ML-DSA-44\""));
+    }
+}
@@ -0,0 +1,240 @@
+//! End-to-end tests of the `boundless-wallet` node commands against the
+//! in-memory mock node
+
+use std::path::PathBuf;
+
+use assert_cmd::Command;
+use boundless_wallet::mock_node::{Fault, MockNode};
+use boundless_wallet::{Keypair, Mnemonic};
+use predicates::prelude::*;
+use serde_json::Value;
+use tempfile::TempDir;
+
+const PASSWORD: &str = "Str0ng!Passw0rd#1";
+const BLS: u128 = 1_000_000_000_000_000_000;
+
+fn mnemonic() -> String {
+    format!("{}art", "abandon ".repeat(23))
+}
+
+/// Command for `bin` that cannot pick up a key agent or password from the
+/// environment
+fn command(bin: &str) -> Command {
+    let mut command = Command::cargo_bin(bin).unwrap();
+    command.env_remove("BOUNDLESS_AGENT_SOCK").env_remove("BOUNDLESS_KEYSTORE_PASSWORD");
+    command
+}
+
+/// KeepBox holding the test mnemonic's wallet, and a node where it holds
+/// `balance`; returns the sender's address
+fn funded(balance: u128) -> (TempDir, PathBuf, MockNode, String) {
+    let dir = TempDir::new().unwrap();
+    let keepbox = dir.path().join("wallet.keepbox");
+    command("boundless-keepbox")
+        .args(["import", "--mnemonic", &mnemonic(), "--password-stdin", "--kdf-preset", "interactive", "-o"])
+        .arg(&keepbox)
+        .write_stdin(format!("{}\n{}\n", PASSWORD, PASSWORD))
+        .assert()
+        .success();
+
+    let keypair = Keypair::from_seed(&Mnemonic::parse(&mnemonic()).unwrap().to_seed(""));
+    let node = MockNode::start().unwrap();
+    let address = node.add_account(&keypair.public_key_bytes(), balance).unwrap();
+    (dir, keepbox, node, address)
+}
+
+fn json_data(output: &[u8]) -> Value {
+    let document: Value = serde_json::from_slice(output).unwrap();
+    document["data"].clone()
+}
+
+fn send(keepbox: &PathBuf, node: &MockNode, amount: &str) -> Command {
+    let mut command = command("boundless-wallet");
+    command
+        .args(["send", "--yes", "--password-stdin", "--no-agent", "--output-format", "json", "--keepbox"])
+        .arg(keepbox)
+        .args(["--to", &"bb".repeat(32), "--amount", amount, "--fee", "0.1"])
+        .args(node.rpc_args())
+        .write_stdin(format!("{}\n", PASSWORD));
+    command
+}
+
+#[test]
+fn test_queries() {
+    // Above 2^64 base units, so reported as a decimal string
+    let (_dir, keepbox, node, address) = funded(30 * BLS);
+    node.set_block_number(41);
+
+    let output = command("boundless-wallet")
+        .args(["balance", "--output-format", "json", "--keepbox"])
+        .arg(&keepbox)
+        .args(node.rpc_args())
+        .output()
+        .unwrap();
+    let data = json_data(&output.stdout);
+    assert_eq!((data["address"].as_str(), data["balance_bls"].as_str()), (Some(address.as_str()), Some("30.0")));
+    assert_eq!(data["balance"], "30000000000000000000");
+
+    command("boundless-wallet")
+        .args(["nonce", "--address", &address.to_uppercase()])
+        .args(node.rpc_args())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("{}: 0", address)));
+    command("boundless-wallet")
+        .args(["block-number"])
+        .args(node.rpc_args())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Block number: 41"));
+    command("boundless-wallet")
+        .args(["tx-status", &"00".repeat(32)])
+        .args(node.rpc_args())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("does not know"));
+}
+
+#[test]
+fn test_send() {
+    let (_dir, keepbox, node, address) = funded(2 * BLS);
+
+    let output = send(&keepbox, &node, "1.5").output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    let data = json_data(&output.stdout);
+    assert_eq!(data["sent"], true);
+    assert_eq!(node.balance(&address), 4 * BLS / 10);
+    assert_eq!((node.nonce(&address), node.balance(&"bb".repeat(32))), (1, 3 * BLS / 2));
+
+    let hash = data["hash"].as_str().unwrap();
+    command("boundless-wallet")
+        .args(["tx-status", hash])
+        .args(node.rpc_args())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Status: confirmed"));
+
+    // The balance no longer covers it: refused before anything is signed
+    send(&keepbox, &node, "1").assert().code(1).stdout(predicate::str::contains("invalid_transaction"));
+    let submitted = node.calls().iter().filter(|call| call.method == "submit_transaction").count();
+    assert_eq!(submitted, 1);
+}
+
+#[test]
+fn test_node_failures() {
+    let (_dir, keepbox, node, address) = funded(2 * BLS);
+
+    // Read-only calls are retried
+    node.fail("*", Fault::Http(503), 2);
+    command("boundless-wallet")
+        .args(["block-number", "--rpc-retries", "2"])
+        .args(node.rpc_args())
+        .assert()
+        .success();
+    assert_eq!(node.calls().len(), 3);
+
+    // A submission whose response is lost is not resubmitted
+    node.fail("submit_transaction", Fault::LoseResponse, 1);
+    send(&keepbox, &node, "1")
+        .assert()
+        .code(11)
+        .stdout(predicate::str::contains("node_unreachable"));
+    let submitted = node.calls().iter().filter(|call| call.method == "submit_transaction").count();
+    assert_eq!((submitted, node.nonce(&address)), (1, 1));
+
+    // Rejections are reported with the node's message
+    let mut stale = send(&keepbox, &node, "0.1");
+    stale.args(["--nonce", "0"]);
+    stale
+        .assert()
+        .code(11)
+        .stdout(predicate::str::contains("rpc_error").and(predicate::str::contains("invalid nonce")));
+
+    drop(node);
+    command("boundless-wallet")
+        .args(["block-number", "--rpc-url", "http://127.0.0.1:1", "--rpc-retries", "0"])
+        .assert()
+        .code(11)
+        .stderr(predicate::str::contains("node_unreachable"));
+}
+
+#[test]
+fn test_split_signing() {
+    let (dir, keepbox, node, address) = funded(2 * BLS);
+    let (unsigned, signed) = (dir.path().join("tx_unsigned.json"), dir.path().join("tx_signed.json"));
+    let recipient = "bb".repeat(32);
+
+    command("boundless-wallet")
+        .args(["tx", "create", "--from", &address, "--to", &recipient, "--amount", "1", "--output"])
+        .arg(&unsigned)
+        .args(node.rpc_args())
+        .assert()
+        .success();
+    command("boundless-wallet")
+        .args(["tx", "sign", "--password-stdin", "--no-agent", "--keepbox"])
+        .arg(&keepbox)
+        .arg("--tx")
+        .arg(&unsigned)
+        .arg("--output")
+        .arg(&signed)
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .success();
+
+    let broadcast = |path: &PathBuf| {
+        let mut command = command("boundless-wallet");
+        command.args(["tx", "broadcast", "--yes", "--tx"]).arg(path).args(node.rpc_args());
+        command
+    };
+
+    // Edits made in transit are caught before anything reaches the node
+    let json = std::fs::read_to_string(&signed).unwrap();
+    let tampered = dir.path().join("tampered.json");
+    std::fs::write(&tampered, json.replace(&recipient, &"cc".repeat(32))).unwrap();
+    broadcast(&tampered).assert().code(4).stderr(predicate::str::contains("integrity_mismatch"));
+    assert!(node.transactions().is_empty());
+
+    broadcast(&signed).assert().success();
+    assert_eq!((node.nonce(&address), node.balance(&recipient)), (1, BLS));
+}
+
+#[test]
+fn test_split_signing_cbor() {
+    let (dir, keepbox, node, address) = funded(2 * BLS);
+    let (unsigned, signed) = (dir.path().join("tx_unsigned.cbor"), dir.path().join("tx_signed.cbor"));
+
+    command("boundless-wallet")
+        .args(["tx", "create", "--encoding", "cbor", "--from", &address, "--to", &"bb".repeat(32)])
+        .args(["--amount", "1", "--output"])
+        .arg(&unsigned)
+        .args(node.rpc_args())
+        .assert()
+        .success();
+    command("boundless-wallet")
+        .args(["tx", "sign", "--password-stdin", "--no-agent", "--keepbox"])
+        .arg(&keepbox)
+        .arg("--tx")
+        .arg(&unsigned)
+        .arg("--output")
+        .arg(&signed)
+        .write_stdin(format!("{}\n", PASSWORD))
+        .assert()
+        .success();
+
+    // Signed in the encoding it was created in
+    assert!(matches!(std::fs::read(&signed).unwrap()[0], 0xa0..=0xbf));
+    let inspect = command("boundless-wallet")
+        .args(["tx", "inspect", "--output-format", "json", "--tx"])
+        .arg(&signed)
+        .assert()
+        .success();
+    assert_eq!(json_data(&inspect.get_output().stdout)["signed"], true);
+
+    command("boundless-wallet")
+        .args(["tx", "broadcast", "--yes", "--tx"])
+        .arg(&signed)
+        .args(node.rpc_args())
+        .assert()
+        .success();
+    assert_eq!(node.nonce(&address), 1);
+}